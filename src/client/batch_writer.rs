@@ -0,0 +1,385 @@
+//! Buffering writer collecting points and writing them in batches.
+//!
+//! [`InfluxDbBatchWriter`] buffers write queries and writes them using
+//! [`batch_write`](crate::client::InfluxDbQueryExecutor::batch_write) once `capacity` points are buffered.
+//! Points still buffered at the end need to be written using [`close`](crate::client::batch_writer::InfluxDbBatchWriter::close),
+//! which is the only reliable way of not losing them.
+//!
+//! Dropping a writer with buffered points loses them, unless flushing on drop is enabled using
+//! [`with_flush_on_drop`](crate::client::batch_writer::InfluxDbBatchWriter::with_flush_on_drop). As `Drop` can neither
+//! wait for the flush nor report its errors, flushing on drop is best-effort: the flush is spawned onto the
+//! current-thread runtime the writer is dropped in, and the points are lost if there is none or the flush fails.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use futures::Future;
+//! use influxdb::client::batch_writer::InfluxDbBatchWriter;
+//! use influxdb::client::InfluxDbClient;
+//! use influxdb::query::{InfluxDbQuery, Timestamp};
+//!
+//! let client = InfluxDbClient::new("http://localhost:8086", "test");
+//! let mut writer = InfluxDbBatchWriter::new(client, 1_000);
+//! for hour in 0..5_000 {
+//!     let point = InfluxDbQuery::write_query(Timestamp::HOURS(hour), "weather").add_field("temperature", 82);
+//!     writer.write(point).wait().unwrap();
+//! }
+//! writer.close().wait().unwrap();
+//! ```
+//!
+//! Points can also be written from a stream using
+//! [`write_stream`](crate::client::batch_writer::InfluxDbBatchWriter::write_stream), which only polls the
+//! stream for further points once the previous batch has been written, so a slow server slows down the producer.
+//! A batch is written once `capacity` points are buffered, the stream ends, or the
+//! [flush interval](crate::client::batch_writer::InfluxDbBatchWriter::with_flush_interval) has passed since the
+//! first point of the batch was buffered.
+//!
+//! ```rust,no_run
+//! use futures::{stream, Stream};
+//! use influxdb::client::batch_writer::InfluxDbBatchWriter;
+//! use influxdb::client::InfluxDbClient;
+//! use influxdb::query::{InfluxDbQuery, Timestamp};
+//! use std::time::Duration;
+//!
+//! let client = InfluxDbClient::new("http://localhost:8086", "test");
+//! let points = stream::iter_ok((0..5_000).map(|hour| {
+//!     InfluxDbQuery::write_query(Timestamp::HOURS(hour), "weather").add_field("temperature", 82)
+//! }));
+//! let writes = InfluxDbBatchWriter::new(client, 1_000)
+//!     .with_flush_interval(Duration::from_secs(1))
+//!     .write_stream(points)
+//!     .for_each(|results| Ok(println!("wrote a batch of {} points", results.len())));
+//! tokio::runtime::current_thread::Runtime::new().unwrap().block_on(writes).unwrap();
+//! ```
+
+use futures::{future, Async, Future, Poll, Stream};
+use tokio::runtime::current_thread::TaskExecutor;
+use tokio::timer::Delay;
+
+use std::mem;
+use std::time::{Duration, Instant};
+
+use crate::client::InfluxDbQueryExecutor;
+use crate::error::InfluxDbError;
+use crate::query::write_query::InfluxDbWriteQuery;
+
+/// Results of the requests sent by a flush, see [`batch_write`](crate::client::InfluxDbClient::batch_write)
+type FlushFuture =
+    Box<dyn Future<Item = Vec<Result<String, InfluxDbError>>, Error = InfluxDbError>>;
+
+/// Writer buffering points and writing them in batches of `capacity` points
+#[derive(Debug)]
+pub struct InfluxDbBatchWriter<E: InfluxDbQueryExecutor> {
+    executor: E,
+    buffer: Vec<InfluxDbWriteQuery>,
+    capacity: usize,
+    flush_on_drop: bool,
+    flush_interval: Option<Duration>,
+}
+
+impl<E: InfluxDbQueryExecutor> InfluxDbBatchWriter<E> {
+    /// Creates a writer writing to `executor` once `capacity` points are buffered
+    pub fn new(executor: E, capacity: usize) -> Self {
+        InfluxDbBatchWriter {
+            executor,
+            buffer: Vec::with_capacity(capacity),
+            capacity: capacity.max(1),
+            flush_on_drop: false,
+            flush_interval: None,
+        }
+    }
+
+    /// Flushes the buffered points when the writer is dropped, see the [module documentation](crate::client::batch_writer)
+    /// for the limitations. Prefer [`close`](crate::client::batch_writer::InfluxDbBatchWriter::close).
+    pub fn with_flush_on_drop(mut self, flush_on_drop: bool) -> Self {
+        self.flush_on_drop = flush_on_drop;
+        self
+    }
+
+    /// Writes a partial batch of [`write_stream`](crate::client::batch_writer::InfluxDbBatchWriter::write_stream)
+    /// once `interval` has passed since its first point was buffered. This needs a tokio runtime to drive the timer.
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = Some(interval);
+        self
+    }
+
+    /// Returns the number of buffered points
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns whether no points are buffered
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Buffers a point, flushing the buffer if it is full. The returned future resolves to the results
+    /// of the flush, which are empty if the buffer was not flushed.
+    pub fn write(&mut self, query: InfluxDbWriteQuery) -> FlushFuture {
+        self.buffer.push(query);
+        if self.buffer.len() >= self.capacity {
+            self.flush()
+        } else {
+            Box::new(future::ok(vec![]))
+        }
+    }
+
+    /// Writes the buffered points, leaving the buffer empty
+    pub fn flush(&mut self) -> FlushFuture {
+        if self.buffer.is_empty() {
+            return Box::new(future::ok(vec![]));
+        }
+        let points = mem::take(&mut self.buffer);
+        self.executor.batch_write(&points)
+    }
+
+    /// Writes the remaining buffered points and closes the writer
+    pub fn close(mut self) -> FlushFuture {
+        self.flush()
+    }
+
+    /// Writes the points of `points` in batches, starting with the points already buffered, and closes the writer
+    /// once the stream ends. The returned stream yields the results of every batch, see
+    /// [`batch_write`](crate::client::InfluxDbClient::batch_write), and needs to be polled for the points to be written.
+    /// Further points are only taken from `points` after the previous batch has been written.
+    pub fn write_stream<S>(
+        mut self,
+        points: S,
+    ) -> impl Stream<Item = Vec<Result<String, InfluxDbError>>, Error = InfluxDbError>
+    where
+        S: Stream<Item = InfluxDbWriteQuery, Error = InfluxDbError>,
+    {
+        let batches = Batches {
+            points: points.fuse(),
+            buffer: mem::take(&mut self.buffer),
+            capacity: self.capacity,
+            flush_interval: self.flush_interval,
+            first_buffered: None,
+            deadline: None,
+        };
+        batches.and_then(move |batch| self.executor.batch_write(&batch))
+    }
+}
+
+/// Stream adapter collecting points into batches of at most `capacity` points
+struct Batches<S: Stream> {
+    points: futures::stream::Fuse<S>,
+    buffer: Vec<InfluxDbWriteQuery>,
+    capacity: usize,
+    flush_interval: Option<Duration>,
+    first_buffered: Option<Instant>,
+    deadline: Option<Delay>,
+}
+
+impl<S: Stream> Batches<S> {
+    fn take_batch(&mut self) -> Vec<InfluxDbWriteQuery> {
+        self.first_buffered = None;
+        self.deadline = None;
+        mem::take(&mut self.buffer)
+    }
+}
+
+impl<S> Stream for Batches<S>
+where
+    S: Stream<Item = InfluxDbWriteQuery, Error = InfluxDbError>,
+{
+    type Item = Vec<InfluxDbWriteQuery>;
+    type Error = InfluxDbError;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        loop {
+            match self.points.poll()? {
+                Async::Ready(Some(point)) => {
+                    if self.buffer.is_empty() {
+                        self.first_buffered = Some(Instant::now());
+                    }
+                    self.buffer.push(point);
+                    if self.buffer.len() >= self.capacity {
+                        return Ok(Async::Ready(Some(self.take_batch())));
+                    }
+                }
+                Async::Ready(None) if self.buffer.is_empty() => return Ok(Async::Ready(None)),
+                Async::Ready(None) => return Ok(Async::Ready(Some(self.take_batch()))),
+                Async::NotReady => break,
+            }
+        }
+
+        if let (Some(interval), false) = (self.flush_interval, self.buffer.is_empty()) {
+            let started = *self.first_buffered.get_or_insert_with(Instant::now);
+            let deadline = self
+                .deadline
+                .get_or_insert_with(|| Delay::new(started + interval));
+            let elapsed = deadline
+                .poll()
+                .map_err(|err| InfluxDbError::ProtocolError {
+                    error: format!("timer error: {}", err),
+                })?;
+            if elapsed.is_ready() {
+                return Ok(Async::Ready(Some(self.take_batch())));
+            }
+        }
+        Ok(Async::NotReady)
+    }
+}
+
+impl<E: InfluxDbQueryExecutor> Drop for InfluxDbBatchWriter<E> {
+    fn drop(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let points = self.buffer.len();
+        if !self.flush_on_drop {
+            warn!(
+                "dropping batch writer with {} unwritten points, use close() to write them",
+                points
+            );
+            return;
+        }
+
+        let flush = self.flush().then(move |result| {
+            if let Err(error) = result {
+                warn!(
+                    "failed to flush {} points of dropped batch writer: {}",
+                    points, error
+                );
+            }
+            Ok(())
+        });
+        if TaskExecutor::current()
+            .spawn_local(Box::new(flush))
+            .is_err()
+        {
+            warn!(
+                "could not flush {} points of dropped batch writer outside of a current-thread runtime",
+                points
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InfluxDbBatchWriter;
+    use crate::client::mock::MockInfluxDbClient;
+    use crate::query::{InfluxDbQuery, Timestamp};
+
+    use futures::sync::mpsc;
+    use futures::{future, stream, Future, Stream};
+    use std::time::{Duration, Instant};
+    use tokio::runtime::current_thread::Runtime;
+    use tokio::timer::Delay;
+
+    fn point(hour: usize) -> crate::query::write_query::InfluxDbWriteQuery {
+        InfluxDbQuery::write_query(Timestamp::HOURS(hour), "weather").add_field("temperature", 82)
+    }
+
+    #[test]
+    fn test_flush_when_full() {
+        let mock = MockInfluxDbClient::new();
+        let mut writer = InfluxDbBatchWriter::new(&mock, 2);
+
+        assert!(writer.write(point(1)).wait().unwrap().is_empty());
+        assert_eq!(writer.len(), 1);
+        assert_eq!(writer.write(point(2)).wait().unwrap().len(), 2);
+        assert!(writer.is_empty());
+
+        assert_eq!(
+            mock.queries(),
+            vec!["weather temperature=82i 1", "weather temperature=82i 2"]
+        );
+    }
+
+    #[test]
+    fn test_close_flushes_remaining_points() {
+        let mock = MockInfluxDbClient::new();
+        let mut writer = InfluxDbBatchWriter::new(&mock, 10);
+        for hour in 1..=3 {
+            writer.write(point(hour)).wait().unwrap();
+        }
+        assert!(mock.queries().is_empty());
+
+        assert_eq!(writer.close().wait().unwrap().len(), 3);
+        assert_eq!(
+            mock.queries(),
+            vec![
+                "weather temperature=82i 1",
+                "weather temperature=82i 2",
+                "weather temperature=82i 3"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_drop() {
+        let mock = MockInfluxDbClient::new();
+        let mut writer = InfluxDbBatchWriter::new(&mock, 10);
+        writer.write(point(1)).wait().unwrap();
+        drop(writer);
+        assert!(
+            mock.queries().is_empty(),
+            "Points were flushed without flush on drop"
+        );
+
+        let mut rt = Runtime::new().unwrap();
+        let mut writer = InfluxDbBatchWriter::new(&mock, 10).with_flush_on_drop(true);
+        writer.write(point(2)).wait().unwrap();
+        rt.block_on(future::lazy(move || {
+            drop(writer);
+            Ok::<(), ()>(())
+        }))
+        .unwrap();
+        rt.run().unwrap();
+        assert_eq!(mock.queries(), vec!["weather temperature=82i 2"]);
+    }
+
+    #[test]
+    fn test_write_stream_in_batches() {
+        let mock = MockInfluxDbClient::new();
+        let mut writer = InfluxDbBatchWriter::new(&mock, 2);
+        writer.write(point(1)).wait().unwrap();
+
+        let batches = writer
+            .write_stream(stream::iter_ok((2..=5).map(point)))
+            .collect()
+            .wait()
+            .unwrap();
+
+        let sizes: Vec<usize> = batches.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![2, 2, 1]);
+        assert_eq!(mock.queries().len(), 5);
+        assert_eq!(mock.queries()[0], "weather temperature=82i 1");
+        assert_eq!(mock.queries()[4], "weather temperature=82i 5");
+    }
+
+    #[test]
+    fn test_write_stream_flush_interval() {
+        let mock = MockInfluxDbClient::new();
+        let (sender, receiver) = mpsc::unbounded();
+        let mut rt = Runtime::new().unwrap();
+
+        for hour in 1..=3 {
+            sender.unbounded_send(point(hour)).unwrap();
+        }
+        rt.spawn(
+            Delay::new(Instant::now() + Duration::from_millis(200))
+                .map_err(|_| ())
+                .map(move |_| {
+                    sender.unbounded_send(point(4)).unwrap();
+                }),
+        );
+
+        let points = receiver.map_err(|_| unreachable!());
+        let batches = rt
+            .block_on(
+                InfluxDbBatchWriter::new(&mock, 10)
+                    .with_flush_interval(Duration::from_millis(20))
+                    .write_stream(points)
+                    .collect(),
+            )
+            .unwrap();
+
+        let sizes: Vec<usize> = batches.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![3, 1]);
+        assert_eq!(mock.queries().len(), 4);
+    }
+}
@@ -0,0 +1,73 @@
+//! Hooks collecting metrics about the requests sent by [`InfluxDbClient`](crate::client::InfluxDbClient).
+//!
+//! Implement [`InfluxDbMetrics`] to feed request counts, latencies and response sizes into a metrics
+//! system like Prometheus, and register it using [`with_metrics`](crate::client::InfluxDbClient::with_metrics).
+//!
+//! # Examples
+//!
+//! ```rust
+//! use influxdb::client::metrics::InfluxDbMetrics;
+//! use influxdb::client::InfluxDbClient;
+//! use reqwest::StatusCode;
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! #[derive(Default)]
+//! struct ByteCounter(AtomicUsize);
+//!
+//! impl InfluxDbMetrics for ByteCounter {
+//!     fn on_request_end(&self, _status: Option<StatusCode>, _duration: Duration, bytes: usize) {
+//!         self.0.fetch_add(bytes, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! let counter = Arc::new(ByteCounter::default());
+//! let _client = InfluxDbClient::new("http://localhost:8086", "test").with_metrics(counter.clone());
+//! ```
+
+use reqwest::StatusCode;
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Receives a call for every request the client sends
+pub trait InfluxDbMetrics: Send + Sync {
+    /// Called right before a request is sent
+    fn on_request_start(&self) {}
+
+    /// Called once a request has finished, successfully or not.
+    ///
+    /// `status` is the HTTP status of the response, `None` if no response was received, e.g. because the
+    /// connection was refused. `duration` is the time since the request was sent and `bytes` the size of
+    /// the response body read.
+    fn on_request_end(&self, status: Option<StatusCode>, duration: Duration, bytes: usize);
+}
+
+#[derive(Clone)]
+/// Internal representation of the metrics hook registered with a client
+pub(crate) struct MetricsHook(Arc<dyn InfluxDbMetrics>);
+
+impl MetricsHook {
+    pub(crate) fn new(metrics: Arc<dyn InfluxDbMetrics>) -> Self {
+        MetricsHook(metrics)
+    }
+
+    /// Reports the start of a request, returning the instant the request started
+    pub(crate) fn start(&self) -> Instant {
+        self.0.on_request_start();
+        Instant::now()
+    }
+
+    /// Reports the end of a request started at `started`
+    pub(crate) fn end(&self, started: Instant, status: Option<StatusCode>, bytes: usize) {
+        self.0.on_request_end(status, started.elapsed(), bytes);
+    }
+}
+
+impl fmt::Debug for MetricsHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MetricsHook").finish()
+    }
+}
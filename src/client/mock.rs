@@ -0,0 +1,131 @@
+//! In-memory test double for [`InfluxDbClient`](crate::client::InfluxDbClient).
+//!
+//! [`MockInfluxDbClient`] records every query it is asked to send and answers with
+//! stubbed responses instead of talking to an InfluxDB server.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use futures::Future;
+//! use influxdb::client::mock::MockInfluxDbClient;
+//! use influxdb::client::InfluxDbQueryExecutor;
+//! use influxdb::query::InfluxDbQuery;
+//!
+//! let mock = MockInfluxDbClient::new().with_response(Ok("{\"results\":[]}".to_string()));
+//!
+//! let result = mock.query(&InfluxDbQuery::raw_read_query("SELECT * FROM weather")).wait();
+//!
+//! assert_eq!(result.unwrap(), "{\"results\":[]}");
+//! assert_eq!(mock.queries(), vec!["SELECT * FROM weather"]);
+//! ```
+
+use futures::{future, Future};
+
+use std::any::Any;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::client::InfluxDbQueryExecutor;
+use crate::error::InfluxDbError;
+use crate::query::InfluxDbQuery;
+
+/// Test double recording queries and returning stubbed responses
+#[derive(Debug, Default)]
+pub struct MockInfluxDbClient {
+    queries: Mutex<Vec<String>>,
+    responses: Mutex<VecDeque<Result<String, InfluxDbError>>>,
+}
+
+impl MockInfluxDbClient {
+    /// Instantiates a new [`MockInfluxDbClient`](crate::client::mock::MockInfluxDbClient) without any stubbed responses
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a response returned for a query. Responses are returned in the order they were added.
+    /// Once all responses have been used up, queries succeed with an empty response.
+    pub fn with_response(self, response: Result<String, InfluxDbError>) -> Self {
+        self.responses.lock().unwrap().push_back(response);
+        self
+    }
+
+    /// Returns the built queries sent so far, in the order they were sent
+    pub fn queries(&self) -> Vec<String> {
+        self.queries.lock().unwrap().clone()
+    }
+}
+
+impl InfluxDbQueryExecutor for MockInfluxDbClient {
+    fn query<Q>(&self, q: &Q) -> Box<dyn Future<Item = String, Error = InfluxDbError>>
+    where
+        Q: Any + InfluxDbQuery,
+    {
+        let query = match q.build() {
+            Ok(query) => query,
+            Err(err) => {
+                let error = InfluxDbError::InvalidQueryError {
+                    error: format!("{}", err),
+                };
+                return Box::new(future::err(error));
+            }
+        };
+        self.queries.lock().unwrap().push(query.get());
+
+        let response = self
+            .responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| Ok(String::new()));
+        Box::new(future::result(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::mock::MockInfluxDbClient;
+    use crate::client::InfluxDbQueryExecutor;
+    use crate::error::InfluxDbError;
+    use crate::query::{InfluxDbQuery, Timestamp};
+    use futures::Future;
+
+    #[test]
+    fn test_records_queries() {
+        let mock = MockInfluxDbClient::new();
+        let write_query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field("temperature", 82);
+        let read_query = InfluxDbQuery::raw_read_query("SELECT * FROM weather");
+
+        assert!(mock.query(&write_query).wait().is_ok());
+        assert!(mock.query(&read_query).wait().is_ok());
+
+        assert_eq!(
+            mock.queries(),
+            vec!["weather temperature=82i 11", "SELECT * FROM weather"]
+        );
+    }
+
+    #[test]
+    fn test_stubbed_responses() {
+        let mock = MockInfluxDbClient::new()
+            .with_response(Ok("first".to_string()))
+            .with_response(Err(InfluxDbError::AuthorizationError));
+        let read_query = InfluxDbQuery::raw_read_query("SELECT * FROM weather");
+
+        assert_eq!(mock.query(&read_query).wait().unwrap(), "first");
+        match mock.query(&read_query).wait() {
+            Err(InfluxDbError::AuthorizationError) => {}
+            _ => panic!("Should be an AuthorizationError"),
+        }
+        assert_eq!(mock.query(&read_query).wait().unwrap(), "");
+    }
+
+    #[test]
+    fn test_invalid_query_not_recorded() {
+        let mock = MockInfluxDbClient::new();
+        let invalid_query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather");
+
+        assert!(mock.query(&invalid_query).wait().is_err());
+        assert!(mock.queries().is_empty());
+    }
+}
@@ -15,19 +15,112 @@
 //! assert_eq!(client.database_name(), "test");
 //! ```
 
+pub mod batch_writer;
+pub mod metrics;
+pub mod mock;
+mod sampling;
+pub mod version;
+
 use futures::{Future, Stream};
-use reqwest::r#async::{Client, Decoder};
-use reqwest::{StatusCode, Url};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER};
+use reqwest::r#async::{Chunk, Client, ClientBuilder, Decoder, RequestBuilder, Response};
+use reqwest::{Identity, RedirectPolicy, StatusCode, Url};
 
+use std::collections::BTreeMap;
+use std::fmt;
 use std::mem;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use crate::error::InfluxDbError;
+use crate::client::metrics::{InfluxDbMetrics, MetricsHook};
+use crate::client::sampling::Sampler;
+use crate::client::version::ServerVersion;
+use crate::error::{ConnectionErrorKind, InfluxDbError};
+use crate::query::create_database::InfluxDbCreateDatabaseQuery;
 use crate::query::read_query::InfluxDbReadQuery;
-use crate::query::write_query::InfluxDbWriteQuery;
-use crate::query::InfluxDbQuery;
+use crate::query::write_query::{chunk_batch, InfluxDbType, InfluxDbWriteQuery};
+use crate::query::{quote_identifier, requires_post, InfluxDbQuery, Method, Precision, Timestamp};
 
 use std::any::Any;
 
+/// Executes queries against InfluxDB.
+///
+/// Implemented by [`InfluxDbClient`](crate::client::InfluxDbClient) and by the test double
+/// [`MockInfluxDbClient`](crate::client::mock::MockInfluxDbClient), so code sending queries can be
+/// tested without a running InfluxDB server.
+///
+/// # Examples
+///
+/// ```rust
+/// use futures::Future;
+/// use influxdb::client::mock::MockInfluxDbClient;
+/// use influxdb::client::InfluxDbQueryExecutor;
+/// use influxdb::error::InfluxDbError;
+/// use influxdb::query::{InfluxDbQuery, Timestamp};
+///
+/// fn record_temperature<E: InfluxDbQueryExecutor>(executor: &E) -> Result<String, InfluxDbError> {
+///     let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather").add_field("temperature", 82);
+///     executor.query(&query).wait()
+/// }
+///
+/// let mock = MockInfluxDbClient::new();
+/// assert!(record_temperature(&mock).is_ok());
+/// assert_eq!(mock.queries(), vec!["weather temperature=82i 11"]);
+/// ```
+pub trait InfluxDbQueryExecutor {
+    /// Sends a [`InfluxDbReadQuery`](crate::query::read_query::InfluxDbReadQuery) or [`InfluxDbWriteQuery`](crate::query::write_query::InfluxDbWriteQuery),
+    /// see [`InfluxDbClient::query`](crate::client::InfluxDbClient::query)
+    fn query<Q>(&self, q: &Q) -> Box<dyn Future<Item = String, Error = InfluxDbError>>
+    where
+        Q: Any + InfluxDbQuery;
+
+    /// Writes a batch of [`InfluxDbWriteQuery`](crate::query::write_query::InfluxDbWriteQuery), see
+    /// [`InfluxDbClient::batch_write`](crate::client::InfluxDbClient::batch_write).
+    /// By default, every query is sent on its own.
+    fn batch_write(
+        &self,
+        queries: &[InfluxDbWriteQuery],
+    ) -> Box<dyn Future<Item = Vec<Result<String, InfluxDbError>>, Error = InfluxDbError>> {
+        let results: Vec<_> = queries
+            .iter()
+            .map(|query| self.query(query).then(Ok))
+            .collect();
+        Box::new(futures::future::join_all(results))
+    }
+}
+
+impl InfluxDbQueryExecutor for InfluxDbClient {
+    fn query<Q>(&self, q: &Q) -> Box<dyn Future<Item = String, Error = InfluxDbError>>
+    where
+        Q: Any + InfluxDbQuery,
+    {
+        InfluxDbClient::query(self, q)
+    }
+
+    fn batch_write(
+        &self,
+        queries: &[InfluxDbWriteQuery],
+    ) -> Box<dyn Future<Item = Vec<Result<String, InfluxDbError>>, Error = InfluxDbError>> {
+        InfluxDbClient::batch_write(self, queries)
+    }
+}
+
+impl<E: InfluxDbQueryExecutor> InfluxDbQueryExecutor for &E {
+    fn query<Q>(&self, q: &Q) -> Box<dyn Future<Item = String, Error = InfluxDbError>>
+    where
+        Q: Any + InfluxDbQuery,
+    {
+        (*self).query(q)
+    }
+
+    fn batch_write(
+        &self,
+        queries: &[InfluxDbWriteQuery],
+    ) -> Box<dyn Future<Item = Vec<Result<String, InfluxDbError>>, Error = InfluxDbError>> {
+        (*self).batch_write(queries)
+    }
+}
+
 #[derive(Clone, Debug)]
 /// Internal Authentication representation
 pub(crate) struct InfluxDbAuthentication {
@@ -35,12 +128,77 @@ pub(crate) struct InfluxDbAuthentication {
     pub password: String,
 }
 
+#[derive(Clone)]
+/// Internal representation of a client certificate and its private key in PKCS #12 format
+pub(crate) struct InfluxDbIdentity {
+    pub pkcs12: Vec<u8>,
+    pub password: String,
+}
+
+impl fmt::Debug for InfluxDbIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InfluxDbIdentity").finish()
+    }
+}
+
 #[derive(Clone, Debug)]
 /// Internal Representation of a Client
 pub struct InfluxDbClient {
     url: String,
     database: String,
     auth: Option<InfluxDbAuthentication>,
+    max_batch_size: usize,
+    split_precisions: bool,
+    auto_create_database: bool,
+    sampler: Option<Sampler>,
+    safe_mode: bool,
+    api_v2: bool,
+    org: Option<String>,
+    bucket: Option<String>,
+    token: Option<String>,
+    server_version: Option<ServerVersion>,
+    default_precision: Option<Precision>,
+    identity: Option<InfluxDbIdentity>,
+    accept_invalid_certs: bool,
+    tcp_nodelay: bool,
+    base_path: String,
+    metrics: Option<MetricsHook>,
+    result_format: ResultFormat,
+    write_content_type: String,
+    max_error_body_size: usize,
+}
+
+/// Default `Content-Type` of write requests, see [`with_write_content_type`](crate::client::InfluxDbClient::with_write_content_type)
+pub const DEFAULT_WRITE_CONTENT_TYPE: &str = "text/plain; charset=utf-8";
+
+/// Default maximum size of a batch write request body in bytes, matching the default `max-body-size` of InfluxDB
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 25_000_000;
+
+/// Default maximum size in bytes of the error message kept from the body of an error response,
+/// see [`with_max_error_body_size`](crate::client::InfluxDbClient::with_max_error_body_size)
+pub const DEFAULT_MAX_ERROR_BODY_SIZE: usize = 4096;
+
+/// Results of the write requests sent to each database by [`write_to_databases`](crate::client::InfluxDbClient::write_to_databases)
+pub type DatabaseWriteResults = BTreeMap<String, Vec<Result<String, InfluxDbError>>>;
+
+/// Format InfluxDB returns the results of read queries in, see [`with_result_format`](crate::client::InfluxDbClient::with_result_format)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ResultFormat {
+    /// `application/json`, the default
+    #[default]
+    Json,
+    /// `application/csv`, which is more compact for large results with few series
+    Csv,
+}
+
+impl ResultFormat {
+    /// Returns the media type requested in the `Accept` header
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ResultFormat::Json => "application/json",
+            ResultFormat::Csv => "application/csv",
+        }
+    }
 }
 
 impl Into<Vec<(String, String)>> for InfluxDbClient {
@@ -91,6 +249,25 @@ impl InfluxDbClient {
             url: url.to_string(),
             database: database.to_string(),
             auth: None,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            split_precisions: false,
+            auto_create_database: false,
+            sampler: None,
+            safe_mode: false,
+            api_v2: false,
+            org: None,
+            bucket: None,
+            token: None,
+            server_version: None,
+            default_precision: None,
+            identity: None,
+            accept_invalid_certs: false,
+            tcp_nodelay: false,
+            base_path: String::new(),
+            metrics: None,
+            result_format: ResultFormat::default(),
+            write_content_type: DEFAULT_WRITE_CONTENT_TYPE.to_string(),
+            max_error_body_size: DEFAULT_MAX_ERROR_BODY_SIZE,
         }
     }
 
@@ -120,51 +297,129 @@ impl InfluxDbClient {
         self
     }
 
-    /// Returns the name of the database the client is using
-    pub fn database_name(&self) -> &str {
-        &self.database
+    /// Returns a copy of the [`InfluxDbClient`](crate::client::InfluxDbClient) using other credentials,
+    /// leaving the credentials of this client untouched.
+    ///
+    /// This is useful to run a single request as another user, e.g. an administrative statement,
+    /// without setting up a second client by hand.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:9086", "test").with_auth("reader", "password");
+    /// let _future = client
+    ///     .as_user("admin", "password")
+    ///     .query(&InfluxDbQuery::raw_read_query("DROP MEASUREMENT weather"));
+    /// ```
+    pub fn as_user<S1, S2>(&self, username: S1, password: S2) -> Self
+    where
+        S1: ToString,
+        S2: ToString,
+    {
+        self.clone().with_auth(username, password)
     }
 
-    /// Returns the URL of the InfluxDB installation the client is using
-    pub fn database_url(&self) -> &str {
-        &self.url
+    /// Authenticates the client using a client certificate, for InfluxDB servers requiring mutual TLS.
+    ///
+    /// The certificate and its private key need to be given as a DER-encoded PKCS #12 archive, which can be
+    /// created from PEM files using `openssl pkcs12 -export -in client.crt -inkey client.key -out client.p12`.
+    /// Fails with a [`ConnectionError`](crate::error::InfluxDbError::ConnectionError) of kind
+    /// [`Tls`](crate::error::ConnectionErrorKind::Tls) if the archive can not be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::client::InfluxDbClient;
+    ///
+    /// let pkcs12 = std::fs::read("client.p12").unwrap();
+    /// let _client = InfluxDbClient::new("https://localhost:8086", "test")
+    ///     .with_identity_pkcs12(&pkcs12, "password")
+    ///     .unwrap();
+    /// ```
+    pub fn with_identity_pkcs12(
+        mut self,
+        pkcs12: &[u8],
+        password: &str,
+    ) -> Result<Self, InfluxDbError> {
+        let identity = InfluxDbIdentity {
+            pkcs12: pkcs12.to_vec(),
+            password: password.to_string(),
+        };
+        load_identity(&identity)?;
+        self.identity = Some(identity);
+        Ok(self)
     }
 
-    /// Pings the InfluxDB Server
+    /// Disables the verification of the TLS certificate of the server if `accept_invalid_certs` is `true`.
     ///
-    /// Returns a tuple of build type and version number
-    pub fn ping(&self) -> impl Future<Item = (String, String), Error = InfluxDbError> {
-        Client::new()
-            .get(format!("{}/ping", self.url).as_str())
-            .send()
-            .map(|res| {
-                let build = res
-                    .headers()
-                    .get("X-Influxdb-Build")
-                    .unwrap()
-                    .to_str()
-                    .unwrap();
-                let version = res
-                    .headers()
-                    .get("X-Influxdb-Version")
-                    .unwrap()
-                    .to_str()
-                    .unwrap();
-
-                (String::from(build), String::from(version))
-            })
-            .map_err(|err| InfluxDbError::ProtocolError {
-                error: format!("{}", err),
-            })
+    /// # Warning
+    ///
+    /// This is insecure: any certificate is trusted, including expired ones and ones issued for other hosts,
+    /// which allows anyone able to intercept the connection to read and alter all requests, credentials included.
+    /// Only use it for local development against servers using self-signed certificates. Certificates are
+    /// verified by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    ///
+    /// let _client = InfluxDbClient::new("https://localhost:8086", "test").danger_accept_invalid_certs(true);
+    /// ```
+    pub fn danger_accept_invalid_certs(mut self, accept_invalid_certs: bool) -> Self {
+        self.accept_invalid_certs = accept_invalid_certs;
+        self
     }
 
-    /// Sends a [`InfluxDbReadQuery`](crate::query::read_query::InfluxDbReadQuery) or [`InfluxDbWriteQuery`](crate::query::write_query::InfluxDbWriteQuery) to the InfluxDB Server.
+    /// Sets `TCP_NODELAY` on the connections to the server if `tcp_nodelay` is `true`, sending small writes right away
+    /// instead of waiting to coalesce them with later data. Disabled by default, like in reqwest.
     ///
-    /// A version capable of parsing the returned string is available under the [serde_integration](crate::integrations::serde_integration)
+    /// The TCP keep-alive interval can not be configured, as reqwest 0.9 does not expose it.
     ///
-    /// # Arguments
+    /// # Examples
     ///
-    ///  * `q`: Query of type [`InfluxDbReadQuery`](crate::query::read_query::InfluxDbReadQuery) or [`InfluxDbWriteQuery`](crate::query::write_query::InfluxDbWriteQuery)
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    ///
+    /// let _client = InfluxDbClient::new("http://localhost:8086", "test").with_tcp_nodelay(true);
+    /// ```
+    pub fn with_tcp_nodelay(mut self, tcp_nodelay: bool) -> Self {
+        self.tcp_nodelay = tcp_nodelay;
+        self
+    }
+
+    /// Creates the HTTP client sending the requests, configured with the TLS settings of the client
+    pub(crate) fn http_client(&self) -> Result<Client, InfluxDbError> {
+        self.http_client_with(ClientBuilder::new())
+    }
+
+    fn http_client_with(&self, mut builder: ClientBuilder) -> Result<Client, InfluxDbError> {
+        if let Some(identity) = &self.identity {
+            builder = builder.identity(load_identity(identity)?);
+        }
+        if self.accept_invalid_certs {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        if self.tcp_nodelay {
+            builder = builder.tcp_nodelay();
+        }
+        builder = builder.redirect(redirect_policy());
+        builder.build().map_err(InfluxDbError::connection_error)
+    }
+
+    /// Returns the hook requests are reported to, see [`with_metrics`](crate::client::InfluxDbClient::with_metrics)
+    pub(crate) fn metrics_hook(&self) -> Option<MetricsHook> {
+        self.metrics.clone()
+    }
+
+    /// Lets [`batch_write`](crate::client::InfluxDbClient::batch_write) accept batches mixing timestamp precisions.
+    ///
+    /// Such batches are grouped by precision, in the order each precision first occurs, and every group is sent
+    /// using its own requests with the matching `precision` parameter. This costs an extra request per precision,
+    /// and the groups are no longer written in the order of the batch. Batches mixing precisions are rejected otherwise.
     ///
     /// # Examples
     ///
@@ -172,186 +427,3008 @@ impl InfluxDbClient {
     /// use influxdb::client::InfluxDbClient;
     /// use influxdb::query::{InfluxDbQuery, Timestamp};
     ///
-    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
-    /// let _future = client.query(
-    ///     &InfluxDbQuery::write_query(Timestamp::NOW, "weather")
-    ///         .add_field("temperature", 82)
-    /// );
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test").with_split_precisions(true);
+    /// let _future = client.batch_write(&[
+    ///     InfluxDbQuery::write_query(Timestamp::MILLISECONDS(1_568_106_310_123), "weather").add_field("temperature", 82),
+    ///     InfluxDbQuery::write_query(Timestamp::NANOSECONDS(1_568_106_370_000_000_000), "weather").add_field("temperature", 84),
+    /// ]);
     /// ```
-    /// # Errors
+    pub fn with_split_precisions(mut self, split_precisions: bool) -> Self {
+        self.split_precisions = split_precisions;
+        self
+    }
+
+    /// Creates the database and retries the write once if [`query`](crate::client::InfluxDbClient::query) fails to write
+    /// a point with [`DatabaseNotFound`](crate::error::InfluxDbError::DatabaseNotFound), which InfluxDB returns for
+    /// missing databases if `auto-create` is disabled in its configuration.
     ///
-    /// If the function can not finish the query,
-    /// a [`InfluxDbError`] variant will be returned.
+    /// The error is only detected with the `use-serde` feature, and neither reads nor
+    /// [`batch_write`](crate::client::InfluxDbClient::batch_write) are retried.
     ///
-    /// [`InfluxDbError`]: enum.InfluxDbError.html
-    pub fn query<Q>(&self, q: &Q) -> Box<dyn Future<Item = String, Error = InfluxDbError>>
-    where
-        Q: Any + InfluxDbQuery,
-    {
-        use futures::future;
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::Timestamp;
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test").with_auto_create_database(true);
+    /// let _future = client.write_point("weather", "temperature", 82, Timestamp::NOW);
+    /// ```
+    pub fn with_auto_create_database(mut self, auto_create_database: bool) -> Self {
+        self.auto_create_database = auto_create_database;
+        self
+    }
 
-        let query = match q.build() {
-            Err(err) => {
-                let error = InfluxDbError::InvalidQueryError {
-                    error: format!("{}", err),
-                };
-                return Box::new(future::err::<String, InfluxDbError>(error));
-            }
-            Ok(query) => query,
-        };
+    /// Writes only a random sample of the points, keeping each with probability `rate` between `0.0` and `1.0`, e.g. `0.1`
+    /// for about every tenth point. This reduces the load of very frequent metrics. Points are written by default.
+    ///
+    /// Sampled out points are dropped without sending a request, succeeding like a write would. This applies to
+    /// [`query`](crate::client::InfluxDbClient::query), [`write_with_precision`](crate::client::InfluxDbClient::write_with_precision)
+    /// and every point of [`batch_write`](crate::client::InfluxDbClient::batch_write). Rates outside of the bounds are clamped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::Timestamp;
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test").with_sample_rate(0.1);
+    /// let _future = client.write_point("requests", "latency", 0.25, Timestamp::NOW);
+    /// ```
+    pub fn with_sample_rate(mut self, rate: f64) -> Self {
+        self.sampler = Some(Sampler::new(rate, None));
+        self
+    }
 
-        let any_value = q as &dyn Any;
-        let basic_parameters: Vec<(String, String)> = self.into();
+    /// Samples writes like [`with_sample_rate`](crate::client::InfluxDbClient::with_sample_rate), seeding the random
+    /// number generator with `seed` so the same points are written every run, e.g. in tests
+    pub fn with_seeded_sample_rate(mut self, rate: f64, seed: u64) -> Self {
+        self.sampler = Some(Sampler::new(rate, Some(seed)));
+        self
+    }
 
-        let client = if let Some(_) = any_value.downcast_ref::<InfluxDbReadQuery>() {
-            let read_query = query.get();
+    /// Returns whether the next point is written, see [`with_sample_rate`](crate::client::InfluxDbClient::with_sample_rate)
+    fn sample(&self) -> bool {
+        match &self.sampler {
+            Some(sampler) => sampler.keep(),
+            None => true,
+        }
+    }
 
-            let mut url = match Url::parse_with_params(
-                format!("{url}/query", url = self.database_url()).as_str(),
-                basic_parameters,
-            ) {
-                Ok(url) => url,
-                Err(err) => {
-                    let error = InfluxDbError::UrlConstructionError {
-                        error: format!("{}", err),
-                    };
-                    return Box::new(future::err::<String, InfluxDbError>(error));
-                }
-            };
-            url.query_pairs_mut().append_pair("q", &read_query.clone());
+    /// Sets the maximum size in bytes of the error message a [`DatabaseError`](crate::error::InfluxDbError::DatabaseError)
+    /// or [`ProtocolError`](crate::error::InfluxDbError::ProtocolError) keeps from the body of an error response. Longer
+    /// messages are truncated and end with `…`. Only this many bytes of the body of an unsuccessful response are read, so
+    /// HTML error pages of a proxy do not need to fit into memory. Defaults to
+    /// [`DEFAULT_MAX_ERROR_BODY_SIZE`](crate::client::DEFAULT_MAX_ERROR_BODY_SIZE).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    ///
+    /// let _client = InfluxDbClient::new("http://localhost:8086", "test").with_max_error_body_size(512);
+    /// ```
+    pub fn with_max_error_body_size(mut self, max_error_body_size: usize) -> Self {
+        self.max_error_body_size = max_error_body_size;
+        self
+    }
 
-            if read_query.contains("SELECT") || read_query.contains("SHOW") {
-                Client::new().get(url)
-            } else {
-                Client::new().post(url)
-            }
-        } else if let Some(write_query) = any_value.downcast_ref::<InfluxDbWriteQuery>() {
-            let mut url = match Url::parse_with_params(
-                format!("{url}/write", url = self.database_url()).as_str(),
-                basic_parameters,
-            ) {
-                Ok(url) => url,
-                Err(err) => {
-                    let error = InfluxDbError::InvalidQueryError {
-                        error: format!("{}", err),
-                    };
-                    return Box::new(future::err::<String, InfluxDbError>(error));
-                }
-            };
-            url.query_pairs_mut()
-                .append_pair("precision", &write_query.get_precision());
-            Client::new().post(url).body(query.get())
+    /// Returns the maximum size of the error message kept from an error response, see
+    /// [`with_max_error_body_size`](crate::client::InfluxDbClient::with_max_error_body_size)
+    #[cfg(feature = "use-serde")]
+    pub(crate) fn max_error_body_size(&self) -> usize {
+        self.max_error_body_size
+    }
+
+    /// Sets the maximum size in bytes of a single request sent by [`batch_write`](crate::client::InfluxDbClient::batch_write).
+    /// Larger batches are split into multiple requests. Defaults to [`DEFAULT_MAX_BATCH_SIZE`](crate::client::DEFAULT_MAX_BATCH_SIZE).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    ///
+    /// let _client = InfluxDbClient::new("http://localhost:8086", "test").with_max_batch_size(1_000_000);
+    /// ```
+    pub fn with_max_batch_size(mut self, max_batch_size: usize) -> Self {
+        self.max_batch_size = max_batch_size;
+        self
+    }
+
+    /// Sets a path prefix for all endpoints, for servers mounted below a path by a reverse proxy.
+    /// With the prefix `/influx`, writes are sent to `/influx/write`, queries to `/influx/query`
+    /// and pings to `/influx/ping`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    ///
+    /// let _client = InfluxDbClient::new("https://example.com", "test").with_base_path("/influx");
+    /// ```
+    pub fn with_base_path<S>(mut self, base_path: S) -> Self
+    where
+        S: ToString,
+    {
+        let base_path = base_path.to_string();
+        let trimmed = base_path.trim_matches('/');
+        self.base_path = if trimmed.is_empty() {
+            String::new()
         } else {
-            unreachable!()
+            format!("/{}", trimmed)
         };
-        Box::new(
-            client
-                .send()
-                .map_err(|err| InfluxDbError::ConnectionError { error: err })
-                .and_then(
-                    |res| -> future::FutureResult<reqwest::r#async::Response, InfluxDbError> {
-                        match res.status() {
-                            StatusCode::UNAUTHORIZED => {
-                                futures::future::err(InfluxDbError::AuthorizationError)
-                            }
-                            StatusCode::FORBIDDEN => {
-                                futures::future::err(InfluxDbError::AuthenticationError)
-                            }
-                            _ => futures::future::ok(res),
-                        }
-                    },
-                )
-                .and_then(|mut res| {
-                    let body = mem::replace(res.body_mut(), Decoder::empty());
-                    body.concat2().map_err(|err| InfluxDbError::ProtocolError {
-                        error: format!("{}", err),
-                    })
-                })
-                .and_then(|body| {
-                    if let Ok(utf8) = std::str::from_utf8(&body) {
-                        let s = utf8.to_owned();
-
-                        // todo: improve error parsing without serde
-                        if s.contains("\"error\"") {
-                            return futures::future::err(InfluxDbError::DatabaseError {
-                                error: format!("influxdb error: \"{}\"", s),
-                            });
-                        }
+        self
+    }
 
-                        return futures::future::ok(s);
-                    }
+    /// Registers a hook called for every request the client sends, e.g. to export request metrics to Prometheus.
+    /// See [`InfluxDbMetrics`](crate::client::metrics::InfluxDbMetrics).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::metrics::InfluxDbMetrics;
+    /// use influxdb::client::InfluxDbClient;
+    /// use reqwest::StatusCode;
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// struct LogMetrics;
+    ///
+    /// impl InfluxDbMetrics for LogMetrics {
+    ///     fn on_request_end(&self, status: Option<StatusCode>, duration: Duration, bytes: usize) {
+    ///         println!("{:?} after {:?}, {} bytes", status, duration, bytes);
+    ///     }
+    /// }
+    ///
+    /// let _client = InfluxDbClient::new("http://localhost:8086", "test").with_metrics(Arc::new(LogMetrics));
+    /// ```
+    pub fn with_metrics(mut self, metrics: Arc<dyn InfluxDbMetrics>) -> Self {
+        self.metrics = Some(MetricsHook::new(metrics));
+        self
+    }
 
-                    futures::future::err(InfluxDbError::DeserializationError {
-                        error: "response could not be converted to UTF-8".to_string(),
-                    })
-                }),
-        )
+    /// Sets the format read queries request their results in using the `Accept` header. Defaults to [`ResultFormat::Json`](crate::client::ResultFormat::Json).
+    ///
+    /// [`query`](crate::client::InfluxDbClient::query) returns the results in this format, while `json_query`
+    /// of the serde integration converts them, so they can be deserialized regardless of the format.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::{InfluxDbClient, ResultFormat};
+    ///
+    /// let _client = InfluxDbClient::new("http://localhost:8086", "test").with_result_format(ResultFormat::Csv);
+    /// ```
+    pub fn with_result_format(mut self, result_format: ResultFormat) -> Self {
+        self.result_format = result_format;
+        self
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::client::InfluxDbClient;
+    /// Returns the format read queries request their results in
+    pub fn result_format(&self) -> ResultFormat {
+        self.result_format
+    }
 
-    #[test]
-    fn test_fn_database() {
-        let client = InfluxDbClient::new("http://localhost:8068", "database");
-        assert_eq!("database", client.database_name());
+    /// Sets the `Content-Type` header of write requests, which defaults to [`DEFAULT_WRITE_CONTENT_TYPE`](crate::client::DEFAULT_WRITE_CONTENT_TYPE).
+    ///
+    /// InfluxDB does not look at the header, but some proxies and gateways in front of it reject requests
+    /// without the content type they expect. Writes fail with an `InvalidQueryError` if `content_type` is no
+    /// valid header value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test").with_write_content_type("text/plain");
+    /// assert_eq!(client.write_content_type(), "text/plain");
+    /// ```
+    pub fn with_write_content_type<S>(mut self, content_type: S) -> Self
+    where
+        S: ToString,
+    {
+        self.write_content_type = content_type.to_string();
+        self
     }
 
-    #[test]
-    fn test_with_auth() {
-        let client = InfluxDbClient::new("http://localhost:8068", "database");
-        assert_eq!(client.url, "http://localhost:8068");
-        assert_eq!(client.database, "database");
-        assert!(client.auth.is_none());
-        let with_auth = client.with_auth("username", "password");
-        assert!(with_auth.auth.is_some());
-        let auth = with_auth.auth.unwrap();
-        assert_eq!(&auth.username, "username");
-        assert_eq!(&auth.password, "password");
+    /// Returns the `Content-Type` header of write requests
+    pub fn write_content_type(&self) -> &str {
+        &self.write_content_type
     }
 
-    #[test]
-    fn test_into_impl() {
-        let client = InfluxDbClient::new("http://localhost:8068", "database");
-        assert!(client.auth.is_none());
-        let basic_parameters: Vec<(String, String)> = client.into();
-        assert_eq!(
-            vec![("db".to_string(), "database".to_string())],
-            basic_parameters
+    /// Returns the `Content-Type` header of write requests, failing if it is no valid header value
+    fn write_content_type_header(&self) -> Result<HeaderValue, InfluxDbError> {
+        HeaderValue::from_str(&self.write_content_type).map_err(|_| {
+            InfluxDbError::InvalidQueryError {
+                error: format!(
+                    "invalid Content-Type of write requests: {}",
+                    self.write_content_type
+                ),
+            }
+        })
+    }
+
+    /// Enables or disables safe mode. In safe mode, read queries containing a `SELECT` statement
+    /// which neither filters on `time` nor uses a `LIMIT` are rejected with an `InvalidQueryError`
+    /// instead of being sent, to prevent accidentally scanning a whole measurement.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    ///
+    /// let _client = InfluxDbClient::new("http://localhost:8086", "test").with_safe_mode(true);
+    /// ```
+    pub fn with_safe_mode(mut self, safe_mode: bool) -> Self {
+        self.safe_mode = safe_mode;
+        self
+    }
+
+    /// Sends requests using the API of InfluxDB 2.x, scoped to the organization and bucket set with
+    /// [`with_org`](crate::client::InfluxDbClient::with_org) and [`with_bucket`](crate::client::InfluxDbClient::with_bucket).
+    ///
+    /// Writes are sent to `/api/v2/write` with the `org` and `bucket` parameters. Read queries are sent to the
+    /// InfluxQL compatibility endpoint `/query` of InfluxDB 2.x, reading from the bucket instead of the database.
+    /// Requests fail with an `InvalidQueryError` if the organization or bucket is missing.
+    ///
+    /// `/api/v2/write` authenticates using the token set with [`with_token`](crate::client::InfluxDbClient::with_token)
+    /// instead of the username and password, and only accepts timestamps in nanoseconds, microseconds, milliseconds
+    /// or seconds. Writes of points with timestamps in minutes or hours fail with an `InvalidQueryError`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    ///
+    /// let _client = InfluxDbClient::new("http://localhost:8086", "test")
+    ///     .with_v2_api()
+    ///     .with_org("my-org")
+    ///     .with_bucket("weather");
+    /// ```
+    pub fn with_v2_api(mut self) -> Self {
+        self.api_v2 = true;
+        self
+    }
+
+    /// Sets the organization used by the InfluxDB 2.x API, see [`with_v2_api`](crate::client::InfluxDbClient::with_v2_api)
+    pub fn with_org<S>(mut self, org: S) -> Self
+    where
+        S: ToString,
+    {
+        self.org = Some(org.to_string());
+        self
+    }
+
+    /// Sets the bucket used by the InfluxDB 2.x API, see [`with_v2_api`](crate::client::InfluxDbClient::with_v2_api)
+    pub fn with_bucket<S>(mut self, bucket: S) -> Self
+    where
+        S: ToString,
+    {
+        self.bucket = Some(bucket.to_string());
+        self
+    }
+
+    /// Sets the API token used by the InfluxDB 2.x API, which is sent as `Authorization: Token <token>` header
+    /// if [`with_v2_api`](crate::client::InfluxDbClient::with_v2_api) is used
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    ///
+    /// let _client = InfluxDbClient::new("http://localhost:8086", "test")
+    ///     .with_v2_api()
+    ///     .with_org("my-org")
+    ///     .with_bucket("weather")
+    ///     .with_token("my-token");
+    /// ```
+    pub fn with_token<S>(mut self, token: S) -> Self
+    where
+        S: ToString,
+    {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// Returns the organization used by the InfluxDB 2.x API, if one was set
+    pub fn org(&self) -> Option<&str> {
+        self.org.as_deref()
+    }
+
+    /// Returns the bucket used by the InfluxDB 2.x API, if one was set
+    pub fn bucket(&self) -> Option<&str> {
+        self.bucket.as_deref()
+    }
+
+    /// Returns the organization and bucket requests are scoped to if the InfluxDB 2.x API is used
+    ///
+    /// Fails if the InfluxDB 2.x API is used, but the organization or bucket is missing.
+    fn v2_scope(&self) -> Result<Option<(&str, &str)>, InfluxDbError> {
+        if !self.api_v2 {
+            return Ok(None);
+        }
+        match (self.org(), self.bucket()) {
+            (Some(org), Some(bucket)) if !org.is_empty() && !bucket.is_empty() => {
+                Ok(Some((org, bucket)))
+            }
+            _ => Err(InfluxDbError::InvalidQueryError {
+                error: "the InfluxDB 2.x API requires an organization and a bucket".to_string(),
+            }),
+        }
+    }
+
+    /// Adds the `Authorization` header with the token of the client to `request` if the InfluxDB 2.x API is used
+    ///
+    /// Fails if the token is no valid header value.
+    fn authorize(&self, request: RequestBuilder) -> Result<RequestBuilder, InfluxDbError> {
+        match &self.token {
+            Some(token) if self.api_v2 => {
+                let token = HeaderValue::from_str(&format!("Token {}", token)).map_err(|_| {
+                    InfluxDbError::InvalidQueryError {
+                        error: "the token is no valid header value".to_string(),
+                    }
+                })?;
+                Ok(request.header(AUTHORIZATION, token))
+            }
+            _ => Ok(request),
+        }
+    }
+
+    /// Sets the precision sent with write queries which do not specify one, i.e. which use [`Timestamp::NOW`](crate::query::Timestamp::NOW).
+    /// The precision of the timestamp of a write query always takes precedence.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::Precision;
+    ///
+    /// let _client = InfluxDbClient::new("http://localhost:8086", "test")
+    ///     .with_default_precision(Precision::MILLISECONDS);
+    /// ```
+    pub fn with_default_precision(mut self, precision: Precision) -> Self {
+        self.default_precision = Some(precision);
+        self
+    }
+
+    /// Returns the precision to send with `query`, falling back to the default precision of the client
+    fn write_precision(&self, query: &InfluxDbWriteQuery) -> String {
+        match (query.get_precision(), self.default_precision) {
+            (precision, Some(default)) if precision.is_empty() => default.to_string(),
+            (precision, _) => precision,
+        }
+    }
+
+    /// Sets the version of the InfluxDB server, enabling checks for features the server does not support.
+    ///
+    /// Writes of unsigned integer fields are rejected with an `InvalidQueryError` before being sent,
+    /// if the server is older than InfluxDB 1.6. Without a version, no checks are done.
+    /// See [`detect_server_version`](crate::client::InfluxDbClient::detect_server_version) to ask the server instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::version::ServerVersion;
+    /// use influxdb::client::InfluxDbClient;
+    ///
+    /// let _client = InfluxDbClient::new("http://localhost:8086", "test")
+    ///     .with_server_version(ServerVersion::new(1, 5, 4));
+    /// ```
+    pub fn with_server_version(mut self, version: ServerVersion) -> Self {
+        self.server_version = Some(version);
+        self
+    }
+
+    /// Asks the server for its version using [`server_version`](crate::client::InfluxDbClient::server_version)
+    /// and returns the client with that version set, see [`with_server_version`](crate::client::InfluxDbClient::with_server_version).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use futures::Future;
+    /// use influxdb::client::InfluxDbClient;
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test")
+    ///     .detect_server_version()
+    ///     .wait();
+    /// ```
+    pub fn detect_server_version(self) -> impl Future<Item = Self, Error = InfluxDbError> {
+        self.server_version()
+            .map(move |version| self.with_server_version(version))
+    }
+
+    /// Rejects writes the server is known to not support
+    fn check_server_support(&self, query: &InfluxDbWriteQuery) -> Result<(), InfluxDbError> {
+        let minimum = ServerVersion::new(1, 6, 0);
+        match self.server_version {
+            Some(version) if version < minimum && query.has_unsigned_fields() => {
+                Err(InfluxDbError::InvalidQueryError {
+                    error: format!(
+                        "unsigned integer fields require InfluxDB {} or later, but the server runs {}",
+                        minimum, version
+                    ),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the name of the database the client is using
+    pub fn database_name(&self) -> &str {
+        &self.database
+    }
+
+    /// Returns the URL of the InfluxDB installation the client is using
+    pub fn database_url(&self) -> &str {
+        &self.url
+    }
+
+    /// Pings the InfluxDB Server
+    ///
+    /// Returns a tuple of build type and version number. Fails with a
+    /// [`ProtocolError`](crate::error::InfluxDbError::ProtocolError) if the response lacks them,
+    /// e.g. because a proxy answered instead of InfluxDB.
+    pub fn ping(&self) -> impl Future<Item = (String, String), Error = InfluxDbError> {
+        self.ping_with(self.http_client())
+    }
+
+    /// Returns the version of the InfluxDB Server, parsed from the version reported by [`ping`](crate::client::InfluxDbClient::ping).
+    ///
+    /// This allows enabling features depending on the server, e.g. unsigned integer fields, which require InfluxDB 1.6.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use futures::Future;
+    /// use influxdb::client::version::ServerVersion;
+    /// use influxdb::client::InfluxDbClient;
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let supports_unsigned = client
+    ///     .server_version()
+    ///     .map(|version| version >= ServerVersion::new(1, 6, 0))
+    ///     .wait();
+    /// ```
+    pub fn server_version(&self) -> impl Future<Item = ServerVersion, Error = InfluxDbError> {
+        self.ping().and_then(|(_, version)| version.parse())
+    }
+
+    /// Pings the InfluxDB Server like [`ping`](crate::client::InfluxDbClient::ping), but fails with a
+    /// [`ConnectionError`](crate::error::InfluxDbError::ConnectionError) of kind
+    /// [`Timeout`](crate::error::ConnectionErrorKind::Timeout) if the server does not answer within `timeout`.
+    ///
+    /// The timeout relies on the timer of the tokio runtime, so the returned future needs to run on one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    /// use std::time::Duration;
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let _future = client.ping_with_timeout(Duration::from_secs(1));
+    /// ```
+    pub fn ping_with_timeout(
+        &self,
+        timeout: Duration,
+    ) -> impl Future<Item = (String, String), Error = InfluxDbError> {
+        self.ping_with(self.http_client_with(ClientBuilder::new().timeout(timeout)))
+    }
+
+    /// Pings the InfluxDB Server every `interval` until it answers, e.g. to wait for InfluxDB on startup.
+    ///
+    /// Fails with a [`TimeoutError`](crate::error::InfluxDbError::TimeoutError) holding the last ping error,
+    /// if the server did not answer within `timeout`. Like [`ping_with_timeout`](crate::client::InfluxDbClient::ping_with_timeout),
+    /// the returned future needs to run on a tokio runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use influxdb::client::InfluxDbClient;
+    /// use std::time::Duration;
+    ///
+    /// let mut rt = tokio::runtime::current_thread::Runtime::new().unwrap();
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// rt.block_on(client.wait_until_ready(Duration::from_secs(30), Duration::from_secs(1)))
+    ///     .expect("InfluxDB did not start");
+    /// ```
+    pub fn wait_until_ready(
+        &self,
+        timeout: Duration,
+        interval: Duration,
+    ) -> impl Future<Item = (), Error = InfluxDbError> {
+        let client = self.clone();
+        wait_until_ready(
+            move |remaining| client.ping_with_timeout(remaining),
+            timeout,
+            interval,
+        )
+    }
+
+    fn ping_with(
+        &self,
+        client: Result<Client, InfluxDbError>,
+    ) -> impl Future<Item = (String, String), Error = InfluxDbError> {
+        let client = match client {
+            Ok(client) => client,
+            Err(error) => return futures::future::Either::A(futures::future::err(error)),
+        };
+        let ping = fetch_response(
+            client.get(self.endpoint_url("ping").as_str()),
+            self.metrics_hook(),
+            self.max_error_body_size,
+        )
+        .and_then(|(res, _)| ping_headers(res.status(), res.headers()));
+        futures::future::Either::B(ping)
+    }
+
+    /// Sends a [`InfluxDbReadQuery`](crate::query::read_query::InfluxDbReadQuery) or [`InfluxDbWriteQuery`](crate::query::write_query::InfluxDbWriteQuery) to the InfluxDB Server.
+    ///
+    /// A version capable of parsing the returned string is available under the [serde_integration](crate::integrations::serde_integration)
+    ///
+    /// # Arguments
+    ///
+    ///  * `q`: Query of type [`InfluxDbReadQuery`](crate::query::read_query::InfluxDbReadQuery) or [`InfluxDbWriteQuery`](crate::query::write_query::InfluxDbWriteQuery)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::{InfluxDbQuery, Timestamp};
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let _future = client.query(
+    ///     &InfluxDbQuery::write_query(Timestamp::NOW, "weather")
+    ///         .add_field("temperature", 82)
+    /// );
+    /// ```
+    /// # Errors
+    ///
+    /// If the function can not finish the query,
+    /// a [`InfluxDbError`] variant will be returned.
+    ///
+    /// [`InfluxDbError`]: enum.InfluxDbError.html
+    pub fn query<Q>(&self, q: &Q) -> Box<dyn Future<Item = String, Error = InfluxDbError>>
+    where
+        Q: Any + InfluxDbQuery,
+    {
+        let any_value = q as &dyn Any;
+        match any_value.downcast_ref::<InfluxDbWriteQuery>() {
+            Some(_) if !self.sample() => Box::new(futures::future::ok(String::new())),
+            Some(write_query) if self.auto_create_database => {
+                let (client, creator) = (self.clone(), self.clone());
+                let write_query = write_query.clone();
+                Box::new(create_database_and_retry(
+                    move || client.send_query(&write_query),
+                    move |name| creator.send_query(&InfluxDbCreateDatabaseQuery::new(name)),
+                ))
+            }
+            _ => self.send_query(q),
+        }
+    }
+
+    fn send_query<Q>(&self, q: &Q) -> Box<dyn Future<Item = String, Error = InfluxDbError>>
+    where
+        Q: Any + InfluxDbQuery,
+    {
+        match self.query_request(q) {
+            Ok(request) => Box::new(send_request(
+                request,
+                self.metrics_hook(),
+                self.max_error_body_size,
+            )),
+            Err(error) => Box::new(futures::future::err(error)),
+        }
+    }
+
+    /// Returns the URL [`query`](crate::client::InfluxDbClient::query) would send the query to, without sending it.
+    ///
+    /// The username and password of the client are redacted, so the URL can be logged when debugging a query.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test").with_auth("admin", "password");
+    /// let url = client.request_url(&InfluxDbQuery::raw_read_query("SELECT * FROM weather")).unwrap();
+    ///
+    /// assert_eq!(
+    ///     url.as_str(),
+    ///     "http://localhost:8086/query?db=test&u=REDACTED&p=REDACTED&q=SELECT+*+FROM+weather"
+    /// );
+    /// ```
+    pub fn request_url<Q>(&self, q: &Q) -> Result<Url, InfluxDbError>
+    where
+        Q: Any + InfluxDbQuery,
+    {
+        let query = self.build_for_server(q)?;
+        let mut url = self.query_url(q, &query)?;
+
+        let redacted: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(key, value)| match key.as_ref() {
+                "u" | "p" => (key.into_owned(), "REDACTED".to_string()),
+                _ => (key.into_owned(), value.into_owned()),
+            })
+            .collect();
+        url.query_pairs_mut().clear().extend_pairs(redacted);
+        Ok(url)
+    }
+
+    /// Creates the request sending the query, see [`query`](crate::client::InfluxDbClient::query)
+    fn query_request<Q>(&self, q: &Q) -> Result<RequestBuilder, InfluxDbError>
+    where
+        Q: Any + InfluxDbQuery,
+    {
+        let query = self.build_for_server(q)?;
+        let url = self.query_url(q, &query)?;
+        let http_client = self.http_client()?;
+
+        let any_value = q as &dyn Any;
+        if any_value.is::<InfluxDbWriteQuery>() {
+            return self.authorize(
+                http_client
+                    .post(url)
+                    .header(CONTENT_TYPE, self.write_content_type_header()?)
+                    .body(query),
+            );
+        }
+
+        let method = match any_value.downcast_ref::<InfluxDbReadQuery>() {
+            Some(read_query) => read_query.http_method(&query),
+            None if requires_post(&query) => Method::Post,
+            None => Method::Get,
+        };
+
+        let request = match method {
+            Method::Get => http_client.get(url),
+            Method::Post => http_client.post(url),
+        };
+        self.authorize(request.header(ACCEPT, self.result_format.mime_type()))
+    }
+
+    /// Builds `q`, leaving out clauses the server is not known to accept,
+    /// see [`if_not_exists`](crate::query::create_database::InfluxDbCreateDatabaseQuery::if_not_exists)
+    fn build_for_server<Q>(&self, q: &Q) -> Result<String, InfluxDbError>
+    where
+        Q: Any + InfluxDbQuery,
+    {
+        let supports_if_not_exists = match self.server_version {
+            Some(version) => version < ServerVersion::new(1, 0, 0),
+            None => false,
+        };
+        match (q as &dyn Any).downcast_ref::<InfluxDbCreateDatabaseQuery>() {
+            Some(create_database) if !supports_if_not_exists => {
+                match create_database.without_if_not_exists() {
+                    Some(create_database) => build_query(&create_database),
+                    None => build_query(q),
+                }
+            }
+            _ => build_query(q),
+        }
+    }
+
+    /// Creates the URL to send the built `query` of `q` to
+    fn query_url<Q>(&self, q: &Q, query: &str) -> Result<Url, InfluxDbError>
+    where
+        Q: Any + InfluxDbQuery,
+    {
+        let any_value = q as &dyn Any;
+
+        if let Some(write_query) = any_value.downcast_ref::<InfluxDbWriteQuery>() {
+            self.check_server_support(write_query)?;
+            self.write_url(
+                &self.write_precision(write_query),
+                write_query.get_extra_params(),
+            )
+        } else {
+            match any_value.downcast_ref::<InfluxDbReadQuery>() {
+                Some(read_query) => self.read_url(read_query, query),
+                // statements of the other builders, e.g. retention policies, are sent like a raw read query
+                None => self.read_url(&InfluxDbReadQuery::new(query), query),
+            }
+        }
+    }
+
+    /// Writes a batch of [`InfluxDbWriteQuery`](crate::query::write_query::InfluxDbWriteQuery) to the InfluxDB Server.
+    ///
+    /// Batches exceeding the maximum batch size of the client (see [`with_max_batch_size`](crate::client::InfluxDbClient::with_max_batch_size))
+    /// are split into multiple requests, which are sent one after another. The returned `Vec` contains the result of each
+    /// of these requests in the order they were sent, so failed chunks can be told apart from successful ones.
+    ///
+    /// All queries in a batch need to use the same timestamp precision, unless the client
+    /// [splits batches by precision](crate::client::InfluxDbClient::with_split_precisions). Extra parameters
+    /// (see [`with_extra_param`](crate::query::write_query::InfluxDbWriteQuery::with_extra_param)) are taken from the first query.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::{InfluxDbQuery, Timestamp};
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let _future = client.batch_write(&[
+    ///     InfluxDbQuery::write_query(Timestamp::HOURS(1), "weather").add_field("temperature", 82),
+    ///     InfluxDbQuery::write_query(Timestamp::HOURS(2), "weather").add_field("temperature", 84),
+    /// ]);
+    /// ```
+    pub fn batch_write(
+        &self,
+        queries: &[InfluxDbWriteQuery],
+    ) -> Box<dyn Future<Item = Vec<Result<String, InfluxDbError>>, Error = InfluxDbError>> {
+        use futures::{future, stream};
+
+        let requests = match self.batch_write_requests(queries) {
+            Ok(requests) => requests,
+            Err(error) => return Box::new(future::err(error)),
+        };
+
+        let metrics = self.metrics_hook();
+        let max_error_body_size = self.max_error_body_size;
+        Box::new(
+            stream::iter_ok(requests)
+                .and_then(move |request| {
+                    send_request(request, metrics.clone(), max_error_body_size).then(Ok)
+                })
+                .collect(),
+        )
+    }
+
+    /// Creates the requests of [`batch_write`](crate::client::InfluxDbClient::batch_write), one per chunk of the batch
+    fn batch_write_requests(
+        &self,
+        queries: &[InfluxDbWriteQuery],
+    ) -> Result<Vec<RequestBuilder>, InfluxDbError> {
+        queries
+            .iter()
+            .try_for_each(|query| self.check_server_support(query))?;
+
+        let sampled: Vec<InfluxDbWriteQuery>;
+        let queries = if self.sampler.is_some() {
+            sampled = queries.iter().filter(|_| self.sample()).cloned().collect();
+            if sampled.is_empty() && !queries.is_empty() {
+                return Ok(vec![]);
+            }
+            &sampled
+        } else {
+            queries
+        };
+
+        let by_precision;
+        let groups: Vec<&[InfluxDbWriteQuery]> = if self.split_precisions && !queries.is_empty() {
+            by_precision = group_by_precision(queries);
+            by_precision.iter().map(Vec::as_slice).collect()
+        } else {
+            vec![queries]
+        };
+
+        let mut requests = vec![];
+        for group in groups {
+            let chunks = chunk_batch(group, self.max_batch_size)?;
+            let precision = self.write_precision(&group[0]);
+            let extra_params = group[0].get_extra_params();
+            for chunk in chunks {
+                requests.push(self.write_request(chunk, &precision, extra_params)?);
+            }
+        }
+        Ok(requests)
+    }
+
+    /// Writes points to several databases, e.g. one database per tenant, without setting up a client for each of them.
+    ///
+    /// The queries are grouped by database and each group is written using [`batch_write`](crate::client::InfluxDbClient::batch_write),
+    /// sending one request per database unless the group exceeds the maximum batch size. The returned map contains the results
+    /// of the requests of each database. If the InfluxDB 2.x API is used, the databases name the buckets to write to.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::{InfluxDbQuery, Timestamp};
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let _future = client.write_to_databases(&[
+    ///     ("tenant_a", InfluxDbQuery::write_query(Timestamp::HOURS(1), "weather").add_field("temperature", 82)),
+    ///     ("tenant_b", InfluxDbQuery::write_query(Timestamp::HOURS(1), "weather").add_field("temperature", 64)),
+    /// ]);
+    /// ```
+    pub fn write_to_databases<S>(
+        &self,
+        writes: &[(S, InfluxDbWriteQuery)],
+    ) -> Box<dyn Future<Item = DatabaseWriteResults, Error = InfluxDbError>>
+    where
+        S: ToString,
+    {
+        use futures::stream;
+
+        let requests: Vec<_> = group_by_database(writes)
+            .into_iter()
+            .map(|(database, queries)| {
+                self.for_database(&database)
+                    .batch_write(&queries)
+                    .then(move |results| {
+                        // failures before sending, e.g. an invalid query, fail the whole group
+                        let results = results.unwrap_or_else(|error| vec![Err(error)]);
+                        Ok((database, results))
+                    })
+            })
+            .collect();
+
+        Box::new(
+            stream::futures_ordered(requests)
+                .collect()
+                .map(|results| results.into_iter().collect()),
+        )
+    }
+
+    /// Returns a copy of the client writing to and reading from `database`, or the bucket `database` if the
+    /// InfluxDB 2.x API is used
+    fn for_database(&self, database: &str) -> Self {
+        let mut client = self.clone();
+        client.database = database.to_string();
+        if client.api_v2 {
+            client.bucket = Some(database.to_string());
+        }
+        client
+    }
+
+    /// Sends the `EXPLAIN` statement of a [`InfluxDbReadQuery`](crate::query::read_query::InfluxDbReadQuery),
+    /// returning the query plan InfluxDB would use to run it. See [`InfluxDbReadQuery::explain`](crate::query::read_query::InfluxDbReadQuery::explain).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let _future = client.explain(&InfluxDbQuery::raw_read_query("SELECT * FROM weather"));
+    /// ```
+    pub fn explain(
+        &self,
+        q: &InfluxDbReadQuery,
+    ) -> Box<dyn Future<Item = String, Error = InfluxDbError>> {
+        self.query(&q.clone().explain())
+    }
+
+    /// Checks a [`InfluxDbReadQuery`](crate::query::read_query::InfluxDbReadQuery) for syntax errors without running it.
+    ///
+    /// The query is checked for gross syntax errors using [`InfluxDbReadQuery::validate`](crate::query::read_query::InfluxDbReadQuery::validate)
+    /// first, failing without sending a request. If every query is a single `SELECT` statement, its `EXPLAIN` statement
+    /// is sent next, so InfluxDB parses and plans the query without reading any data. Other statements, like `DROP`
+    /// or `DELETE`, can not be checked by InfluxDB without running them, so only the local check applies to them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use futures::Future;
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let query = InfluxDbQuery::raw_read_query("DROP SERIES FROM weather WHERE location = 'us-midwest");
+    /// assert!(client.validate_query(&query).wait().is_err());
+    /// ```
+    pub fn validate_query(
+        &self,
+        q: &InfluxDbReadQuery,
+    ) -> Box<dyn Future<Item = (), Error = InfluxDbError>> {
+        if let Err(error) = q.validate() {
+            return Box::new(futures::future::err(error));
+        }
+        if !q.is_explainable() {
+            return Box::new(futures::future::ok(()));
+        }
+        Box::new(self.explain(q).map(|_| ()))
+    }
+
+    /// Sends the `EXPLAIN ANALYZE` statement of a [`InfluxDbReadQuery`](crate::query::read_query::InfluxDbReadQuery),
+    /// which runs the query and returns its plan annotated with execution statistics.
+    /// See [`InfluxDbReadQuery::explain_analyze`](crate::query::read_query::InfluxDbReadQuery::explain_analyze).
+    pub fn explain_analyze(
+        &self,
+        q: &InfluxDbReadQuery,
+    ) -> Box<dyn Future<Item = String, Error = InfluxDbError>> {
+        self.query(&q.clone().explain_analyze())
+    }
+
+    /// Deletes **all points** of `measurement` using `DELETE FROM` without a `WHERE` clause. This is destructive and
+    /// can not be undone.
+    ///
+    /// Unlike `DROP MEASUREMENT`, the series and the tag and field keys of the measurement are kept, so
+    /// the measurement stays defined in the schema. Use a raw read query with a `WHERE` clause to delete only
+    /// some of the points.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let _future = client.delete_all_points("weather");
+    /// ```
+    pub fn delete_all_points<S>(
+        &self,
+        measurement: S,
+    ) -> Box<dyn Future<Item = String, Error = InfluxDbError>>
+    where
+        S: ToString,
+    {
+        self.query(&delete_all_points_query(&measurement.to_string()))
+    }
+
+    /// Writes a body of raw line protocol to the InfluxDB Server.
+    ///
+    /// InfluxDB interprets the timestamps of the points in `body` using `precision`, defaulting to nanoseconds
+    /// if none is given. The timestamps in `body` therefore need to match the supplied `precision`, otherwise
+    /// the points are silently written to the wrong time.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::Precision;
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let _future = client.write_line_protocol(
+    ///     "weather temperature=82 1568106310\nweather temperature=84 1568106370",
+    ///     Precision::SECONDS,
+    /// );
+    /// ```
+    pub fn write_line_protocol<S>(
+        &self,
+        body: S,
+        precision: Precision,
+    ) -> Box<dyn Future<Item = String, Error = InfluxDbError>>
+    where
+        S: ToString,
+    {
+        match self.write_request(body.to_string(), &precision.to_string(), &[]) {
+            Ok(request) => Box::new(send_request(
+                request,
+                self.metrics_hook(),
+                self.max_error_body_size,
+            )),
+            Err(error) => Box::new(futures::future::err(error)),
+        }
+    }
+
+    /// Writes a [`InfluxDbWriteQuery`](crate::query::write_query::InfluxDbWriteQuery), sending `precision` instead of
+    /// the precision of its timestamp.
+    ///
+    /// This reinterprets the integer of the timestamp, e.g. `Timestamp::HOURS(1568106310)` written with
+    /// `Precision::SECONDS` is written at second 1568106310. Nothing checks that the integer fits the precision,
+    /// so a mismatch silently writes the point to the wrong time rather than failing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::{InfluxDbQuery, Precision, Timestamp};
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// // the timestamp was read from a source using seconds
+    /// let _future = client.write_with_precision(
+    ///     &InfluxDbQuery::write_query(Timestamp::NANOSECONDS(1568106310), "weather").add_field("temperature", 82),
+    ///     Precision::SECONDS,
+    /// );
+    /// ```
+    pub fn write_with_precision(
+        &self,
+        q: &InfluxDbWriteQuery,
+        precision: Precision,
+    ) -> Box<dyn Future<Item = String, Error = InfluxDbError>> {
+        if !self.sample() {
+            return Box::new(futures::future::ok(String::new()));
+        }
+        match self.write_with_precision_request(q, precision) {
+            Ok(request) => Box::new(send_request(
+                request,
+                self.metrics_hook(),
+                self.max_error_body_size,
+            )),
+            Err(error) => Box::new(futures::future::err(error)),
+        }
+    }
+
+    /// Creates the request of [`write_with_precision`](crate::client::InfluxDbClient::write_with_precision)
+    fn write_with_precision_request(
+        &self,
+        q: &InfluxDbWriteQuery,
+        precision: Precision,
+    ) -> Result<RequestBuilder, InfluxDbError> {
+        self.check_server_support(q)?;
+        let body = build_query(q)?;
+        self.write_request(body, &precision.to_string(), q.get_extra_params())
+    }
+
+    /// Writes a single field `field` of `measurement` with `value` at `timestamp`, a shorthand for writing a
+    /// [`InfluxDbWriteQuery`](crate::query::write_query::InfluxDbWriteQuery) with one field and no tags.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::Timestamp;
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let _future = client.write_point("weather", "temperature", 82, Timestamp::NOW);
+    /// ```
+    pub fn write_point<S1, S2, I>(
+        &self,
+        measurement: S1,
+        field: S2,
+        value: I,
+        timestamp: Timestamp,
+    ) -> Box<dyn Future<Item = String, Error = InfluxDbError>>
+    where
+        S1: ToString,
+        S2: ToString,
+        I: Into<InfluxDbType>,
+    {
+        self.query(&point_query(measurement, field, value, timestamp))
+    }
+
+    /// Sends a request to an arbitrary endpoint of the InfluxDB Server, returning the body of the response.
+    ///
+    /// This is an escape hatch for endpoints the client does not support yet, e.g. `debug/vars`. `path` is relative to
+    /// the URL and [base path](crate::client::InfluxDbClient::with_base_path) of the client, and `params` are appended
+    /// to the query string verbatim, so the database needs to be passed as `db` if the endpoint requires one.
+    /// The credentials of the client are sent as `u` and `p` like with any other request, and can not be overridden by `params`.
+    ///
+    /// Responses are checked like those of [`query`](crate::client::InfluxDbClient::query), so error bodies of InfluxDB fail
+    /// with a [`DatabaseError`](crate::error::InfluxDbError::DatabaseError).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::Method;
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test").with_auth("admin", "password");
+    /// let _future = client.send_raw(Method::Get, "debug/vars", &[("pretty", "true")], None);
+    /// ```
+    pub fn send_raw<S, K, V>(
+        &self,
+        method: Method,
+        path: S,
+        params: &[(K, V)],
+        body: Option<String>,
+    ) -> Box<dyn Future<Item = String, Error = InfluxDbError>>
+    where
+        S: AsRef<str>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        match self.raw_request(method, path.as_ref(), params, body) {
+            Ok(request) => Box::new(send_request(
+                request,
+                self.metrics_hook(),
+                self.max_error_body_size,
+            )),
+            Err(error) => Box::new(futures::future::err(error)),
+        }
+    }
+
+    /// Creates the request of [`send_raw`](crate::client::InfluxDbClient::send_raw)
+    fn raw_request<K, V>(
+        &self,
+        method: Method,
+        path: &str,
+        params: &[(K, V)],
+        body: Option<String>,
+    ) -> Result<RequestBuilder, InfluxDbError>
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        if let Some((key, _)) = params
+            .iter()
+            .find(|(key, _)| key.as_ref() == "u" || key.as_ref() == "p")
+        {
+            return Err(InfluxDbError::InvalidQueryError {
+                error: format!(
+                    "\"{}\" is set by the client and cannot be used as a parameter",
+                    key.as_ref()
+                ),
+            });
+        }
+
+        let mut url =
+            Url::parse(&self.endpoint_url(path.trim_start_matches('/'))).map_err(|err| {
+                InfluxDbError::UrlConstructionError {
+                    error: format!("{}", err),
+                }
+            })?;
+        if let Some(auth) = &self.auth {
+            url.query_pairs_mut()
+                .append_pair("u", &auth.username)
+                .append_pair("p", &auth.password);
+        }
+        for (key, value) in params {
+            url.query_pairs_mut()
+                .append_pair(key.as_ref(), value.as_ref());
+        }
+
+        let http_client = self.http_client()?;
+        let request = match method {
+            Method::Get => http_client.get(url),
+            Method::Post => http_client.post(url),
+        };
+        self.authorize(match body {
+            Some(body) => request.body(body),
+            None => request,
+        })
+    }
+
+    /// Creates the URL running the built `query` of a [`InfluxDbReadQuery`](crate::query::read_query::InfluxDbReadQuery)
+    ///
+    /// Fails if the client is in safe mode and the query contains an unbounded `SELECT`.
+    pub(crate) fn read_url(
+        &self,
+        read_query: &InfluxDbReadQuery,
+        query: &str,
+    ) -> Result<Url, InfluxDbError> {
+        if self.safe_mode {
+            if let Some(statement) = read_query.unbounded_select() {
+                return Err(InfluxDbError::InvalidQueryError {
+                    error: format!(
+                        "safe mode rejected \"{}\", which neither filters on time nor uses a LIMIT",
+                        statement
+                    ),
+                });
+            }
+        }
+
+        let mut basic_parameters: Vec<(String, String)> = self.into();
+        if read_query.skips_database() {
+            basic_parameters.retain(|(key, _)| key != "db");
+        } else if let Some((_, bucket)) = self.v2_scope()? {
+            // the InfluxQL compatibility endpoint of InfluxDB 2.x reads the bucket from `db`
+            for (key, value) in basic_parameters.iter_mut() {
+                if key == "db" {
+                    *value = bucket.to_string();
+                }
+            }
+        }
+        if let (false, Some(database)) = (read_query.skips_database(), read_query.get_database()) {
+            for (key, value) in basic_parameters.iter_mut() {
+                if key == "db" {
+                    *value = database.to_string();
+                }
+            }
+        }
+
+        let mut url =
+            match Url::parse_with_params(self.endpoint_url("query").as_str(), basic_parameters) {
+                Ok(url) => url,
+                Err(err) => {
+                    return Err(InfluxDbError::UrlConstructionError {
+                        error: format!("{}", err),
+                    });
+                }
+            };
+        url.query_pairs_mut().append_pair("q", query);
+        if let Some(epoch) = read_query.get_epoch() {
+            url.query_pairs_mut()
+                .append_pair("epoch", &epoch.to_string());
+        }
+        if let Some(node_id) = read_query.get_node_id() {
+            url.query_pairs_mut()
+                .append_pair("node_id", &node_id.to_string());
+        }
+        if let Some(chunk_size) = read_query.get_chunk_size() {
+            url.query_pairs_mut()
+                .append_pair("chunked", "true")
+                .append_pair("chunk_size", &chunk_size.to_string());
+        }
+        if let Some(params) = read_query.bind_params_json()? {
+            url.query_pairs_mut().append_pair("params", &params);
+        }
+        append_extra_params(&mut url, read_query.get_extra_params())?;
+
+        Ok(url)
+    }
+
+    /// Returns the URL of `endpoint`, including the base path
+    fn endpoint_url(&self, endpoint: &str) -> String {
+        format!(
+            "{url}{base_path}/{endpoint}",
+            url = self.database_url().trim_end_matches('/'),
+            base_path = self.base_path,
+            endpoint = endpoint
+        )
+    }
+
+    /// Creates the request writing the line protocol `body` with the given `precision`
+    ///
+    /// Fails if `body` does not contain any points, as sending it would be pointless.
+    fn write_request(
+        &self,
+        body: String,
+        precision: &str,
+        extra_params: &[(String, String)],
+    ) -> Result<RequestBuilder, InfluxDbError> {
+        if body.trim().is_empty() {
+            return Err(InfluxDbError::InvalidQueryError {
+                error: "write body cannot be empty".to_string(),
+            });
+        }
+        let url = self.write_url(precision, extra_params)?;
+        self.authorize(
+            self.http_client()?
+                .post(url)
+                .header(CONTENT_TYPE, self.write_content_type_header()?)
+                .body(body),
+        )
+    }
+
+    /// Creates the URL of the write endpoint for line protocol with the given `precision` and extra parameters
+    pub(crate) fn write_url(
+        &self,
+        precision: &str,
+        extra_params: &[(String, String)],
+    ) -> Result<Url, InfluxDbError> {
+        let mut basic_parameters: Vec<(String, String)> = self.into();
+        let (endpoint, precision) = match self.v2_scope()? {
+            Some((org, bucket)) => {
+                // `/api/v2/write` ignores `u` and `p`, it authenticates using the token instead
+                basic_parameters.retain(|(key, _)| key != "db" && key != "u" && key != "p");
+                basic_parameters.push(("org".to_string(), org.to_string()));
+                basic_parameters.push(("bucket".to_string(), bucket.to_string()));
+                ("api/v2/write", v2_precision(precision)?)
+            }
+            None => ("write", precision),
+        };
+        let mut url =
+            match Url::parse_with_params(self.endpoint_url(endpoint).as_str(), basic_parameters) {
+                Ok(url) => url,
+                Err(err) => {
+                    return Err(InfluxDbError::InvalidQueryError {
+                        error: format!("{}", err),
+                    });
+                }
+            };
+        url.query_pairs_mut().append_pair("precision", precision);
+        append_extra_params(&mut url, extra_params)?;
+        Ok(url)
+    }
+}
+
+/// Translates the `precision` parameter of InfluxDB 1.x into the one of `/api/v2/write`, which names microseconds
+/// `us` and does not support minutes and hours
+fn v2_precision(precision: &str) -> Result<&str, InfluxDbError> {
+    match precision {
+        "u" => Ok("us"),
+        // writes without a timestamp send an empty precision
+        "" | "ns" | "ms" | "s" => Ok(precision),
+        _ => Err(InfluxDbError::InvalidQueryError {
+            error: format!(
+                "the InfluxDB 2.x API does not support the precision \"{}\", only ns, us, ms and s",
+                precision
+            ),
+        }),
+    }
+}
+
+/// Groups `writes` by database, keeping the order of the queries of each database
+fn group_by_database<S>(
+    writes: &[(S, InfluxDbWriteQuery)],
+) -> BTreeMap<String, Vec<InfluxDbWriteQuery>>
+where
+    S: ToString,
+{
+    let mut groups: BTreeMap<String, Vec<InfluxDbWriteQuery>> = BTreeMap::new();
+    for (database, query) in writes {
+        groups
+            .entry(database.to_string())
+            .or_default()
+            .push(query.clone());
+    }
+    groups
+}
+
+/// Groups `queries` by the precision of their timestamps, in the order each precision first occurs
+fn group_by_precision(queries: &[InfluxDbWriteQuery]) -> Vec<Vec<InfluxDbWriteQuery>> {
+    let mut groups: Vec<(String, Vec<InfluxDbWriteQuery>)> = vec![];
+    for query in queries {
+        let precision = query.get_precision();
+        match groups.iter_mut().find(|(key, _)| *key == precision) {
+            Some((_, group)) => group.push(query.clone()),
+            None => groups.push((precision, vec![query.clone()])),
+        }
+    }
+    groups.into_iter().map(|(_, group)| group).collect()
+}
+
+/// Creates the `DELETE` statement deleting all points of `measurement`
+fn delete_all_points_query(measurement: &str) -> InfluxDbReadQuery {
+    InfluxDbReadQuery::new(format!("DELETE FROM {}", quote_identifier(measurement)))
+}
+
+/// Creates the write query of [`write_point`](crate::client::InfluxDbClient::write_point)
+fn point_query<S1, S2, I>(
+    measurement: S1,
+    field: S2,
+    value: I,
+    timestamp: Timestamp,
+) -> InfluxDbWriteQuery
+where
+    S1: ToString,
+    S2: ToString,
+    I: Into<InfluxDbType>,
+{
+    InfluxDbQuery::write_query(timestamp, measurement).add_field(field, value)
+}
+
+/// Builds a query, wrapping errors into an `InvalidQueryError`
+fn build_query<Q>(q: &Q) -> Result<String, InfluxDbError>
+where
+    Q: InfluxDbQuery,
+{
+    q.build()
+        .map(|query| query.get())
+        .map_err(|err| InfluxDbError::InvalidQueryError {
+            error: format!("{}", err),
+        })
+}
+
+/// Reads the PKCS #12 archive of a client certificate
+fn load_identity(identity: &InfluxDbIdentity) -> Result<Identity, InfluxDbError> {
+    Identity::from_pkcs12_der(&identity.pkcs12, &identity.password).map_err(|error| {
+        InfluxDbError::ConnectionError {
+            kind: ConnectionErrorKind::Tls,
+            error,
+        }
+    })
+}
+
+/// Maximum number of redirects followed for a single request
+const MAX_REDIRECTS: usize = 10;
+
+/// Follows redirects as long as [`follows_redirect`](crate::client::follows_redirect) allows them,
+/// up to [`MAX_REDIRECTS`](crate::client::MAX_REDIRECTS)
+fn redirect_policy() -> RedirectPolicy {
+    RedirectPolicy::custom(|attempt| {
+        if attempt.previous().len() > MAX_REDIRECTS {
+            attempt.too_many_redirects()
+        } else if follows_redirect(attempt.url(), attempt.previous()) {
+            attempt.follow()
+        } else {
+            attempt.stop()
+        }
+    })
+}
+
+/// Returns whether a redirect from the last of the `previous` URLs to `next` may be followed
+///
+/// The credentials of the client are sent as `u` and `p` query parameters, which load balancers usually keep
+/// in the redirect location. Redirects carrying them to another host or from HTTPS to HTTP are not followed, so
+/// the credentials are never sent anywhere but to the configured server. reqwest drops the `Authorization`
+/// header on redirects to another host on its own.
+fn follows_redirect(next: &Url, previous: &[Url]) -> bool {
+    let previous = match previous.last() {
+        Some(previous) => previous,
+        None => return true,
+    };
+    let has_credentials = next.query_pairs().any(|(key, _)| key == "u" || key == "p");
+    let same_host = next.host_str() == previous.host_str();
+    let downgrade = previous.scheme() == "https" && next.scheme() != "https";
+
+    !has_credentials || (same_host && !downgrade)
+}
+
+/// Error for a redirect response which was not followed, see [`follows_redirect`](crate::client::follows_redirect)
+pub(crate) fn unfollowed_redirect(status: StatusCode) -> InfluxDbError {
+    InfluxDbError::ProtocolError {
+        error: format!(
+            "redirect ({}) was not followed, as it would send the credentials to another host",
+            status
+        ),
+    }
+}
+
+/// Query parameters set by the client, which can not be passed as extra parameters
+const RESERVED_PARAMS: &[&str] = &[
+    "db",
+    "u",
+    "p",
+    "q",
+    "epoch",
+    "node_id",
+    "chunked",
+    "chunk_size",
+    "precision",
+    "org",
+    "bucket",
+    "params",
+];
+
+/// Appends the extra parameters of a query to `url`, failing if one of them is reserved
+fn append_extra_params(
+    url: &mut Url,
+    extra_params: &[(String, String)],
+) -> Result<(), InfluxDbError> {
+    if let Some((key, _)) = extra_params
+        .iter()
+        .find(|(key, _)| RESERVED_PARAMS.contains(&key.as_str()))
+    {
+        return Err(InfluxDbError::InvalidQueryError {
+            error: format!(
+                "\"{}\" is set by the client and cannot be used as an extra parameter",
+                key
+            ),
+        });
+    }
+    url.query_pairs_mut().extend_pairs(extra_params);
+    Ok(())
+}
+
+/// Calls `ping` with the time remaining until `timeout` every `interval`, until it succeeds or the time is up
+fn wait_until_ready<P, F>(
+    ping: P,
+    timeout: Duration,
+    interval: Duration,
+) -> impl Future<Item = (), Error = InfluxDbError>
+where
+    P: FnMut(Duration) -> F,
+    F: Future<Item = (String, String), Error = InfluxDbError>,
+{
+    use futures::future::{self, Either, Loop};
+    use tokio::timer::Delay;
+
+    let deadline = Instant::now() + timeout;
+    future::loop_fn(ping, move |mut ping| {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        ping(remaining).then(move |result| match result {
+            Ok(_) => Either::A(future::ok(Loop::Break(()))),
+            Err(error) => {
+                let retry_at = Instant::now() + interval;
+                if retry_at >= deadline {
+                    return Either::A(future::err(InfluxDbError::TimeoutError {
+                        error: format!(
+                            "InfluxDB was not ready within {:?}, last error: {}",
+                            timeout, error
+                        ),
+                    }));
+                }
+                Either::B(
+                    Delay::new(retry_at)
+                        .map(move |_| Loop::Continue(ping))
+                        .map_err(|err| InfluxDbError::ProtocolError {
+                            error: format!("timer error: {}", err),
+                        }),
+                )
+            }
+        })
+    })
+}
+
+/// Sends `write`, calling `create` with the name of the database and sending `write` once more if the database is missing
+fn create_database_and_retry<W, C, F>(
+    write: W,
+    create: C,
+) -> impl Future<Item = String, Error = InfluxDbError>
+where
+    W: Fn() -> F + 'static,
+    C: FnOnce(String) -> F,
+    F: Future<Item = String, Error = InfluxDbError>,
+{
+    use futures::future::{self, Either};
+    use std::rc::Rc;
+
+    let write = Rc::new(write);
+    let retry = Rc::clone(&write);
+    write().or_else(move |error| match error {
+        InfluxDbError::DatabaseNotFound { name } => {
+            warn!(
+                "database \"{}\" not found, creating it and retrying the write",
+                name
+            );
+            Either::A(create(name).and_then(move |_| retry()))
+        }
+        error => Either::B(future::err(error)),
+    })
+}
+
+/// Sends the request and checks the response for errors, keeping at most `max_error_body_size` bytes of an error body
+fn send_request(
+    request: RequestBuilder,
+    metrics: Option<MetricsHook>,
+    max_error_body_size: usize,
+) -> impl Future<Item = String, Error = InfluxDbError> {
+    fetch_response(request, metrics, max_error_body_size).and_then(move |(res, body)| {
+        check_rate_limit(res.status(), res.headers())?;
+        check_response(res.status(), &body, max_error_body_size)
+    })
+}
+
+/// Sends the request and reads the body of the response, reporting the request to `metrics`
+///
+/// The body is taken out of the returned response, leaving the status and headers. Only the first
+/// `max_error_body_size` bytes of the body of an unsuccessful response are kept, as it may be an arbitrarily
+/// large error page of a proxy.
+pub(crate) fn fetch_response(
+    request: RequestBuilder,
+    metrics: Option<MetricsHook>,
+    max_error_body_size: usize,
+) -> impl Future<Item = (Response, Chunk), Error = InfluxDbError> {
+    use futures::future::Either;
+
+    let started = metrics.as_ref().map(MetricsHook::start);
+    request
+        .send()
+        .map_err(|err| (None, InfluxDbError::connection_error(err)))
+        .and_then(move |mut res| {
+            let status = res.status();
+            let body = mem::replace(res.body_mut(), Decoder::empty());
+            let body = if status.is_success() {
+                Either::A(body.concat2())
+            } else {
+                Either::B(body.fold(Chunk::default(), move |mut kept, chunk| {
+                    let room = max_error_body_size.saturating_sub(kept.len());
+                    kept.extend(chunk.iter().take(room).cloned());
+                    Ok::<_, reqwest::Error>(kept)
+                }))
+            };
+            body.map(move |body| (res, body)).map_err(move |err| {
+                let error = InfluxDbError::ProtocolError {
+                    error: format!("{}", err),
+                };
+                (Some(status), error)
+            })
+        })
+        .then(move |result| {
+            if let (Some(metrics), Some(started)) = (metrics, started) {
+                match &result {
+                    Ok((res, body)) => metrics.end(started, Some(res.status()), body.len()),
+                    Err((status, _)) => metrics.end(started, *status, 0),
+                }
+            }
+            result.map_err(|(_, error)| error)
+        })
+}
+
+/// Checks the status and body of a response for errors, returning the body on success
+/// Fails with a [`RateLimited`](crate::error::InfluxDbError::RateLimited) error if the response is `429 Too Many Requests`,
+/// passing on the delay of the `Retry-After` header. Dates in `Retry-After` are not supported.
+pub(crate) fn check_rate_limit(
+    status: StatusCode,
+    headers: &HeaderMap,
+) -> Result<(), InfluxDbError> {
+    if status != StatusCode::TOO_MANY_REQUESTS {
+        return Ok(());
+    }
+    let retry_after = headers
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .map(Duration::from_secs);
+    Err(InfluxDbError::RateLimited { retry_after })
+}
+
+/// Returns the build and version of InfluxDB from the headers of a ping response.
+///
+/// Fails with a [`ProtocolError`](crate::error::InfluxDbError::ProtocolError) if the ping was not successful
+/// or one of the headers is missing, e.g. if a proxy answered instead of InfluxDB.
+fn ping_headers(
+    status: StatusCode,
+    headers: &HeaderMap,
+) -> Result<(String, String), InfluxDbError> {
+    if !status.is_success() {
+        return Err(InfluxDbError::ProtocolError {
+            error: format!("ping failed with status {}", status),
+        });
+    }
+    let header = |name: &str| {
+        let value = headers
+            .get(name)
+            .ok_or_else(|| InfluxDbError::ProtocolError {
+                error: format!("ping response is missing the {} header", name),
+            })?;
+        value
+            .to_str()
+            .map(String::from)
+            .map_err(|_| InfluxDbError::ProtocolError {
+                error: format!("{} header of the ping response is not ASCII", name),
+            })
+    };
+    Ok((header("X-Influxdb-Build")?, header("X-Influxdb-Version")?))
+}
+
+/// Error for an unsuccessful response whose body is no InfluxDB error
+pub(crate) fn unexpected_response(status: StatusCode, body: &[u8]) -> InfluxDbError {
+    InfluxDbError::ProtocolError {
+        error: format!(
+            "unexpected response ({}): {}",
+            status,
+            String::from_utf8_lossy(body)
+        ),
+    }
+}
+
+/// Checks the status and body of a response for errors, keeping at most `max_error_body_size` bytes of an error message
+fn check_response(
+    status: StatusCode,
+    body: &[u8],
+    max_error_body_size: usize,
+) -> Result<String, InfluxDbError> {
+    check_response_body(status, body).map_err(|error| error.truncated(max_error_body_size))
+}
+
+fn check_response_body(status: StatusCode, body: &[u8]) -> Result<String, InfluxDbError> {
+    match status {
+        StatusCode::UNAUTHORIZED => return Err(InfluxDbError::AuthorizationError),
+        StatusCode::FORBIDDEN => return Err(InfluxDbError::AuthenticationError),
+        // successful writes do not return a body
+        StatusCode::NO_CONTENT => return Ok(String::new()),
+        status if status.is_redirection() => return Err(unfollowed_redirect(status)),
+        _ => {}
+    }
+
+    #[cfg(feature = "use-serde")]
+    {
+        use crate::integrations::serde_integration::{parse_database_error, parse_statement_error};
+
+        if let Some(error) = parse_database_error(body) {
+            return Err(error);
+        }
+        // the results are checked whatever the status, as failed statements are returned with status 200
+        match parse_statement_error(body) {
+            Some(Err(error)) => return Err(error),
+            Some(Ok(())) => return Ok(String::from_utf8_lossy(body).into_owned()),
+            None => {}
+        }
+    }
+
+    let utf8 = std::str::from_utf8(body);
+    if let Ok(s) = utf8 {
+        // todo: improve error parsing without serde
+        if s.contains("\"error\"") {
+            return Err(InfluxDbError::DatabaseError {
+                error: format!("influxdb error: \"{}\"", s),
+                code: None,
+            });
+        }
+    }
+
+    // error responses which are no InfluxDB errors, e.g. error pages of a proxy
+    if !status.is_success() {
+        return Err(unexpected_response(status, body));
+    }
+
+    if let Ok(s) = utf8 {
+        return Ok(s.to_owned());
+    }
+
+    Err(InfluxDbError::DeserializationError {
+        error: "response could not be converted to UTF-8".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::metrics::InfluxDbMetrics;
+    use crate::client::version::ServerVersion;
+    use crate::client::{
+        check_rate_limit, check_response, create_database_and_retry, delete_all_points_query,
+        follows_redirect, group_by_database, group_by_precision, ping_headers, point_query,
+        wait_until_ready, InfluxDbClient, ResultFormat, DEFAULT_MAX_ERROR_BODY_SIZE,
+    };
+    use crate::error::{ConnectionErrorKind, InfluxDbError};
+    use crate::query::read_query::InfluxDbReadQuery;
+    use crate::query::{InfluxDbQuery, Method, Precision, Timestamp};
+    use futures::future::{self, FutureResult};
+    use futures::Future;
+    use reqwest::header::{
+        HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER,
+    };
+    use reqwest::{StatusCode, Url};
+    use std::cell::{Cell, RefCell};
+    use std::net::TcpListener;
+    use std::rc::Rc;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::runtime::current_thread::Runtime;
+
+    #[derive(Default)]
+    struct RecordingMetrics {
+        started: Mutex<usize>,
+        ended: Mutex<Vec<(Option<StatusCode>, usize)>>,
+    }
+
+    impl InfluxDbMetrics for RecordingMetrics {
+        fn on_request_start(&self) {
+            *self.started.lock().unwrap() += 1;
+        }
+
+        fn on_request_end(&self, status: Option<StatusCode>, _duration: Duration, bytes: usize) {
+            self.ended.lock().unwrap().push((status, bytes));
+        }
+    }
+
+    #[test]
+    fn test_fn_database() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        assert_eq!("database", client.database_name());
+    }
+
+    #[test]
+    fn test_with_auth() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        assert_eq!(client.url, "http://localhost:8068");
+        assert_eq!(client.database, "database");
+        assert!(client.auth.is_none());
+        let with_auth = client.with_auth("username", "password");
+        assert!(with_auth.auth.is_some());
+        let auth = with_auth.auth.unwrap();
+        assert_eq!(&auth.username, "username");
+        assert_eq!(&auth.password, "password");
+    }
+
+    #[test]
+    fn test_as_user() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database")
+            .with_auth("reader", "password");
+        let query = InfluxDbQuery::raw_read_query("SHOW USERS");
+        let built = query.build().unwrap().get();
+
+        let admin_url = client
+            .as_user("admin", "secret")
+            .read_url(&query, &built)
+            .unwrap();
+        assert_eq!(
+            admin_url.as_str(),
+            "http://localhost:8068/query?db=database&u=admin&p=secret&q=SHOW+USERS"
+        );
+
+        let url = client.read_url(&query, &built).unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:8068/query?db=database&u=reader&p=password&q=SHOW+USERS"
+        );
+    }
+
+    #[test]
+    fn test_batch_write_split_precisions() {
+        let batch = [
+            InfluxDbQuery::write_query(Timestamp::MILLISECONDS(1_568_106_310_123), "weather")
+                .add_field("temperature", 82),
+            InfluxDbQuery::write_query(
+                Timestamp::NANOSECONDS(1_568_106_370_000_000_000),
+                "weather",
+            )
+            .add_field("temperature", 84),
+            InfluxDbQuery::write_query(Timestamp::MILLISECONDS(1_568_106_430_123), "weather")
+                .add_field("temperature", 86),
+        ];
+
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        match client.batch_write_requests(&batch) {
+            Err(InfluxDbError::InvalidQueryError { .. }) => {}
+            _ => panic!("Mixed precisions were accepted"),
+        }
+
+        let client = client.with_split_precisions(true);
+        let urls: Vec<String> = client
+            .batch_write_requests(&batch)
+            .unwrap()
+            .into_iter()
+            .map(|request| request.build().unwrap().url().to_string())
+            .collect();
+        assert_eq!(
+            urls,
+            vec![
+                "http://localhost:8068/write?db=database&precision=ms",
+                "http://localhost:8068/write?db=database&precision=ns"
+            ]
+        );
+        assert_eq!(
+            group_by_precision(&batch)
+                .iter()
+                .map(Vec::len)
+                .collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+
+        match client.batch_write_requests(&[]) {
+            Err(InfluxDbError::InvalidQueryError { .. }) => {}
+            _ => panic!("Empty batch was accepted"),
+        }
+    }
+
+    #[test]
+    fn test_group_by_database() {
+        let groups = group_by_database(&[
+            (
+                "tenant_a",
+                InfluxDbQuery::write_query(Timestamp::HOURS(1), "weather")
+                    .add_field("temperature", 82),
+            ),
+            (
+                "tenant_b",
+                InfluxDbQuery::write_query(Timestamp::HOURS(1), "weather")
+                    .add_field("temperature", 64),
+            ),
+            (
+                "tenant_a",
+                InfluxDbQuery::write_query(Timestamp::HOURS(2), "weather")
+                    .add_field("temperature", 84),
+            ),
+        ]);
+        let groups: Vec<(String, Vec<String>)> = groups
+            .into_iter()
+            .map(|(database, queries)| {
+                let queries = queries
+                    .iter()
+                    .map(|query| query.build().unwrap().get())
+                    .collect();
+                (database, queries)
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (
+                    "tenant_a".to_string(),
+                    vec![
+                        "weather temperature=82i 1".to_string(),
+                        "weather temperature=84i 2".to_string()
+                    ]
+                ),
+                (
+                    "tenant_b".to_string(),
+                    vec!["weather temperature=64i 1".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_for_database() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        assert_eq!(client.for_database("tenant_a").database_name(), "tenant_a");
+        // the client itself keeps writing to its own database
+        assert_eq!(client.database_name(), "database");
+
+        let client = client.with_v2_api().with_bucket("weather");
+        assert_eq!(client.for_database("tenant_a").bucket(), Some("tenant_a"));
+    }
+
+    #[test]
+    fn test_with_identity_pkcs12() {
+        let pkcs12 = include_bytes!("../../tests/fixtures/identity.p12");
+        let client = InfluxDbClient::new("https://localhost:8068", "database")
+            .with_identity_pkcs12(pkcs12, "password")
+            .unwrap();
+        assert!(client.identity.is_some());
+        assert!(client.http_client().is_ok());
+
+        let result = InfluxDbClient::new("https://localhost:8068", "database")
+            .with_identity_pkcs12(pkcs12, "wrong password");
+        match result {
+            Err(InfluxDbError::ConnectionError { kind, .. }) => {
+                assert_eq!(kind, ConnectionErrorKind::Tls)
+            }
+            _ => panic!("Should be a ConnectionError"),
+        }
+    }
+
+    #[test]
+    fn test_danger_accept_invalid_certs() {
+        let client = InfluxDbClient::new("https://localhost:8068", "database");
+        assert!(!client.accept_invalid_certs);
+
+        let client = client.danger_accept_invalid_certs(true);
+        assert!(client.accept_invalid_certs);
+        assert!(client.http_client().is_ok());
+        // the setting is kept when deriving a client
+        assert!(client.as_user("admin", "password").accept_invalid_certs);
+
+        let client = client.danger_accept_invalid_certs(false);
+        assert!(!client.accept_invalid_certs);
+    }
+
+    #[test]
+    fn test_tcp_nodelay() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        assert!(!client.tcp_nodelay);
+
+        let client = client.with_tcp_nodelay(true);
+        assert!(client.tcp_nodelay);
+        assert!(client.http_client().is_ok());
+        // the setting is kept when deriving a client
+        assert!(client.as_user("admin", "password").tcp_nodelay);
+    }
+
+    #[test]
+    fn test_request_url() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database")
+            .with_auth("username", "password");
+
+        let read_query =
+            InfluxDbQuery::raw_read_query("SELECT * FROM weather").with_epoch(Precision::SECONDS);
+        assert_eq!(
+            client.request_url(&read_query).unwrap().as_str(),
+            "http://localhost:8068/query?db=database&u=REDACTED&p=REDACTED&q=SELECT+*+FROM+weather&epoch=s"
+        );
+
+        let write_query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field("temperature", 82);
+        assert_eq!(
+            client.request_url(&write_query).unwrap().as_str(),
+            "http://localhost:8068/write?db=database&u=REDACTED&p=REDACTED&precision=h"
+        );
+
+        let without_auth = InfluxDbClient::new("http://localhost:8068", "database");
+        assert_eq!(
+            without_auth.request_url(&write_query).unwrap().as_str(),
+            "http://localhost:8068/write?db=database&precision=h"
+        );
+
+        let invalid = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather");
+        assert!(client.request_url(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_base_path() {
+        let client =
+            InfluxDbClient::new("http://localhost:8068", "database").with_base_path("influx/");
+        assert_eq!(
+            client.endpoint_url("ping"),
+            "http://localhost:8068/influx/ping"
+        );
+        assert_eq!(
+            client
+                .request_url(&InfluxDbQuery::raw_read_query("SELECT * FROM weather"))
+                .unwrap()
+                .as_str(),
+            "http://localhost:8068/influx/query?db=database&q=SELECT+*+FROM+weather"
+        );
+        assert_eq!(
+            client.write_url("s", &[]).unwrap().as_str(),
+            "http://localhost:8068/influx/write?db=database&precision=s"
+        );
+
+        let v2_client = client
+            .with_v2_api()
+            .with_org("my-org")
+            .with_bucket("weather");
+        assert_eq!(
+            v2_client.write_url("s", &[]).unwrap().as_str(),
+            "http://localhost:8068/influx/api/v2/write?org=my-org&bucket=weather&precision=s"
+        );
+
+        let root = InfluxDbClient::new("http://localhost:8068/", "database").with_base_path("/");
+        assert_eq!(root.endpoint_url("ping"), "http://localhost:8068/ping");
+    }
+
+    #[test]
+    fn test_database_override() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        let read_query =
+            InfluxDbQuery::raw_read_query("SELECT * FROM weather").with_database("archive");
+        assert_eq!(
+            client.request_url(&read_query).unwrap().as_str(),
+            "http://localhost:8068/query?db=archive&q=SELECT+*+FROM+weather"
+        );
+
+        let without_database = read_query.without_database();
+        assert_eq!(
+            client.request_url(&without_database).unwrap().as_str(),
+            "http://localhost:8068/query?q=SELECT+*+FROM+weather"
+        );
+
+        let select_query: InfluxDbReadQuery = InfluxDbQuery::select_query("weather")
+            .with_database("archive")
+            .into();
+        assert_eq!(
+            client.request_url(&select_query).unwrap().as_str(),
+            "http://localhost:8068/query?db=archive&q=SELECT+*+FROM+%22weather%22"
+        );
+
+        // management statements sent without a database stay without one
+        let show_databases = InfluxDbQuery::raw_read_query("SHOW DATABASES").without_database();
+        assert_eq!(
+            client.request_url(&show_databases).unwrap().as_str(),
+            "http://localhost:8068/query?q=SHOW+DATABASES"
+        );
+    }
+
+    #[test]
+    fn test_bind_params() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        let read_query =
+            InfluxDbQuery::raw_read_query("SELECT * FROM weather WHERE location = $location")
+                .bind_param("location", "us-midwest");
+        let url = client.request_url(&read_query).unwrap();
+
+        let pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert!(pairs.contains(&(
+            "q".to_string(),
+            "SELECT * FROM weather WHERE location = $location".to_string()
+        )));
+        assert!(pairs.contains(&(
+            "params".to_string(),
+            r#"{"location":"us-midwest"}"#.to_string()
+        )));
+    }
+
+    #[test]
+    fn test_into_impl() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        assert!(client.auth.is_none());
+        let basic_parameters: Vec<(String, String)> = client.into();
+        assert_eq!(
+            vec![("db".to_string(), "database".to_string())],
+            basic_parameters
+        );
+
+        let with_auth = InfluxDbClient::new("http://localhost:8068", "database")
+            .with_auth("username", "password");
+        let basic_parameters_with_auth: Vec<(String, String)> = with_auth.into();
+        assert_eq!(
+            vec![
+                ("db".to_string(), "database".to_string()),
+                ("u".to_string(), "username".to_string()),
+                ("p".to_string(), "password".to_string())
+            ],
+            basic_parameters_with_auth
+        );
+
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        assert!(client.auth.is_none());
+        let basic_parameters: Vec<(String, String)> = (&client).into();
+        assert_eq!(
+            vec![("db".to_string(), "database".to_string())],
+            basic_parameters
+        );
+
+        let with_auth = InfluxDbClient::new("http://localhost:8068", "database")
+            .with_auth("username", "password");
+        let basic_parameters_with_auth: Vec<(String, String)> = (&with_auth).into();
+        assert_eq!(
+            vec![
+                ("db".to_string(), "database".to_string()),
+                ("u".to_string(), "username".to_string()),
+                ("p".to_string(), "password".to_string())
+            ],
+            basic_parameters_with_auth
+        );
+    }
+
+    #[test]
+    fn test_metrics_without_response() {
+        // nothing listens on the port once the listener is dropped
+        let refused = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let metrics = Arc::new(RecordingMetrics::default());
+        let client = InfluxDbClient::new(format!("http://{}", refused), "database")
+            .with_metrics(metrics.clone());
+
+        assert!(client.ping().wait().is_err());
+        assert_eq!(*metrics.started.lock().unwrap(), 1);
+        assert_eq!(*metrics.ended.lock().unwrap(), vec![(None, 0)]);
+    }
+
+    #[test]
+    fn test_read_url() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        let query = InfluxDbQuery::raw_read_query("SELECT * FROM weather");
+        let url = client
+            .read_url(&query, &query.build().unwrap().get())
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:8068/query?db=database&q=SELECT+*+FROM+weather"
+        );
+    }
+
+    #[test]
+    fn test_read_url_node_id() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        let query = InfluxDbQuery::raw_read_query("SELECT * FROM weather").with_node_id(2);
+        let url = client
+            .read_url(&query, &query.build().unwrap().get())
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:8068/query?db=database&q=SELECT+*+FROM+weather&node_id=2"
+        );
+    }
+
+    #[test]
+    fn test_read_url_chunk_size() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        let query = InfluxDbQuery::raw_read_query("SELECT * FROM weather");
+        let url = client
+            .read_url(&query, &query.build().unwrap().get())
+            .unwrap();
+        assert!(
+            !url.as_str().contains("chunk"),
+            "Chunking was requested by default"
+        );
+
+        let query = query.with_chunk_size(100);
+        let url = client
+            .read_url(&query, &query.build().unwrap().get())
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:8068/query?db=database&q=SELECT+*+FROM+weather&chunked=true&chunk_size=100"
+        );
+    }
+
+    #[test]
+    fn test_read_url_without_database() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database")
+            .with_auth("username", "password");
+        let query = InfluxDbQuery::raw_read_query("SHOW DATABASES").without_database();
+        let url = client
+            .read_url(&query, &query.build().unwrap().get())
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:8068/query?u=username&p=password&q=SHOW+DATABASES"
+        );
+    }
+
+    #[test]
+    fn test_write_url_precision() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        let url = client
+            .write_url(&Precision::SECONDS.to_string(), &[])
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:8068/write?db=database&precision=s"
+        );
+
+        let url = client
+            .write_url(&Precision::MICROSECONDS.to_string(), &[])
+            .unwrap();
+        assert_eq!(url.query_pairs().last().unwrap().1, "u");
+    }
+
+    #[test]
+    fn test_check_response_no_content() {
+        assert_eq!(
+            check_response(StatusCode::NO_CONTENT, b"", DEFAULT_MAX_ERROR_BODY_SIZE).unwrap(),
+            ""
+        );
+        // the body of a successful write is never scanned for errors
+        assert_eq!(
+            check_response(
+                StatusCode::NO_CONTENT,
+                br#""error""#,
+                DEFAULT_MAX_ERROR_BODY_SIZE
+            )
+            .unwrap(),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_check_response_truncates_error_body() {
+        let page = format!(
+            "<html><body><h1>Bad Gateway</h1>{}</body></html>",
+            "ä".repeat(1_000_000)
+        );
+        match check_response(StatusCode::BAD_GATEWAY, page.as_bytes(), 100) {
+            Err(InfluxDbError::ProtocolError { error }) => {
+                assert!(error.len() <= 100 + "…".len());
+                assert!(error.ends_with('…'));
+                assert!(error.starts_with(
+                    "unexpected response (502 Bad Gateway): <html><body><h1>Bad Gateway</h1>"
+                ));
+            }
+            other => panic!("Should be a ProtocolError: {:?}", other),
+        }
+
+        match check_response(
+            StatusCode::BAD_GATEWAY,
+            b"<html>Bad Gateway</html>",
+            DEFAULT_MAX_ERROR_BODY_SIZE,
+        ) {
+            Err(InfluxDbError::ProtocolError { error }) => assert_eq!(
+                error,
+                "unexpected response (502 Bad Gateway): <html>Bad Gateway</html>"
+            ),
+            other => panic!("Should be a ProtocolError: {:?}", other),
+        }
+
+        let message = format!(r#"{{"error":"{}"}}"#, "x".repeat(10_000));
+        match check_response(
+            StatusCode::BAD_REQUEST,
+            message.as_bytes(),
+            DEFAULT_MAX_ERROR_BODY_SIZE,
+        ) {
+            Err(InfluxDbError::DatabaseError { error, .. }) => {
+                assert_eq!(error.len(), DEFAULT_MAX_ERROR_BODY_SIZE + "…".len());
+                assert!(error.ends_with("xxx…"));
+            }
+            other => panic!("Should be a DatabaseError: {:?}", other),
+        }
+
+        // short messages are kept as they are
+        match check_response(
+            StatusCode::BAD_REQUEST,
+            br#"{"error":"unable to parse"}"#,
+            100,
+        ) {
+            Err(InfluxDbError::DatabaseError { error, .. }) => {
+                assert!(error.contains("unable to parse") && !error.ends_with('…'))
+            }
+            other => panic!("Should be a DatabaseError: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_check_response_errors() {
+        match check_response(StatusCode::UNAUTHORIZED, b"", DEFAULT_MAX_ERROR_BODY_SIZE) {
+            Err(InfluxDbError::AuthorizationError) => {}
+            _ => panic!("Should be an AuthorizationError"),
+        }
+        match check_response(StatusCode::FORBIDDEN, b"", DEFAULT_MAX_ERROR_BODY_SIZE) {
+            Err(InfluxDbError::AuthenticationError) => {}
+            _ => panic!("Should be an AuthenticationError"),
+        }
+        match check_response(
+            StatusCode::BAD_REQUEST,
+            br#"{"error":"unable to parse"}"#,
+            DEFAULT_MAX_ERROR_BODY_SIZE,
+        ) {
+            Err(InfluxDbError::DatabaseError { .. }) => {}
+            _ => panic!("Should be a DatabaseError"),
+        }
+        assert_eq!(
+            check_response(
+                StatusCode::OK,
+                br#"{"results":[]}"#,
+                DEFAULT_MAX_ERROR_BODY_SIZE
+            )
+            .unwrap(),
+            r#"{"results":[]}"#
         );
+    }
 
-        let with_auth = InfluxDbClient::new("http://localhost:8068", "database")
-            .with_auth("username", "password");
-        let basic_parameters_with_auth: Vec<(String, String)> = with_auth.into();
+    #[test]
+    fn test_check_response_statement_error() {
+        let body = br#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","temperature"],"values":[[1,82]]}]},{"statement_id":1,"error":"database not found: test"}]}"#;
+        match check_response(StatusCode::OK, body, DEFAULT_MAX_ERROR_BODY_SIZE) {
+            #[cfg(feature = "use-serde")]
+            Err(InfluxDbError::DatabaseNotFound { name }) => assert_eq!(name, "test"),
+            #[cfg(not(feature = "use-serde"))]
+            Err(InfluxDbError::DatabaseError { .. }) => {}
+            other => panic!("Should be an error: {:?}", other),
+        }
+
+        // string values reading "error" are no errors
+        #[cfg(feature = "use-serde")]
+        {
+            let body = br#"{"results":[{"statement_id":0,"series":[{"name":"logs","columns":["time","level"],"values":[[1,"error"]]}]}]}"#;
+            assert_eq!(
+                check_response(StatusCode::OK, body, DEFAULT_MAX_ERROR_BODY_SIZE).unwrap(),
+                String::from_utf8_lossy(body)
+            );
+        }
+    }
+
+    #[cfg(feature = "use-serde")]
+    #[test]
+    fn test_check_response_missing_database() {
+        match check_response(
+            StatusCode::NOT_FOUND,
+            br#"{"error":"database not found: \"test\""}"#,
+            DEFAULT_MAX_ERROR_BODY_SIZE,
+        ) {
+            Err(InfluxDbError::DatabaseNotFound { name }) => assert_eq!(name, "test"),
+            other => panic!("Should be a DatabaseNotFound: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_create_database_and_retry() {
+        let writes = Rc::new(Cell::new(0));
+        let created = Rc::new(RefCell::new(vec![]));
+
+        let (attempts, creations) = (Rc::clone(&writes), Rc::clone(&created));
+        let result = create_database_and_retry(
+            move || {
+                attempts.set(attempts.get() + 1);
+                if attempts.get() == 1 {
+                    future::err(InfluxDbError::DatabaseNotFound {
+                        name: "test".to_string(),
+                    })
+                } else {
+                    future::ok(String::new())
+                }
+            },
+            move |name| {
+                creations.borrow_mut().push(name);
+                future::ok(String::new())
+            },
+        )
+        .wait();
+        assert!(result.is_ok());
+        assert_eq!(writes.get(), 2);
+        assert_eq!(*created.borrow(), vec!["test"]);
+
+        // other errors are not retried, and the write is only retried once
+        for (missing_database, expected_writes) in [(false, 1), (true, 2)] {
+            let writes = Rc::new(Cell::new(0));
+            let attempts = Rc::clone(&writes);
+            let result = create_database_and_retry(
+                move || {
+                    attempts.set(attempts.get() + 1);
+                    future::err::<String, _>(if missing_database {
+                        InfluxDbError::DatabaseNotFound {
+                            name: "test".to_string(),
+                        }
+                    } else {
+                        InfluxDbError::AuthorizationError
+                    })
+                },
+                |_| future::ok(String::new()),
+            )
+            .wait();
+            assert!(result.is_err());
+            assert_eq!(writes.get(), expected_writes);
+        }
+    }
+
+    #[test]
+    fn test_follows_redirect() {
+        let previous =
+            vec![Url::parse("http://localhost:8068/query?db=database&u=admin&p=secret").unwrap()];
+
+        let same_host =
+            Url::parse("http://localhost:8068/influx/query?db=database&u=admin&p=secret").unwrap();
+        assert!(follows_redirect(&same_host, &previous));
+        let upgrade =
+            Url::parse("https://localhost:8443/query?db=database&u=admin&p=secret").unwrap();
+        assert!(follows_redirect(&upgrade, &previous));
+
+        // the credentials are never sent to another host
+        let cross_host =
+            Url::parse("http://example.com:8068/query?db=database&u=admin&p=secret").unwrap();
+        assert!(!follows_redirect(&cross_host, &previous));
+        let without_credentials = Url::parse("http://example.com:8068/query?db=database").unwrap();
+        assert!(follows_redirect(&without_credentials, &previous));
+
+        let previous = vec![upgrade];
+        let downgrade =
+            Url::parse("http://localhost:8068/query?db=database&u=admin&p=secret").unwrap();
+        assert!(!follows_redirect(&downgrade, &previous));
+    }
+
+    #[test]
+    fn test_check_rate_limit() {
+        let mut headers = HeaderMap::new();
+        assert!(check_rate_limit(StatusCode::OK, &headers).is_ok());
+
+        match check_rate_limit(StatusCode::TOO_MANY_REQUESTS, &headers) {
+            Err(InfluxDbError::RateLimited { retry_after: None }) => {}
+            other => panic!("Should be RateLimited without delay, got {:?}", other),
+        }
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("5"));
+        match check_rate_limit(StatusCode::TOO_MANY_REQUESTS, &headers) {
+            Err(InfluxDbError::RateLimited { retry_after }) => {
+                assert_eq!(retry_after, Some(Duration::from_secs(5)))
+            }
+            other => panic!("Should be RateLimited, got {:?}", other),
+        }
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        match check_rate_limit(StatusCode::TOO_MANY_REQUESTS, &headers) {
+            Err(InfluxDbError::RateLimited { retry_after: None }) => {}
+            other => panic!("Should be RateLimited without delay, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_ping_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Influxdb-Build", HeaderValue::from_static("OSS"));
+        headers.insert("X-Influxdb-Version", HeaderValue::from_static("1.7.6"));
         assert_eq!(
-            vec![
-                ("db".to_string(), "database".to_string()),
-                ("u".to_string(), "username".to_string()),
-                ("p".to_string(), "password".to_string())
-            ],
-            basic_parameters_with_auth
+            ping_headers(StatusCode::NO_CONTENT, &headers).unwrap(),
+            (String::from("OSS"), String::from("1.7.6"))
+        );
+
+        match ping_headers(StatusCode::BAD_GATEWAY, &headers) {
+            Err(InfluxDbError::ProtocolError { error }) => {
+                assert_eq!(error, "ping failed with status 502 Bad Gateway")
+            }
+            other => panic!("Should be a ProtocolError, got {:?}", other),
+        }
+
+        headers.insert(
+            "X-Influxdb-Version",
+            HeaderValue::from_bytes(b"1.7.6\xff").unwrap(),
+        );
+        match ping_headers(StatusCode::NO_CONTENT, &headers) {
+            Err(InfluxDbError::ProtocolError { error }) => assert_eq!(
+                error,
+                "X-Influxdb-Version header of the ping response is not ASCII"
+            ),
+            other => panic!("Should be a ProtocolError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_ping_headers_missing() {
+        match ping_headers(StatusCode::NO_CONTENT, &HeaderMap::new()) {
+            Err(InfluxDbError::ProtocolError { error }) => assert_eq!(
+                error,
+                "ping response is missing the X-Influxdb-Build header"
+            ),
+            other => panic!("Should be a ProtocolError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_check_response_unfollowed_redirect() {
+        match check_response(
+            StatusCode::TEMPORARY_REDIRECT,
+            b"",
+            DEFAULT_MAX_ERROR_BODY_SIZE,
+        ) {
+            Err(InfluxDbError::ProtocolError { .. }) => {}
+            _ => panic!("Should be a ProtocolError"),
+        }
+    }
+
+    #[test]
+    fn test_default_precision() {
+        let now =
+            InfluxDbQuery::write_query(Timestamp::NOW, "weather").add_field("temperature", 82);
+        let hours = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field("temperature", 82);
+
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        assert_eq!(client.write_precision(&now), "");
+        assert_eq!(client.write_precision(&hours), "h");
+
+        let client = client.with_default_precision(Precision::MILLISECONDS);
+        assert_eq!(client.write_precision(&now), "ms");
+        assert_eq!(client.write_precision(&hours), "h");
+    }
+
+    #[test]
+    fn test_extra_params() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        let query = InfluxDbQuery::raw_read_query("SELECT * FROM weather")
+            .with_extra_param("rp", "one_week")
+            .with_extra_param("pretty", "true");
+        let url = client
+            .read_url(&query, &query.build().unwrap().get())
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:8068/query?db=database&q=SELECT+*+FROM+weather&rp=one_week&pretty=true"
         );
 
+        let write_query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field("temperature", 82)
+            .with_extra_param("consistency", "all");
+        let url = client
+            .write_url(&write_query.get_precision(), write_query.get_extra_params())
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:8068/write?db=database&precision=h&consistency=all"
+        );
+    }
+
+    #[test]
+    fn test_extra_params_reserved() {
         let client = InfluxDbClient::new("http://localhost:8068", "database");
-        assert!(client.auth.is_none());
-        let basic_parameters: Vec<(String, String)> = (&client).into();
+        let query =
+            InfluxDbQuery::raw_read_query("SELECT * FROM weather").with_extra_param("db", "other");
+        match client.query(&query).wait() {
+            Err(InfluxDbError::InvalidQueryError { error }) => assert_eq!(
+                error,
+                "\"db\" is set by the client and cannot be used as an extra parameter"
+            ),
+            _ => panic!("Should be an InvalidQueryError"),
+        }
+
+        let query = InfluxDbQuery::raw_read_query("SELECT * FROM weather")
+            .with_extra_param("chunked", "false");
+        assert!(client.query(&query).wait().is_err());
+
+        let write_query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field("temperature", 82)
+            .with_extra_param("precision", "s");
+        assert!(client.query(&write_query).wait().is_err());
+    }
+
+    #[test]
+    fn test_query_method() {
+        let client = InfluxDbClient::new("http://localhost:8086", "database");
+        let method = |query: &InfluxDbReadQuery| {
+            let request = client.query_request(query).unwrap().build().unwrap();
+            request.method().clone()
+        };
+
+        let select = InfluxDbQuery::raw_read_query("SELECT * FROM weather");
+        assert_eq!(method(&select), reqwest::Method::GET);
+        assert_eq!(method(&select.method(Method::Post)), reqwest::Method::POST);
+
+        let drop = InfluxDbQuery::raw_read_query("DROP SERIES FROM weather");
+        assert_eq!(method(&drop), reqwest::Method::POST);
+        assert_eq!(method(&drop.method(Method::Get)), reqwest::Method::GET);
+    }
+
+    #[test]
+    fn test_delete_all_points_query() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        let query = delete_all_points_query("weather \"berlin\"");
+
         assert_eq!(
-            vec![("db".to_string(), "database".to_string())],
-            basic_parameters
+            query.build().unwrap(),
+            "DELETE FROM \"weather \\\"berlin\\\"\""
         );
+        let request = client.query_request(&query).unwrap().build().unwrap();
+        assert_eq!(request.method(), reqwest::Method::POST);
+    }
 
-        let with_auth = InfluxDbClient::new("http://localhost:8068", "database")
-            .with_auth("username", "password");
-        let basic_parameters_with_auth: Vec<(String, String)> = (&with_auth).into();
+    #[test]
+    fn test_write_with_precision() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        let query = InfluxDbQuery::write_query(Timestamp::HOURS(1568106310), "weather")
+            .add_field("temperature", 82)
+            .with_extra_param("consistency", "all");
+
+        let request = client
+            .write_with_precision_request(&query, Precision::SECONDS)
+            .unwrap()
+            .build()
+            .unwrap();
         assert_eq!(
-            vec![
-                ("db".to_string(), "database".to_string()),
-                ("u".to_string(), "username".to_string()),
-                ("p".to_string(), "password".to_string())
-            ],
-            basic_parameters_with_auth
+            request.url().as_str(),
+            "http://localhost:8068/write?db=database&precision=s&consistency=all"
+        );
+        assert_eq!(request.method(), reqwest::Method::POST);
+
+        // the precision of the query is used otherwise
+        let request = client.query_request(&query).unwrap().build().unwrap();
+        assert_eq!(
+            request.url().as_str(),
+            "http://localhost:8068/write?db=database&precision=h&consistency=all"
+        );
+    }
+
+    #[test]
+    fn test_sample_rate() {
+        let queries: Vec<_> = (0..20)
+            .map(|hour| point_query("weather", "temperature", 82, Timestamp::HOURS(hour)))
+            .collect();
+        let sampled = |rate, seed| {
+            InfluxDbClient::new("http://localhost:8068", "database")
+                .with_seeded_sample_rate(rate, seed)
+                .with_max_batch_size(30)
+        };
+
+        // every request holds one point, so the number of requests is the number of written points
+        let written = sampled(0.5, 42)
+            .batch_write_requests(&queries)
+            .unwrap()
+            .len();
+        assert!(
+            written > 0 && written < 20,
+            "wrote {} of 20 points",
+            written
+        );
+        // the same seed writes the same points
+        assert_eq!(
+            sampled(0.5, 42)
+                .batch_write_requests(&queries)
+                .unwrap()
+                .len(),
+            written
+        );
+
+        let none = sampled(0.0, 42);
+        assert!(none.batch_write_requests(&queries).unwrap().is_empty());
+        assert_eq!(
+            none.query(&queries[0]).wait().unwrap(),
+            "",
+            "Sampled out points should not be sent"
+        );
+
+        let all = sampled(1.0, 42);
+        assert_eq!(all.batch_write_requests(&queries).unwrap().len(), 20);
+    }
+
+    #[test]
+    fn test_point_query() {
+        let query = point_query("weather", "temperature", 82, Timestamp::HOURS(11));
+        assert_eq!(query.build().unwrap(), "weather temperature=82i 11");
+
+        let query = point_query("wind", "gust", 12.5, Timestamp::NOW);
+        assert_eq!(query.build().unwrap(), "wind gust=12.5");
+
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        let request = client
+            .query_request(&point_query(
+                "weather",
+                "temperature",
+                82,
+                Timestamp::HOURS(11),
+            ))
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.url().as_str(),
+            "http://localhost:8068/write?db=database&precision=h"
+        );
+    }
+
+    #[test]
+    fn test_raw_request() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database")
+            .with_auth("admin", "p@ss")
+            .with_base_path("/influx");
+
+        let request = client
+            .raw_request(Method::Get, "/debug/vars", &[("pretty", "true")], None)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(request.method(), reqwest::Method::GET);
+        assert_eq!(
+            request.url().as_str(),
+            "http://localhost:8068/influx/debug/vars?u=admin&p=p%40ss&pretty=true"
+        );
+
+        let request = client
+            .raw_request(
+                Method::Post,
+                "api/v2/delete",
+                &[("bucket", "weather")],
+                Some("{}".to_string()),
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(request.method(), reqwest::Method::POST);
+        assert_eq!(
+            request.url().as_str(),
+            "http://localhost:8068/influx/api/v2/delete?u=admin&p=p%40ss&bucket=weather"
+        );
+        assert!(request.body().is_some());
+
+        match client.raw_request(Method::Get, "ping", &[("u", "root")], None) {
+            Err(InfluxDbError::InvalidQueryError { .. }) => {}
+            _ => panic!("Credentials were overridden"),
+        }
+    }
+
+    #[test]
+    fn test_create_database_if_not_exists() {
+        let query = InfluxDbQuery::create_database("weather").if_not_exists();
+        let statement = |client: &InfluxDbClient| {
+            let url = client.request_url(&query).unwrap();
+            let (_, statement) = url.query_pairs().find(|(key, _)| key == "q").unwrap();
+            statement.into_owned()
+        };
+
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        assert_eq!(statement(&client), "CREATE DATABASE \"weather\"");
+        let client = client.with_server_version(ServerVersion::new(1, 7, 6));
+        assert_eq!(statement(&client), "CREATE DATABASE \"weather\"");
+        let client = client.with_server_version(ServerVersion::new(0, 13, 0));
+        assert_eq!(
+            statement(&client),
+            "CREATE DATABASE IF NOT EXISTS \"weather\""
+        );
+    }
+
+    #[test]
+    fn test_validate_query() {
+        let client = InfluxDbClient::new("http://localhost:8086", "database");
+
+        let unbalanced =
+            InfluxDbQuery::raw_read_query("DROP SERIES FROM weather WHERE location = 'us-midwest");
+        match client.validate_query(&unbalanced).wait() {
+            Err(InfluxDbError::InvalidQueryError { error }) => {
+                assert_eq!(error, "unterminated string at position 42")
+            }
+            other => panic!("Unbalanced quote was not caught: {:?}", other),
+        }
+
+        // statements InfluxDB can not explain are only checked locally, without sending a request
+        let drop =
+            InfluxDbQuery::raw_read_query("DROP SERIES FROM weather WHERE location = 'us-midwest'");
+        assert!(client.validate_query(&drop).wait().is_ok());
+    }
+
+    #[test]
+    fn test_result_format_header() {
+        let query = InfluxDbQuery::raw_read_query("SELECT * FROM weather");
+        let accept = |client: &InfluxDbClient| {
+            let request = client.query_request(&query).unwrap().build().unwrap();
+            request.headers()[ACCEPT].to_str().unwrap().to_string()
+        };
+
+        let client = InfluxDbClient::new("http://localhost:8086", "database");
+        assert_eq!(client.result_format(), ResultFormat::Json);
+        assert_eq!(accept(&client), "application/json");
+        assert_eq!(
+            accept(&client.with_result_format(ResultFormat::Csv)),
+            "application/csv"
+        );
+    }
+
+    #[test]
+    fn test_empty_writes() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+
+        match client.batch_write(&[]).wait() {
+            Err(InfluxDbError::InvalidQueryError { .. }) => {}
+            _ => panic!("Should be an InvalidQueryError"),
+        }
+        match client.write_line_protocol(" \n", Precision::SECONDS).wait() {
+            Err(InfluxDbError::InvalidQueryError { error }) => {
+                assert_eq!(error, "write body cannot be empty")
+            }
+            _ => panic!("Should be an InvalidQueryError"),
+        }
+    }
+
+    #[test]
+    fn test_write_content_type() {
+        let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field("temperature", 82);
+        let content_type = |client: &InfluxDbClient| {
+            let request = client.query_request(&query).unwrap().build().unwrap();
+            let batch = client
+                .write_request(query.build().unwrap().get(), "h", &[])
+                .unwrap()
+                .build()
+                .unwrap();
+            assert_eq!(
+                request.headers()[CONTENT_TYPE],
+                batch.headers()[CONTENT_TYPE]
+            );
+            request.headers()[CONTENT_TYPE]
+                .to_str()
+                .unwrap()
+                .to_string()
+        };
+
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        assert_eq!(content_type(&client), "text/plain; charset=utf-8");
+        assert_eq!(
+            content_type(
+                &client
+                    .clone()
+                    .with_write_content_type("application/octet-stream")
+            ),
+            "application/octet-stream"
         );
+
+        // read queries do not send a body, so they have no content type
+        let read_query = InfluxDbQuery::raw_read_query("SELECT * FROM weather");
+        let request = client.query_request(&read_query).unwrap().build().unwrap();
+        assert!(request.headers().get(CONTENT_TYPE).is_none());
+
+        match client
+            .with_write_content_type("text/plain\n")
+            .query_request(&query)
+        {
+            Err(InfluxDbError::InvalidQueryError { .. }) => {}
+            _ => panic!("Invalid content type was accepted"),
+        }
+    }
+
+    #[test]
+    fn test_v2_api() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database")
+            .with_v2_api()
+            .with_org("my-org")
+            .with_bucket("weather");
+
+        let url = client.write_url("s", &[]).unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:8068/api/v2/write?org=my-org&bucket=weather&precision=s"
+        );
+
+        let query = InfluxDbQuery::raw_read_query("SELECT * FROM temperature");
+        let url = client
+            .read_url(&query, &query.build().unwrap().get())
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:8068/query?db=weather&q=SELECT+*+FROM+temperature"
+        );
+
+        let write_query = InfluxDbQuery::write_query(Timestamp::MICROSECONDS(11), "weather")
+            .add_field("temperature", 82);
+        let request = client.query_request(&write_query).unwrap().build().unwrap();
+        assert_eq!(
+            request.url().as_str(),
+            "http://localhost:8068/api/v2/write?org=my-org&bucket=weather&precision=us"
+        );
+        assert!(request.headers().get(AUTHORIZATION).is_none());
+
+        let write_query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field("temperature", 82);
+        match client.query_request(&write_query) {
+            Err(InfluxDbError::InvalidQueryError { error }) => assert_eq!(
+                error,
+                "the InfluxDB 2.x API does not support the precision \"h\", only ns, us, ms and s"
+            ),
+            _ => panic!("Should be an InvalidQueryError"),
+        }
+    }
+
+    #[test]
+    fn test_v2_api_token() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database")
+            .with_auth("admin", "password")
+            .with_v2_api()
+            .with_org("my-org")
+            .with_bucket("weather")
+            .with_token("my-token");
+
+        let write_query = InfluxDbQuery::write_query(Timestamp::SECONDS(11), "weather")
+            .add_field("temperature", 82);
+        let request = client.query_request(&write_query).unwrap().build().unwrap();
+        assert_eq!(
+            request.url().as_str(),
+            "http://localhost:8068/api/v2/write?org=my-org&bucket=weather&precision=s"
+        );
+        assert_eq!(request.headers()[AUTHORIZATION], "Token my-token");
+
+        let request = client
+            .write_request("weather temperature=82 11".to_string(), "ms", &[])
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(request.headers()[AUTHORIZATION], "Token my-token");
+
+        let query = InfluxDbQuery::raw_read_query("SELECT * FROM temperature");
+        let request = client.query_request(&query).unwrap().build().unwrap();
+        assert_eq!(request.headers()[AUTHORIZATION], "Token my-token");
+
+        // the token is only sent to the InfluxDB 2.x API
+        let client =
+            InfluxDbClient::new("http://localhost:8068", "database").with_token("my-token");
+        let request = client.query_request(&write_query).unwrap().build().unwrap();
+        assert!(request.headers().get(AUTHORIZATION).is_none());
+    }
+
+    #[test]
+    fn test_v2_api_requires_org_and_bucket() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database")
+            .with_v2_api()
+            .with_bucket("weather");
+        match client.write_url("s", &[]) {
+            Err(InfluxDbError::InvalidQueryError { .. }) => {}
+            _ => panic!("Should be an InvalidQueryError"),
+        }
+
+        let client = InfluxDbClient::new("http://localhost:8068", "database")
+            .with_v2_api()
+            .with_org("my-org");
+        let query = InfluxDbQuery::raw_read_query("SELECT * FROM temperature");
+        match client.read_url(&query, &query.build().unwrap().get()) {
+            Err(InfluxDbError::InvalidQueryError { .. }) => {}
+            _ => panic!("Should be an InvalidQueryError"),
+        }
+    }
+
+    #[test]
+    fn test_unsigned_fields_on_old_server() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database")
+            .with_server_version(ServerVersion::new(1, 5, 4));
+        let query =
+            InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather").add_field("count", 5u64);
+
+        match client.query(&query).wait() {
+            Err(InfluxDbError::InvalidQueryError { error }) => assert_eq!(
+                error,
+                "unsigned integer fields require InfluxDB 1.6.0 or later, but the server runs 1.5.4"
+            ),
+            _ => panic!("Should be an InvalidQueryError"),
+        }
+        match client.batch_write(std::slice::from_ref(&query)).wait() {
+            Err(InfluxDbError::InvalidQueryError { .. }) => {}
+            _ => panic!("Should be an InvalidQueryError"),
+        }
+
+        let signed =
+            InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather").add_field("count", 5i64);
+        assert!(client.check_server_support(&signed).is_ok());
+
+        let client = client.with_server_version(ServerVersion::new(1, 6, 0));
+        assert!(client.check_server_support(&query).is_ok());
+
+        let client = InfluxDbClient::new("http://localhost:8068", "database");
+        assert!(client.check_server_support(&query).is_ok());
+    }
+
+    fn failing_ping(
+        failures: usize,
+    ) -> impl FnMut(Duration) -> FutureResult<(String, String), InfluxDbError> {
+        let mut pings = 0;
+        move |_| {
+            pings += 1;
+            if pings > failures {
+                future::ok(("OSS".to_string(), "1.7.6".to_string()))
+            } else {
+                future::err(InfluxDbError::ProtocolError {
+                    error: "not ready".to_string(),
+                })
+            }
+        }
+    }
+
+    #[test]
+    fn test_wait_until_ready() {
+        let mut rt = Runtime::new().unwrap();
+        let mut attempts = 0;
+        let mut ping = failing_ping(2);
+        let ready = rt.block_on(wait_until_ready(
+            |remaining| {
+                attempts += 1;
+                ping(remaining)
+            },
+            Duration::from_secs(5),
+            Duration::from_millis(10),
+        ));
+
+        assert!(ready.is_ok());
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn test_wait_until_ready_timeout() {
+        let mut rt = Runtime::new().unwrap();
+        let ready = rt.block_on(wait_until_ready(
+            failing_ping(usize::MAX),
+            Duration::from_millis(50),
+            Duration::from_millis(10),
+        ));
+
+        match ready {
+            Err(InfluxDbError::TimeoutError { error }) => assert!(error.ends_with("not ready")),
+            _ => panic!("Should be a TimeoutError"),
+        }
+    }
+
+    #[test]
+    fn test_safe_mode() {
+        let client = InfluxDbClient::new("http://localhost:8068", "database").with_safe_mode(true);
+
+        let bounded =
+            InfluxDbQuery::raw_read_query("SELECT * FROM weather WHERE time > now() - 1h");
+        assert!(client
+            .read_url(&bounded, &bounded.build().unwrap().get())
+            .is_ok());
+
+        let unbounded = InfluxDbQuery::raw_read_query("SELECT * FROM weather");
+        match client.query(&unbounded).wait() {
+            Err(InfluxDbError::InvalidQueryError { .. }) => {}
+            _ => panic!("Should be an InvalidQueryError"),
+        }
+
+        let client = client.with_safe_mode(false);
+        assert!(client
+            .read_url(&unbounded, &unbounded.build().unwrap().get())
+            .is_ok());
     }
 }
@@ -0,0 +1,80 @@
+//! Client-side sampling of writes, see [`InfluxDbClient::with_sample_rate`](crate::client::InfluxDbClient::with_sample_rate)
+
+use rand::rngs::StdRng;
+use rand::{FromEntropy, Rng, SeedableRng};
+
+use std::sync::{Arc, Mutex};
+
+/// Decides randomly which points are written, keeping each with probability `rate`.
+///
+/// The random number generator is shared by the clones of a client, so a seeded sampler keeps a deterministic
+/// sequence of decisions across them.
+#[derive(Clone, Debug)]
+pub(crate) struct Sampler {
+    rate: f64,
+    rng: Arc<Mutex<StdRng>>,
+}
+
+impl Sampler {
+    /// Creates a sampler keeping points with probability `rate`, clamped to `0.0..=1.0`.
+    /// The random number generator is seeded with `seed`, or from the system's entropy source if there is none.
+    pub(crate) fn new(rate: f64, seed: Option<u64>) -> Self {
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Sampler {
+            // NaN is no valid probability, so it keeps every point
+            rate: if rate.is_nan() {
+                1.0
+            } else {
+                rate.clamp(0.0, 1.0)
+            },
+            rng: Arc::new(Mutex::new(rng)),
+        }
+    }
+
+    /// Returns whether the next point is written
+    pub(crate) fn keep(&self) -> bool {
+        self.rng.lock().unwrap().gen_bool(self.rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sampler;
+
+    #[test]
+    fn test_sampler_fraction() {
+        let sampler = Sampler::new(0.25, Some(42));
+        let kept = (0..10_000).filter(|_| sampler.keep()).count();
+        assert!(
+            kept > 2_300 && kept < 2_700,
+            "kept {} of 10000 points",
+            kept
+        );
+    }
+
+    #[test]
+    fn test_sampler_seed() {
+        let first = Sampler::new(0.5, Some(7));
+        let second = Sampler::new(0.5, Some(7));
+        let decisions: Vec<bool> = (0..100).map(|_| first.keep()).collect();
+        assert_eq!(
+            decisions,
+            (0..100).map(|_| second.keep()).collect::<Vec<bool>>()
+        );
+
+        // clones share the generator, continuing its sequence
+        let clone = first.clone();
+        assert_eq!(clone.keep(), second.keep());
+    }
+
+    #[test]
+    fn test_sampler_bounds() {
+        assert!((0..100).all(|_| Sampler::new(1.0, None).keep()));
+        assert!((0..100).all(|_| Sampler::new(f64::NAN, None).keep()));
+        assert!((0..100).all(|_| !Sampler::new(-1.0, None).keep()));
+        assert!((0..100).all(|_| Sampler::new(2.0, None).keep()));
+    }
+}
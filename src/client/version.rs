@@ -0,0 +1,111 @@
+//! Version of the InfluxDB server, as reported in the `X-Influxdb-Version` header
+//!
+//! # Examples
+//!
+//! ```rust
+//! use influxdb::client::version::ServerVersion;
+//!
+//! let version: ServerVersion = "1.7.6".parse().unwrap();
+//! assert_eq!(version, ServerVersion::new(1, 7, 6));
+//! assert!(version >= ServerVersion::new(1, 6, 0));
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::InfluxDbError;
+
+/// Semantic version of an InfluxDB server
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ServerVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl ServerVersion {
+    /// Creates a new [`ServerVersion`](crate::client::version::ServerVersion)
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        ServerVersion {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+/// Parses versions like `1.7.6`, `v2.0.0` or `1.8.10-c1.8.10`, ignoring a leading `v` and any pre-release
+/// or build suffix. A missing patch version is read as `0`.
+impl FromStr for ServerVersion {
+    type Err = InfluxDbError;
+
+    fn from_str(version: &str) -> Result<Self, Self::Err> {
+        let invalid = || InfluxDbError::ProtocolError {
+            error: format!("could not parse server version \"{}\"", version),
+        };
+
+        let trimmed = version.trim().trim_start_matches('v');
+        let end = trimmed
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(trimmed.len());
+        let parts = trimmed[..end]
+            .split('.')
+            .map(|part| part.parse::<u64>().map_err(|_| invalid()))
+            .collect::<Result<Vec<u64>, InfluxDbError>>()?;
+
+        match parts.as_slice() {
+            [major, minor] => Ok(ServerVersion::new(*major, *minor, 0)),
+            [major, minor, patch] => Ok(ServerVersion::new(*major, *minor, *patch)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for ServerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::version::ServerVersion;
+
+    #[test]
+    fn test_parse_version() {
+        let version: ServerVersion = "1.7.6".parse().unwrap();
+        assert_eq!(version.major, 1);
+        assert_eq!(version.minor, 7);
+        assert_eq!(version.patch, 6);
+    }
+
+    #[test]
+    fn test_parse_version_with_prefix_and_suffix() {
+        assert_eq!(
+            "v2.0.0-beta.5".parse::<ServerVersion>().unwrap(),
+            ServerVersion::new(2, 0, 0)
+        );
+        assert_eq!(
+            "1.8.10-c1.8.10".parse::<ServerVersion>().unwrap(),
+            ServerVersion::new(1, 8, 10)
+        );
+        assert_eq!(
+            "1.5".parse::<ServerVersion>().unwrap(),
+            ServerVersion::new(1, 5, 0)
+        );
+    }
+
+    #[test]
+    fn test_parse_version_invalid() {
+        assert!("unknown".parse::<ServerVersion>().is_err());
+        assert!("1".parse::<ServerVersion>().is_err());
+        assert!("1.2.3.4".parse::<ServerVersion>().is_err());
+    }
+
+    #[test]
+    fn test_version_ordering() {
+        assert!(ServerVersion::new(1, 6, 0) > ServerVersion::new(1, 5, 4));
+        assert!(ServerVersion::new(2, 0, 0) > ServerVersion::new(1, 8, 10));
+        assert_eq!(ServerVersion::new(1, 7, 6).to_string(), "1.7.6");
+    }
+}
@@ -1,6 +1,10 @@
 //! Errors that might happen in the crate
 use reqwest;
 
+use std::error::Error;
+use std::io;
+use std::time::Duration;
+
 #[derive(Debug, Fail)]
 pub enum InfluxDbError {
     #[fail(display = "query is invalid: {}", error)]
@@ -20,8 +24,17 @@ pub enum InfluxDbError {
     DeserializationError { error: String },
 
     #[fail(display = "InfluxDB encountered the following error: {}", error)]
-    /// Error which has happened inside InfluxDB
-    DatabaseError { error: String },
+    /// Error which has happened inside InfluxDB. `code` holds the error code or category,
+    /// if InfluxDB reported one alongside the message.
+    DatabaseError { error: String, code: Option<String> },
+
+    #[fail(display = "database not found: {}", name)]
+    /// Error happens when the database of a query or write does not exist, so callers can create it on demand
+    DatabaseNotFound { name: String },
+
+    #[fail(display = "retention policy not found: {}", name)]
+    /// Error happens when the retention policy of a query or write does not exist
+    RetentionPolicyNotFound { name: String },
 
     #[fail(display = "authentication error. No or incorrect credentials")]
     /// Error happens when no or incorrect credentials are used. `HTTP 401 Unauthorized`
@@ -31,10 +44,248 @@ pub enum InfluxDbError {
     /// Error happens when the supplied user is not authorized. `HTTP 403 Forbidden`
     AuthorizationError,
 
-    #[fail(display = "connection error: {}", error)]
-    /// Error happens when reqwest fails
+    #[fail(display = "timed out: {}", error)]
+    /// Error happens when InfluxDB does not become ready in time, see [`wait_until_ready`](crate::client::InfluxDbClient::wait_until_ready)
+    TimeoutError { error: String },
+
+    #[fail(display = "rate limited by the server, retry after {:?}", retry_after)]
+    /// Error happens when the server, or a gateway in front of it, rate limits the client. `HTTP 429 Too Many Requests`
+    ///
+    /// `retry_after` holds the delay of the `Retry-After` header, `None` if the response did not specify one in seconds.
+    RateLimited { retry_after: Option<Duration> },
+
+    #[fail(display = "connection error ({:?}): {}", kind, error)]
+    /// Error happens when reqwest fails. `kind` tells apart the most common causes.
     ConnectionError {
+        kind: ConnectionErrorKind,
         #[fail(cause)]
         error: reqwest::Error,
     },
 }
+
+/// Cause of a [`ConnectionError`](crate::error::InfluxDbError::ConnectionError)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConnectionErrorKind {
+    /// The server refused the connection, e.g. because InfluxDB is not running
+    ConnectionRefused,
+    /// The host name of the server could not be resolved
+    Dns,
+    /// The TLS handshake with the server failed
+    Tls,
+    /// The connection or request timed out
+    Timeout,
+    /// Any other failure
+    Other,
+}
+
+impl InfluxDbError {
+    /// Wraps an error of reqwest into a [`ConnectionError`](crate::error::InfluxDbError::ConnectionError),
+    /// classifying its cause
+    pub(crate) fn connection_error(error: reqwest::Error) -> Self {
+        let kind = if error.is_timeout() {
+            ConnectionErrorKind::Timeout
+        } else {
+            classify_connection_error(error.source())
+        };
+        InfluxDbError::ConnectionError { kind, error }
+    }
+
+    /// Creates the error for an error message returned by InfluxDB, using [`DatabaseNotFound`](crate::error::InfluxDbError::DatabaseNotFound)
+    /// and [`RetentionPolicyNotFound`](crate::error::InfluxDbError::RetentionPolicyNotFound) for missing resources
+    /// and [`DatabaseError`](crate::error::InfluxDbError::DatabaseError) for anything else
+    #[cfg(feature = "use-serde")]
+    pub(crate) fn database_error(error: String, code: Option<String>) -> Self {
+        if let Some(name) = not_found_name(&error, "database not found") {
+            return InfluxDbError::DatabaseNotFound { name };
+        }
+        if let Some(name) = not_found_name(&error, "retention policy not found") {
+            return InfluxDbError::RetentionPolicyNotFound { name };
+        }
+        InfluxDbError::DatabaseError { error, code }
+    }
+
+    /// Truncates the message of a [`DatabaseError`](crate::error::InfluxDbError::DatabaseError) or
+    /// [`ProtocolError`](crate::error::InfluxDbError::ProtocolError), which may be read from the response body,
+    /// to at most `max_len` bytes followed by `…`. Other errors are returned as they are.
+    pub(crate) fn truncated(self, max_len: usize) -> Self {
+        match self {
+            InfluxDbError::DatabaseError { error, code } => InfluxDbError::DatabaseError {
+                error: truncate(error, max_len),
+                code,
+            },
+            InfluxDbError::ProtocolError { error } => InfluxDbError::ProtocolError {
+                error: truncate(error, max_len),
+            },
+            error => error,
+        }
+    }
+}
+
+/// Truncates `message` to at most `max_len` bytes followed by `…`, if it is longer
+fn truncate(mut message: String, max_len: usize) -> String {
+    if message.len() <= max_len {
+        return message;
+    }
+    let mut end = max_len;
+    while !message.is_char_boundary(end) {
+        end -= 1;
+    }
+    message.truncate(end);
+    message.push('…');
+    message
+}
+
+#[cfg(feature = "use-serde")]
+/// Extracts the name of `<prefix>: "name"` or `<prefix>: name`, InfluxDB quoting the name in most but not all messages
+fn not_found_name(error: &str, prefix: &str) -> Option<String> {
+    let name = error.trim().strip_prefix(prefix)?.strip_prefix(':')?.trim();
+    let name = match name.strip_prefix('"') {
+        Some(quoted) => {
+            let mut name = String::new();
+            let mut chars = quoted.chars();
+            loop {
+                match chars.next()? {
+                    '\\' => name.push(chars.next()?),
+                    '"' => break,
+                    c => name.push(c),
+                }
+            }
+            name
+        }
+        None => name.to_string(),
+    };
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+/// Walks the chain of causes of a connection error to find out what went wrong
+fn classify_connection_error(mut cause: Option<&(dyn Error + 'static)>) -> ConnectionErrorKind {
+    while let Some(error) = cause {
+        if let Some(io_error) = error.downcast_ref::<io::Error>() {
+            match io_error.kind() {
+                io::ErrorKind::ConnectionRefused => return ConnectionErrorKind::ConnectionRefused,
+                io::ErrorKind::TimedOut => return ConnectionErrorKind::Timeout,
+                _ => {}
+            }
+        }
+
+        // resolver and TLS errors are not exposed as types by reqwest, so fall back to their messages
+        let message = error.to_string().to_lowercase();
+        if message.contains("dns")
+            || message.contains("lookup address")
+            || message.contains("name or service not known")
+        {
+            return ConnectionErrorKind::Dns;
+        }
+        if message.contains("tls") || message.contains("ssl") || message.contains("certificate") {
+            return ConnectionErrorKind::Tls;
+        }
+
+        cause = error.source();
+    }
+    ConnectionErrorKind::Other
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "use-serde")]
+    use super::InfluxDbError;
+    use super::{classify_connection_error, ConnectionErrorKind};
+    use std::io;
+
+    fn classify(error: io::Error) -> ConnectionErrorKind {
+        classify_connection_error(Some(&error))
+    }
+
+    #[test]
+    fn test_classify_connection_refused() {
+        assert_eq!(
+            classify(io::Error::from(io::ErrorKind::ConnectionRefused)),
+            ConnectionErrorKind::ConnectionRefused
+        );
+    }
+
+    #[test]
+    fn test_classify_timeout() {
+        assert_eq!(
+            classify(io::Error::from(io::ErrorKind::TimedOut)),
+            ConnectionErrorKind::Timeout
+        );
+    }
+
+    #[test]
+    fn test_classify_dns() {
+        assert_eq!(
+            classify(io::Error::other(
+                "failed to lookup address information: Name or service not known"
+            )),
+            ConnectionErrorKind::Dns
+        );
+    }
+
+    #[test]
+    fn test_classify_tls() {
+        assert_eq!(
+            classify(io::Error::other(
+                "the handshake failed: certificate verify failed"
+            )),
+            ConnectionErrorKind::Tls
+        );
+    }
+
+    #[test]
+    fn test_classify_other() {
+        assert_eq!(
+            classify(io::Error::other("connection reset")),
+            ConnectionErrorKind::Other
+        );
+        assert_eq!(classify_connection_error(None), ConnectionErrorKind::Other);
+    }
+
+    #[test]
+    #[cfg(feature = "use-serde")]
+    fn test_database_not_found() {
+        match InfluxDbError::database_error(
+            "database not found: \"my \\\"db\\\"\"".to_string(),
+            None,
+        ) {
+            InfluxDbError::DatabaseNotFound { name } => assert_eq!(name, "my \"db\""),
+            error => panic!("Should be DatabaseNotFound, not {:?}", error),
+        }
+        match InfluxDbError::database_error("database not found: weather".to_string(), None) {
+            InfluxDbError::DatabaseNotFound { name } => assert_eq!(name, "weather"),
+            error => panic!("Should be DatabaseNotFound, not {:?}", error),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "use-serde")]
+    fn test_retention_policy_not_found() {
+        match InfluxDbError::database_error(
+            "retention policy not found: autogen".to_string(),
+            Some("not found".to_string()),
+        ) {
+            InfluxDbError::RetentionPolicyNotFound { name } => assert_eq!(name, "autogen"),
+            error => panic!("Should be RetentionPolicyNotFound, not {:?}", error),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "use-serde")]
+    fn test_other_database_errors() {
+        for message in &[
+            "retention policy not found",
+            "database not found: \"unterminated",
+            "measurement not found",
+            "error parsing query: database not found: weather",
+        ] {
+            match InfluxDbError::database_error(message.to_string(), None) {
+                InfluxDbError::DatabaseError { error, .. } => assert_eq!(&error, message),
+                error => panic!("Should be a DatabaseError, not {:?}", error),
+            }
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Conditional writes, writing a point only if the value of a field changed since the last point of its series.
+//!
+//! This saves storing long runs of the same value, e.g. of a sensor reporting its state periodically, at the cost
+//! of a `SELECT LAST()` round trip before every write. The read and the write are separate requests, so concurrent
+//! writers to the same series can still both write the same value.
+//!
+//! ```rust,no_run
+//! use futures::prelude::*;
+//! use influxdb::client::InfluxDbClient;
+//! use influxdb::query::{InfluxDbQuery, Timestamp};
+//!
+//! let mut rt = tokio::runtime::current_thread::Runtime::new().unwrap();
+//! let client = InfluxDbClient::new("http://localhost:8086", "test");
+//!
+//! let point = InfluxDbQuery::write_query(Timestamp::NOW, "door").add_tag("room", "kitchen");
+//! let written = rt.block_on(client.write_if_changed(point, "open", true)).unwrap();
+//! println!("state changed: {}", written);
+//! ```
+
+use futures::future::{self, Either};
+use futures::Future;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::client::{InfluxDbClient, InfluxDbQueryExecutor};
+use crate::error::InfluxDbError;
+use crate::integrations::serde_integration::DatabaseQueryResult;
+use crate::query::read_query::InfluxDbReadQuery;
+use crate::query::select_query::{Field, InfluxDbSelectQuery};
+use crate::query::write_query::{is_empty_tag_value, tag_value, InfluxDbType, InfluxDbWriteQuery};
+use crate::query::{quote_identifier, quote_string_literal};
+
+impl InfluxDbClient {
+    /// Writes `point` with the field `field` set to `value`, unless the last value of `field` in the series of
+    /// the point equals `value`. See [`write_if_changed`](crate::integrations::conditional_write::write_if_changed).
+    pub fn write_if_changed<S, T>(
+        &self,
+        point: InfluxDbWriteQuery,
+        field: S,
+        value: T,
+    ) -> Box<dyn Future<Item = bool, Error = InfluxDbError> + '_>
+    where
+        S: ToString,
+        T: Into<InfluxDbType> + DeserializeOwned + PartialEq + 'static,
+    {
+        write_if_changed(self, point, field, value)
+    }
+}
+
+/// Writes `point` with the field `field` set to `value` using `executor`, unless the last value of `field` in the
+/// series of the point equals `value`. Resolves to whether the point was written.
+///
+/// The last value is read using `SELECT LAST(field)` from the measurement of the point, restricted to the tags
+/// of the point, and deserialized as `T` for the comparison. The point is written if the series has no value for
+/// `field` yet. As only the tags of the point are known, the last value of all series sharing these tags is used.
+///
+/// The response of the read is expected to be JSON, i.e. [`ResultFormat::Json`](crate::client::ResultFormat::Json).
+///
+/// # Examples
+///
+/// ```rust
+/// use futures::Future;
+/// use influxdb::client::mock::MockInfluxDbClient;
+/// use influxdb::integrations::conditional_write::write_if_changed;
+/// use influxdb::query::{InfluxDbQuery, Timestamp};
+///
+/// let mock = MockInfluxDbClient::new().with_response(Ok(r#"{"results":[{"statement_id":0}]}"#.to_string()));
+/// let point = InfluxDbQuery::write_query(Timestamp::HOURS(11), "door").add_tag("room", "kitchen");
+///
+/// assert!(write_if_changed(&mock, point, "open", true).wait().unwrap());
+/// assert_eq!(
+///     mock.queries(),
+///     vec![
+///         "SELECT LAST(\"open\") FROM \"door\" WHERE \"room\" = '\"kitchen\"' LIMIT 1",
+///         "door,room=\"kitchen\" open=true 11"
+///     ]
+/// );
+/// ```
+pub fn write_if_changed<'a, E, S, T>(
+    executor: &'a E,
+    point: InfluxDbWriteQuery,
+    field: S,
+    value: T,
+) -> Box<dyn Future<Item = bool, Error = InfluxDbError> + 'a>
+where
+    E: InfluxDbQueryExecutor,
+    S: ToString,
+    T: Into<InfluxDbType> + DeserializeOwned + PartialEq + 'static,
+{
+    let field = field.to_string();
+    let write = executor
+        .query(&last_value_query(&point, &field))
+        .and_then(|body| {
+            serde_json::from_str::<DatabaseQueryResult>(&body).map_err(|err| {
+                InfluxDbError::DeserializationError {
+                    error: format!("could not deserialize last value: {}", err),
+                }
+            })
+        })
+        .and_then(|mut result| result.deserialize_next_rows::<(Value, T)>())
+        .and_then(move |rows| match rows.last() {
+            Some((_, last)) if *last == value => Either::A(future::ok(false)),
+            _ => Either::B(executor.query(&point.add_field(field, value)).map(|_| true)),
+        });
+    Box::new(write)
+}
+
+/// Creates the `SELECT LAST(field)` statement reading the last value of `field` in the series of `point`.
+/// `LAST` returns a single row anyway, the `LIMIT` lets the statement pass the safe mode of the client.
+fn last_value_query(point: &InfluxDbWriteQuery, field: &str) -> InfluxDbReadQuery {
+    let mut query = InfluxDbSelectQuery::new(point.get_measurement()).add_field(Field::last(field));
+    if !point.get_tags().is_empty() {
+        // tags are matched as rendered into the line protocol, where empty tags are left out
+        let conditions = point.get_tags().iter().map(|(tag, value)| {
+            let value = if is_empty_tag_value(value) {
+                String::new()
+            } else {
+                tag_value(value)
+            };
+            format!(
+                "{} = {}",
+                quote_identifier(tag),
+                quote_string_literal(&value)
+            )
+        });
+        query = query.where_clause(conditions.collect::<Vec<_>>().join(" AND "));
+    }
+    query.limit(1).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{last_value_query, write_if_changed};
+    use crate::client::mock::MockInfluxDbClient;
+    use crate::client::InfluxDbClient;
+    use crate::query::{InfluxDbQuery, Timestamp};
+
+    use futures::Future;
+
+    fn last_temperature(temperature: f64) -> String {
+        format!(
+            r#"{{"results":[{{"statement_id":0,"series":[{{"name":"weather","columns":["time","last"],"values":[["2019-09-10T09:05:10Z",{}]]}}]}}]}}"#,
+            temperature
+        )
+    }
+
+    #[test]
+    fn test_write_if_changed() {
+        let point = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_tag("location", "us-midwest");
+
+        let mock = MockInfluxDbClient::new().with_response(Ok(last_temperature(82.5)));
+        assert!(!write_if_changed(&mock, point.clone(), "temperature", 82.5)
+            .wait()
+            .unwrap());
+        assert_eq!(mock.queries().len(), 1, "Unchanged value was written");
+
+        let mock = MockInfluxDbClient::new().with_response(Ok(last_temperature(82.5)));
+        assert!(write_if_changed(&mock, point.clone(), "temperature", 84.0)
+            .wait()
+            .unwrap());
+        assert_eq!(
+            mock.queries(),
+            vec![
+                "SELECT LAST(\"temperature\") FROM \"weather\" WHERE \"location\" = '\"us-midwest\"' LIMIT 1",
+                "weather,location=\"us-midwest\" temperature=84 11"
+            ]
+        );
+
+        // a series without values is written
+        let mock = MockInfluxDbClient::new()
+            .with_response(Ok(r#"{"results":[{"statement_id":0}]}"#.to_string()));
+        assert!(write_if_changed(&mock, point, "temperature", 82.5)
+            .wait()
+            .unwrap());
+        assert_eq!(mock.queries().len(), 2);
+    }
+
+    #[test]
+    fn test_write_if_changed_read_error() {
+        let mock = MockInfluxDbClient::new().with_response(Ok(
+            r#"{"results":[{"statement_id":0,"error":"database not found: test"}]}"#.to_string(),
+        ));
+        let point = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather");
+
+        assert!(write_if_changed(&mock, point, "temperature", 82.5)
+            .wait()
+            .is_err());
+        assert_eq!(
+            mock.queries().len(),
+            1,
+            "Point was written after a failed read"
+        );
+    }
+
+    #[test]
+    fn test_last_value_query() {
+        let point = InfluxDbQuery::write_query(Timestamp::NOW, "weather")
+            .add_tag("station", 1)
+            .add_tag("owner", "it's")
+            .add_tag("location", "");
+
+        assert_eq!(
+            last_value_query(&point, "temperature").build().unwrap(),
+            "SELECT LAST(\"temperature\") FROM \"weather\" WHERE \"station\" = '1' AND \"owner\" = '\"it\\'s\"' AND \"location\" = '' LIMIT 1"
+        );
+        assert_eq!(
+            last_value_query(
+                &InfluxDbQuery::write_query(Timestamp::NOW, "weather"),
+                "temperature"
+            )
+            .build()
+            .unwrap(),
+            "SELECT LAST(\"temperature\") FROM \"weather\" LIMIT 1"
+        );
+    }
+
+    #[test]
+    fn test_last_value_query_safe_mode() {
+        let client = InfluxDbClient::new("http://localhost:8086", "test").with_safe_mode(true);
+        let point = InfluxDbQuery::write_query(Timestamp::NOW, "weather").add_tag("station", 1);
+
+        let query = last_value_query(&point, "temperature");
+        assert!(client
+            .read_url(&query, &query.build().unwrap().get())
+            .is_ok());
+    }
+}
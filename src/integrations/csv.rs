@@ -0,0 +1,181 @@
+//! Conversion of query results returned as CSV (`Accept: application/csv`) into the structure of JSON results,
+//! so they can be deserialized like them. See [`ResultFormat`](crate::client::ResultFormat).
+//!
+//! InfluxDB writes a header row `name,tags,<columns>` followed by one row per point, separating results with
+//! other columns by an empty line. The CSV does not carry statement ids, so every block of rows below a header is
+//! treated as the result of its own statement. Values are typed by their appearance, i.e. a string field
+//! containing `1` is read as a number.
+
+use serde_json::{json, Map, Number, Value};
+use std::mem;
+
+use crate::error::InfluxDbError;
+
+/// Converts a CSV query response into the `results` of a JSON query response
+pub(crate) fn csv_results(body: &str) -> Result<Vec<Value>, InfluxDbError> {
+    let mut results = vec![];
+    let mut block: Vec<Vec<String>> = vec![];
+    for record in records(body) {
+        if record.len() == 1 && record[0].is_empty() {
+            if !block.is_empty() {
+                results.push(statement(results.len(), &block)?);
+                block.clear();
+            }
+        } else {
+            block.push(record);
+        }
+    }
+    if !block.is_empty() {
+        results.push(statement(results.len(), &block)?);
+    }
+    Ok(results)
+}
+
+/// Converts a header row and the following rows into the result of a statement
+fn statement(statement_id: usize, block: &[Vec<String>]) -> Result<Value, InfluxDbError> {
+    let header = &block[0];
+    if header.len() < 2 || header[0] != "name" || header[1] != "tags" {
+        return Err(InfluxDbError::DeserializationError {
+            error: format!("unexpected CSV header: {}", header.join(",")),
+        });
+    }
+    let columns = &header[2..];
+
+    let mut series: Vec<Value> = vec![];
+    let mut current: Option<(&str, &str)> = None;
+    for row in &block[1..] {
+        if row.len() != header.len() {
+            return Err(InfluxDbError::DeserializationError {
+                error: format!(
+                    "CSV row has {} values, but the header has {} columns",
+                    row.len(),
+                    header.len()
+                ),
+            });
+        }
+        let values: Vec<Value> = row[2..].iter().map(|value| typed(value)).collect();
+
+        // consecutive rows with the same name and tags belong to the same series
+        if current != Some((&row[0], &row[1])) {
+            current = Some((&row[0], &row[1]));
+            series.push(json!({
+                "name": row[0],
+                "tags": tags(&row[1]),
+                "columns": columns,
+                "values": [],
+            }));
+        }
+        if let Some(Value::Array(rows)) = series.last_mut().and_then(|last| last.get_mut("values"))
+        {
+            rows.push(Value::Array(values));
+        }
+    }
+
+    Ok(json!({ "statement_id": statement_id, "series": series }))
+}
+
+/// Parses the `tags` column, e.g. `location=us-midwest,station=1`
+fn tags(tags: &str) -> Map<String, Value> {
+    tags.split(',')
+        .filter_map(|tag| {
+            let mut key_value = tag.splitn(2, '=');
+            match (key_value.next(), key_value.next()) {
+                (Some(key), Some(value)) if !key.is_empty() => {
+                    Some((key.to_string(), Value::String(value.to_string())))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Types a CSV value like InfluxDB would have in JSON, empty values being `null`
+fn typed(value: &str) -> Value {
+    if value.is_empty() {
+        return Value::Null;
+    }
+    if let Ok(integer) = value.parse::<i64>() {
+        return Value::Number(integer.into());
+    }
+    if let Some(float) = value.parse::<f64>().ok().and_then(Number::from_f64) {
+        return Value::Number(float);
+    }
+    match value {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => Value::String(value.to_string()),
+    }
+}
+
+/// Splits `body` into records of fields, unquoting quoted fields. Empty lines result in a record with a single empty field.
+fn records(body: &str) -> Vec<Vec<String>> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = body.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(mem::take(&mut field));
+                records.push(mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::csv_results;
+    use serde_json::json;
+
+    #[test]
+    fn test_csv_results() {
+        let results = csv_results(
+            "name,tags,time,temperature,location\r\n\
+             weather,\"station=1,type=outdoor\",1568106310000000000,82,\"us, midwest\"\r\n\
+             weather,\"station=1,type=outdoor\",1568106370000000000,84.5,\r\n\
+             weather,station=2,1568106310000000000,64,\"say \"\"hi\"\"\"\r\n\
+             \r\n\
+             name,tags,name\r\n\
+             databases,,weather\r\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            results,
+            vec![
+                json!({"statement_id": 0, "series": [
+                    {"name": "weather", "tags": {"station": "1", "type": "outdoor"}, "columns": ["time", "temperature", "location"],
+                     "values": [[1568106310000000000i64, 82, "us, midwest"], [1568106370000000000i64, 84.5, null]]},
+                    {"name": "weather", "tags": {"station": "2"}, "columns": ["time", "temperature", "location"],
+                     "values": [[1568106310000000000i64, 64, "say \"hi\""]]},
+                ]}),
+                json!({"statement_id": 1, "series": [
+                    {"name": "databases", "tags": {}, "columns": ["name"], "values": [["weather"]]},
+                ]}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_csv_results_invalid() {
+        assert!(csv_results("time,temperature\n1,82\n").is_err());
+        assert!(csv_results("name,tags,time\nweather,,1,82\n").is_err());
+        assert!(csv_results("").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+//! Runtime diagnostics and statistics of the InfluxDB server, as returned by `SHOW DIAGNOSTICS` and `SHOW STATS`.
+//!
+//! ```rust,no_run
+//! use futures::prelude::*;
+//! use influxdb::client::InfluxDbClient;
+//!
+//! let mut rt = tokio::runtime::current_thread::Runtime::new().unwrap();
+//! let client = InfluxDbClient::new("http://localhost:8086", "test");
+//!
+//! let diagnostics = rt.block_on(client.show_diagnostics()).unwrap();
+//! println!("running InfluxDB {}", diagnostics["build"]["Version"]);
+//!
+//! for statistics in rt.block_on(client.show_stats()).unwrap() {
+//!     println!("{} {:?}: {:?}", statistics.name, statistics.tags, statistics.values);
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use futures::Future;
+use serde_json::Value;
+
+use crate::client::InfluxDbClient;
+use crate::error::InfluxDbError;
+use crate::integrations::serde_integration::InfluxDbReturn;
+use crate::query::read_query::InfluxDbReadQuery;
+
+/// Diagnostics of `SHOW DIAGNOSTICS`, by module (e.g. `build`, `runtime` or `system`) and key
+pub type Diagnostics = BTreeMap<String, BTreeMap<String, Value>>;
+
+/// A series of `SHOW STATS`, e.g. the `httpd` statistics of the HTTP service listening on `:8086`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Statistics {
+    /// Module the statistics belong to, e.g. `runtime`, `httpd` or `shard`
+    pub name: String,
+    /// Tags telling apart the instances of a module, e.g. the `bind` address or the shard `id`
+    pub tags: BTreeMap<String, String>,
+    pub values: BTreeMap<String, Value>,
+}
+
+impl InfluxDbClient {
+    /// Returns the diagnostics of the server using `SHOW DIAGNOSTICS`, such as its build, configuration and uptime
+    pub fn show_diagnostics(&self) -> impl Future<Item = Diagnostics, Error = InfluxDbError> {
+        self.json_query(InfluxDbReadQuery::new("SHOW DIAGNOSTICS"))
+            .and_then(|mut result| result.deserialize_next::<Vec<Value>>())
+            .map(diagnostics)
+    }
+
+    /// Returns the statistics of the modules of the server using `SHOW STATS`, one entry per module instance
+    pub fn show_stats(&self) -> impl Future<Item = Vec<Statistics>, Error = InfluxDbError> {
+        self.json_query(InfluxDbReadQuery::new("SHOW STATS"))
+            .and_then(|mut result| result.deserialize_next::<Vec<Value>>())
+            .map(statistics)
+    }
+}
+
+/// Collects the series of a `SHOW DIAGNOSTICS` result by module
+fn diagnostics(result: InfluxDbReturn<Vec<Value>>) -> Diagnostics {
+    result
+        .series
+        .into_iter()
+        .map(|series| (series.name, by_column(&series.columns, series.values)))
+        .collect()
+}
+
+/// Converts the series of a `SHOW STATS` result, which contain a single row each
+fn statistics(result: InfluxDbReturn<Vec<Value>>) -> Vec<Statistics> {
+    result
+        .series
+        .into_iter()
+        .map(|series| Statistics {
+            values: by_column(&series.columns, series.values),
+            name: series.name,
+            tags: series.tags,
+        })
+        .collect()
+}
+
+/// Maps the values of `rows` to their column names
+fn by_column(columns: &[String], rows: Vec<Vec<Value>>) -> BTreeMap<String, Value> {
+    rows.into_iter()
+        .flat_map(|row| columns.iter().cloned().zip(row).collect::<Vec<_>>())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diagnostics, statistics};
+    use crate::integrations::serde_integration::DatabaseQueryResult;
+
+    use futures::Future;
+    use serde_json::json;
+
+    #[test]
+    fn test_diagnostics() {
+        let mut result = serde_json::from_str::<DatabaseQueryResult>(
+            r#"{"results":[{"statement_id":0,"series":[
+                {"name":"build","columns":["Branch","Commit","Version"],"values":[["1.7","ff383cdc0420217e3460dabe17db54f8557d95b6","1.7.6"]]},
+                {"name":"runtime","columns":["GOARCH","GOMAXPROCS","GOOS","version"],"values":[["amd64",4,"linux","go1.11"]]},
+                {"name":"system","columns":["PID","currentTime","started","uptime"],"values":[[1,"2019-09-10T09:05:10Z","2019-09-10T08:05:10Z","1h0m0s"]]}
+            ]}]}"#,
+        )
+        .unwrap();
+        let diagnostics = diagnostics(result.deserialize_next().wait().unwrap());
+
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics["build"]["Version"], json!("1.7.6"));
+        assert_eq!(diagnostics["runtime"]["GOMAXPROCS"], json!(4));
+        assert_eq!(diagnostics["system"]["uptime"], json!("1h0m0s"));
+    }
+
+    #[test]
+    fn test_statistics() {
+        let mut result = serde_json::from_str::<DatabaseQueryResult>(
+            r#"{"results":[{"statement_id":0,"series":[
+                {"name":"runtime","columns":["Alloc","NumGoroutine"],"values":[[4507488,29]]},
+                {"name":"httpd","tags":{"bind":":8086"},"columns":["queryReq","writeReq"],"values":[[12,34]]},
+                {"name":"shard","tags":{"database":"weather","id":"2"},"columns":["diskBytes"],"values":[[1024]]},
+                {"name":"shard","tags":{"database":"weather","id":"3"},"columns":["diskBytes"],"values":[[2048]]}
+            ]}]}"#,
+        )
+        .unwrap();
+        let statistics = statistics(result.deserialize_next().wait().unwrap());
+
+        assert_eq!(statistics.len(), 4);
+        assert_eq!(statistics[0].name, "runtime");
+        assert!(statistics[0].tags.is_empty());
+        assert_eq!(statistics[0].values["NumGoroutine"], json!(29));
+
+        assert_eq!(statistics[1].name, "httpd");
+        assert_eq!(statistics[1].tags["bind"], ":8086");
+        assert_eq!(statistics[1].values["queryReq"], json!(12));
+        assert_eq!(statistics[1].values["writeReq"], json!(34));
+
+        // the statistics of multiple instances of a module are kept apart by their tags
+        assert_eq!(statistics[2].tags["id"], "2");
+        assert_eq!(statistics[2].values["diskBytes"], json!(1024));
+        assert_eq!(statistics[3].tags["id"], "3");
+        assert_eq!(statistics[3].values["diskBytes"], json!(2048));
+    }
+}
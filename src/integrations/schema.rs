@@ -0,0 +1,642 @@
+//! Schema discovery for InfluxDB. Lists the measurements of a database together with their tag and field keys.
+//!
+//! ```rust,no_run
+//! use futures::prelude::*;
+//! use influxdb::client::InfluxDbClient;
+//!
+//! let mut rt = tokio::runtime::current_thread::Runtime::new().unwrap();
+//! let client = InfluxDbClient::new("http://localhost:8086", "test");
+//! let schema = rt.block_on(client.schema().collect()).unwrap();
+//! for measurement in schema {
+//!     println!(
+//!         "{}: tags {:?}, fields {:?}",
+//!         measurement.name, measurement.tag_keys, measurement.field_keys
+//!     );
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use futures::{stream, Future, Stream};
+use serde_json::Value;
+
+use crate::client::InfluxDbClient;
+use crate::error::InfluxDbError;
+use crate::integrations::serde_integration::{DatabaseQueryResult, InfluxDbReturn};
+use crate::query::quote_identifier;
+use crate::query::read_query::InfluxDbReadQuery;
+
+/// A measurement together with its tag and field keys
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeasurementSchema {
+    pub name: String,
+    pub tag_keys: Vec<String>,
+    pub field_keys: Vec<FieldKey>,
+}
+
+/// A field key together with the type of the field, as returned by `SHOW FIELD KEYS`
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldKey {
+    pub name: String,
+    /// One of `float`, `integer`, `unsigned`, `string` or `boolean`
+    pub field_type: String,
+}
+
+/// A series key as returned by `SHOW SERIES`, e.g. `weather,location=us-midwest`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeriesKey {
+    pub measurement: String,
+    pub tags: BTreeMap<String, String>,
+}
+
+impl InfluxDbClient {
+    /// Returns a stream of the measurements of the database of the client, including their tag and field keys.
+    ///
+    /// The measurements are listed using `SHOW MEASUREMENTS`. The tag and field keys of a measurement are only
+    /// queried once the stream is polled for it.
+    pub fn schema(&self) -> impl Stream<Item = MeasurementSchema, Error = InfluxDbError> {
+        let client = self.clone();
+        schema(move |read_query| client.json_query(read_query))
+    }
+
+    /// Lists the series of the database of the client using `SHOW SERIES`, optionally restricted to the measurement
+    /// `from` and to the series matching the InfluxQL condition `where_clause`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use futures::Future;
+    /// use influxdb::client::InfluxDbClient;
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let series = client
+    ///     .show_series(Some("weather"), Some("location = 'us-midwest'"))
+    ///     .wait()
+    ///     .unwrap();
+    /// println!("{} series", series.len());
+    /// ```
+    pub fn show_series(
+        &self,
+        from: Option<&str>,
+        where_clause: Option<&str>,
+    ) -> impl Future<Item = Vec<SeriesKey>, Error = InfluxDbError> {
+        self.json_query(show_series_query(from, where_clause))
+            .and_then(|mut result| result.deserialize_next::<(String,)>())
+            .and_then(|keys| {
+                rows(keys)
+                    .into_iter()
+                    .map(|(key,)| parse_series_key(&key))
+                    .collect::<Result<Vec<SeriesKey>, InfluxDbError>>()
+            })
+    }
+
+    /// Lists the values of the tag `key` of the measurement `measurement` using `SHOW TAG VALUES`, optionally
+    /// restricted to the series matching the InfluxQL condition `where_clause`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use futures::Future;
+    /// use influxdb::client::InfluxDbClient;
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let locations = client
+    ///     .show_tag_values("weather", "location", Some("season = 'summer'"))
+    ///     .wait()
+    ///     .unwrap();
+    /// println!("locations: {:?}", locations);
+    /// ```
+    pub fn show_tag_values(
+        &self,
+        measurement: &str,
+        key: &str,
+        where_clause: Option<&str>,
+    ) -> impl Future<Item = Vec<String>, Error = InfluxDbError> {
+        self.tag_values(show_tag_values_query(Some(measurement), key, where_clause))
+            .map(|values| values.into_iter().map(|(_, value)| value).collect())
+    }
+
+    /// Lists the values of the tag `key` across all measurements of the database of the client using
+    /// `SHOW TAG VALUES`, as pairs of measurement and value. Like
+    /// [`show_tag_values`](crate::client::InfluxDbClient::show_tag_values), the series can be restricted by `where_clause`.
+    pub fn show_tag_values_by_measurement(
+        &self,
+        key: &str,
+        where_clause: Option<&str>,
+    ) -> impl Future<Item = Vec<(String, String)>, Error = InfluxDbError> {
+        self.tag_values(show_tag_values_query(None, key, where_clause))
+    }
+
+    fn tag_values(
+        &self,
+        read_query: InfluxDbReadQuery,
+    ) -> impl Future<Item = Vec<(String, String)>, Error = InfluxDbError> {
+        self.json_query(read_query)
+            .and_then(|mut result| result.deserialize_next::<(String, String)>())
+            .map(tag_values)
+    }
+
+    /// Returns the number of series in the database of the client using `SHOW SERIES CARDINALITY`.
+    ///
+    /// Unless `exact` is set, InfluxDB returns an estimation, which is considerably cheaper to compute.
+    pub fn show_series_cardinality(
+        &self,
+        exact: bool,
+    ) -> impl Future<Item = u64, Error = InfluxDbError> {
+        self.cardinality(cardinality_query("SERIES", exact))
+    }
+
+    /// Returns the number of measurements in the database of the client using `SHOW MEASUREMENT CARDINALITY`.
+    ///
+    /// Unless `exact` is set, InfluxDB returns an estimation, which is considerably cheaper to compute.
+    pub fn show_measurement_cardinality(
+        &self,
+        exact: bool,
+    ) -> impl Future<Item = u64, Error = InfluxDbError> {
+        self.cardinality(cardinality_query("MEASUREMENT", exact))
+    }
+
+    /// Counts the points of `measurement` using `SELECT COUNT(*)`, optionally restricted to the points matching the
+    /// InfluxQL condition `where_clause`.
+    ///
+    /// `COUNT(*)` counts every field on its own, as points do not need to have all fields, so the counts are returned
+    /// per field. Measurements without matching points return no counts.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use futures::Future;
+    /// use influxdb::client::InfluxDbClient;
+    ///
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let counts = client
+    ///     .count("weather", Some("time > now() - 1h"))
+    ///     .wait()
+    ///     .unwrap();
+    /// println!("{:?} temperatures", counts.get("temperature"));
+    /// ```
+    pub fn count(
+        &self,
+        measurement: &str,
+        where_clause: Option<&str>,
+    ) -> impl Future<Item = BTreeMap<String, u64>, Error = InfluxDbError> {
+        self.json_query(count_query(measurement, where_clause))
+            .and_then(|mut result| result.deserialize_next::<Vec<Value>>())
+            .and_then(field_counts)
+    }
+
+    fn cardinality(
+        &self,
+        read_query: InfluxDbReadQuery,
+    ) -> impl Future<Item = u64, Error = InfluxDbError> {
+        self.json_query(read_query)
+            .and_then(|mut result| result.deserialize_next::<(u64,)>())
+            .map(total_cardinality)
+    }
+}
+
+/// Builds a `SHOW ... CARDINALITY` statement for `SERIES` or `MEASUREMENT`
+fn cardinality_query(of: &str, exact: bool) -> InfluxDbReadQuery {
+    if exact {
+        InfluxDbReadQuery::new(format!("SHOW {} EXACT CARDINALITY", of))
+    } else {
+        InfluxDbReadQuery::new(format!("SHOW {} CARDINALITY", of))
+    }
+}
+
+/// Sums up a cardinality result. Exact series cardinalities are returned with one series per measurement.
+fn total_cardinality(result: InfluxDbReturn<(u64,)>) -> u64 {
+    rows(result).into_iter().map(|(count,)| count).sum()
+}
+
+/// Builds the `SELECT COUNT(*)` statement of [`count`](crate::client::InfluxDbClient::count)
+fn count_query(measurement: &str, where_clause: Option<&str>) -> InfluxDbReadQuery {
+    let mut query = format!("SELECT COUNT(*) FROM {}", quote_identifier(measurement));
+    if let Some(condition) = where_clause {
+        query.push_str(" WHERE ");
+        query.push_str(condition);
+    }
+    InfluxDbReadQuery::new(query)
+}
+
+/// Reads the counts of a `COUNT(*)` result, whose columns are named `count_<field>`, summing them up across series
+fn field_counts(
+    result: InfluxDbReturn<Vec<Value>>,
+) -> Result<BTreeMap<String, u64>, InfluxDbError> {
+    let mut counts = BTreeMap::new();
+    for series in result.series {
+        for row in series.values {
+            for (column, value) in series.columns.iter().zip(row) {
+                let field = match column.strip_prefix("count_") {
+                    Some(field) => field,
+                    None => continue,
+                };
+                let count = match value {
+                    Value::Null => 0,
+                    value => value
+                        .as_u64()
+                        .ok_or_else(|| InfluxDbError::DeserializationError {
+                            error: format!("invalid count {} of field \"{}\"", value, field),
+                        })?,
+                };
+                *counts.entry(field.to_string()).or_insert(0) += count;
+            }
+        }
+    }
+    Ok(counts)
+}
+
+/// Builds the `SHOW SERIES` statement of [`show_series`](crate::client::InfluxDbClient::show_series)
+fn show_series_query(from: Option<&str>, where_clause: Option<&str>) -> InfluxDbReadQuery {
+    let mut query = String::from("SHOW SERIES");
+    if let Some(measurement) = from {
+        query.push_str(" FROM ");
+        query.push_str(&quote_identifier(measurement));
+    }
+    if let Some(condition) = where_clause {
+        query.push_str(" WHERE ");
+        query.push_str(condition);
+    }
+    InfluxDbReadQuery::new(query)
+}
+
+/// Builds the `SHOW TAG VALUES` statement of [`show_tag_values`](crate::client::InfluxDbClient::show_tag_values)
+fn show_tag_values_query(
+    from: Option<&str>,
+    key: &str,
+    where_clause: Option<&str>,
+) -> InfluxDbReadQuery {
+    let mut query = String::from("SHOW TAG VALUES");
+    if let Some(measurement) = from {
+        query.push_str(" FROM ");
+        query.push_str(&quote_identifier(measurement));
+    }
+    query.push_str(" WITH KEY = ");
+    query.push_str(&quote_identifier(key));
+    if let Some(condition) = where_clause {
+        query.push_str(" WHERE ");
+        query.push_str(condition);
+    }
+    InfluxDbReadQuery::new(query)
+}
+
+/// Converts a `SHOW TAG VALUES` result, with one series of `(key, value)` rows per measurement, into pairs of
+/// measurement and value
+fn tag_values(result: InfluxDbReturn<(String, String)>) -> Vec<(String, String)> {
+    result
+        .series
+        .into_iter()
+        .flat_map(|series| {
+            let measurement = series.name;
+            series
+                .values
+                .into_iter()
+                .map(move |(_, value)| (measurement.clone(), value))
+        })
+        .collect()
+}
+
+/// Parses a series key like `weather,location=us\,midwest`, unescaping the line protocol escapes
+fn parse_series_key(key: &str) -> Result<SeriesKey, InfluxDbError> {
+    let invalid = || InfluxDbError::DeserializationError {
+        error: format!("invalid series key \"{}\"", key),
+    };
+
+    let mut parts = split_unescaped(key, ',').into_iter();
+    let measurement = parts.next().filter(|m| !m.is_empty()).ok_or_else(invalid)?;
+    let mut tags = BTreeMap::new();
+    for tag in parts {
+        match split_unescaped(&tag, '=').as_slice() {
+            [tag_key, tag_value] => {
+                tags.insert(unescape(tag_key), unescape(tag_value));
+            }
+            _ => return Err(invalid()),
+        }
+    }
+
+    Ok(SeriesKey {
+        measurement: unescape(&measurement),
+        tags,
+    })
+}
+
+/// Splits `input` at each `separator` which is not escaped by a backslash, keeping the escapes
+fn split_unescaped(input: &str, separator: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        let current = parts.last_mut().unwrap();
+        if c == '\\' {
+            current.push(c);
+            if let Some(escaped) = chars.next() {
+                current.push(escaped);
+            }
+        } else if c == separator {
+            parts.push(String::new());
+        } else {
+            current.push(c);
+        }
+    }
+    parts
+}
+
+/// Removes the backslashes escaping commas, spaces and equal signs
+fn unescape(input: &str) -> String {
+    let mut unescaped = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(',')) | ('\\', Some(' ')) | ('\\', Some('=')) => {}
+            _ => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+/// Discovers the schema, using `fetch` to run the `SHOW` queries
+fn schema<F, R>(fetch: F) -> impl Stream<Item = MeasurementSchema, Error = InfluxDbError>
+where
+    F: Fn(InfluxDbReadQuery) -> R + 'static,
+    R: Future<Item = DatabaseQueryResult, Error = InfluxDbError> + 'static,
+{
+    let fetch = Rc::new(fetch);
+    let fetch_keys = Rc::clone(&fetch);
+
+    fetch(InfluxDbReadQuery::new("SHOW MEASUREMENTS"))
+        .and_then(|mut result| result.deserialize_next::<(String,)>())
+        .map(|measurements| stream::iter_ok(rows(measurements)))
+        .flatten_stream()
+        .and_then(move |(name,)| {
+            let measurement = quote_identifier(&name);
+            let tag_keys = fetch_keys(InfluxDbReadQuery::new(format!(
+                "SHOW TAG KEYS FROM {}",
+                measurement
+            )))
+            .and_then(|mut result| result.deserialize_next::<(String,)>());
+            let field_keys = fetch_keys(InfluxDbReadQuery::new(format!(
+                "SHOW FIELD KEYS FROM {}",
+                measurement
+            )))
+            .and_then(|mut result| result.deserialize_next::<(String, String)>());
+
+            tag_keys
+                .join(field_keys)
+                .map(move |(tag_keys, field_keys)| MeasurementSchema {
+                    name,
+                    tag_keys: rows(tag_keys).into_iter().map(|(key,)| key).collect(),
+                    field_keys: rows(field_keys)
+                        .into_iter()
+                        .map(|(name, field_type)| FieldKey { name, field_type })
+                        .collect(),
+                })
+        })
+}
+
+/// Returns the rows of all series of a statement result
+fn rows<T>(result: InfluxDbReturn<T>) -> Vec<T> {
+    result
+        .series
+        .into_iter()
+        .flat_map(|series| series.values)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        cardinality_query, count_query, field_counts, parse_series_key, schema, show_series_query,
+        show_tag_values_query, tag_values, total_cardinality, FieldKey, MeasurementSchema,
+        SeriesKey,
+    };
+    use crate::integrations::serde_integration::DatabaseQueryResult;
+
+    use futures::{Future, Stream};
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_schema() {
+        let queries = Rc::new(RefCell::new(vec![]));
+        let recorded = Rc::clone(&queries);
+
+        let result = schema(move |read_query| {
+            let query = read_query.to_string();
+            let response = match query.as_str() {
+                "SHOW MEASUREMENTS" => {
+                    r#"{"results":[{"statement_id":0,"series":[{"name":"measurements","columns":["name"],"values":[["humidity"],["weather"]]}]}]}"#
+                }
+                "SHOW TAG KEYS FROM \"humidity\"" => r#"{"results":[{"statement_id":0}]}"#,
+                "SHOW FIELD KEYS FROM \"humidity\"" => {
+                    r#"{"results":[{"statement_id":0,"series":[{"name":"humidity","columns":["fieldKey","fieldType"],"values":[["humidity","integer"]]}]}]}"#
+                }
+                "SHOW TAG KEYS FROM \"weather\"" => {
+                    r#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["tagKey"],"values":[["location"],["season"]]}]}]}"#
+                }
+                "SHOW FIELD KEYS FROM \"weather\"" => {
+                    r#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["fieldKey","fieldType"],"values":[["temperature","float"],["wind_strength","float"]]}]}]}"#
+                }
+                _ => panic!("unexpected query {}", query),
+            };
+            recorded.borrow_mut().push(query);
+            futures::future::ok(serde_json::from_str::<DatabaseQueryResult>(response).unwrap())
+        });
+
+        let mut measurements = result.wait();
+        assert_eq!(
+            measurements.next().unwrap().unwrap(),
+            MeasurementSchema {
+                name: "humidity".to_string(),
+                tag_keys: vec![],
+                field_keys: vec![FieldKey {
+                    name: "humidity".to_string(),
+                    field_type: "integer".to_string(),
+                }],
+            }
+        );
+        // the keys of the next measurement are only queried once it is requested
+        assert_eq!(queries.borrow().len(), 3);
+
+        assert_eq!(
+            measurements.next().unwrap().unwrap(),
+            MeasurementSchema {
+                name: "weather".to_string(),
+                tag_keys: vec!["location".to_string(), "season".to_string()],
+                field_keys: vec![
+                    FieldKey {
+                        name: "temperature".to_string(),
+                        field_type: "float".to_string(),
+                    },
+                    FieldKey {
+                        name: "wind_strength".to_string(),
+                        field_type: "float".to_string(),
+                    },
+                ],
+            }
+        );
+        assert!(measurements.next().is_none());
+        assert_eq!(queries.borrow().len(), 5);
+    }
+
+    #[test]
+    fn test_show_series_query() {
+        assert_eq!(show_series_query(None, None).to_string(), "SHOW SERIES");
+        assert_eq!(
+            show_series_query(Some("weather"), Some("location = 'us-midwest'")).to_string(),
+            "SHOW SERIES FROM \"weather\" WHERE location = 'us-midwest'"
+        );
+    }
+
+    #[test]
+    fn test_show_tag_values_query() {
+        assert_eq!(
+            show_tag_values_query(Some("weather"), "location", None).to_string(),
+            "SHOW TAG VALUES FROM \"weather\" WITH KEY = \"location\""
+        );
+        assert_eq!(
+            show_tag_values_query(None, "host name", Some("season = 'summer'")).to_string(),
+            "SHOW TAG VALUES WITH KEY = \"host name\" WHERE season = 'summer'"
+        );
+    }
+
+    #[test]
+    fn test_tag_values() {
+        let mut result = serde_json::from_str::<DatabaseQueryResult>(
+            r#"{"results":[{"statement_id":0,"series":[
+                {"name":"humidity","columns":["key","value"],"values":[["location","us-east"]]},
+                {"name":"weather","columns":["key","value"],"values":[["location","us-east"],["location","us-midwest"]]}
+            ]}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            tag_values(result.deserialize_next().wait().unwrap()),
+            vec![
+                ("humidity".to_string(), "us-east".to_string()),
+                ("weather".to_string(), "us-east".to_string()),
+                ("weather".to_string(), "us-midwest".to_string()),
+            ]
+        );
+
+        let mut empty =
+            serde_json::from_str::<DatabaseQueryResult>(r#"{"results":[{"statement_id":0}]}"#)
+                .unwrap();
+        assert!(tag_values(empty.deserialize_next().wait().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_parse_series_key() {
+        assert_eq!(
+            parse_series_key("weather").unwrap(),
+            SeriesKey {
+                measurement: "weather".to_string(),
+                tags: BTreeMap::new(),
+            }
+        );
+
+        let series = parse_series_key("weather,location=us-midwest,season=summer").unwrap();
+        assert_eq!(series.measurement, "weather");
+        assert_eq!(series.tags["location"], "us-midwest");
+        assert_eq!(series.tags["season"], "summer");
+
+        let escaped =
+            parse_series_key(r"wind\ speed,location=us\,midwest,sensor\=id=a\ b").unwrap();
+        assert_eq!(escaped.measurement, "wind speed");
+        assert_eq!(escaped.tags["location"], "us,midwest");
+        assert_eq!(escaped.tags["sensor=id"], "a b");
+    }
+
+    #[test]
+    fn test_parse_series_key_invalid() {
+        assert!(parse_series_key("").is_err());
+        assert!(parse_series_key("weather,location").is_err());
+        assert!(parse_series_key(",location=us-midwest").is_err());
+    }
+
+    #[test]
+    fn test_count_query() {
+        assert_eq!(
+            count_query("weather", None).to_string(),
+            "SELECT COUNT(*) FROM \"weather\""
+        );
+        assert_eq!(
+            count_query("weather", Some("time > now() - 1h")).to_string(),
+            "SELECT COUNT(*) FROM \"weather\" WHERE time > now() - 1h"
+        );
+    }
+
+    #[test]
+    fn test_field_counts() {
+        let mut result = serde_json::from_str::<DatabaseQueryResult>(
+            r#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","count_humidity","count_temperature"],"values":[["1970-01-01T00:00:00Z",3,5]]}]}]}"#,
+        )
+        .unwrap();
+        let counts = field_counts(result.deserialize_next().wait().unwrap()).unwrap();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["humidity"], 3);
+        assert_eq!(counts["temperature"], 5);
+
+        let mut empty =
+            serde_json::from_str::<DatabaseQueryResult>(r#"{"results":[{"statement_id":0}]}"#)
+                .unwrap();
+        assert!(field_counts(empty.deserialize_next().wait().unwrap())
+            .unwrap()
+            .is_empty());
+
+        let mut invalid = serde_json::from_str::<DatabaseQueryResult>(
+            r#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","count_temperature"],"values":[["1970-01-01T00:00:00Z","many"]]}]}]}"#,
+        )
+        .unwrap();
+        assert!(field_counts(invalid.deserialize_next().wait().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_cardinality_query() {
+        assert_eq!(
+            cardinality_query("SERIES", false).to_string(),
+            "SHOW SERIES CARDINALITY"
+        );
+        assert_eq!(
+            cardinality_query("SERIES", true).to_string(),
+            "SHOW SERIES EXACT CARDINALITY"
+        );
+        assert_eq!(
+            cardinality_query("MEASUREMENT", false).to_string(),
+            "SHOW MEASUREMENT CARDINALITY"
+        );
+        assert_eq!(
+            cardinality_query("MEASUREMENT", true).to_string(),
+            "SHOW MEASUREMENT EXACT CARDINALITY"
+        );
+    }
+
+    #[test]
+    fn test_total_cardinality() {
+        let mut estimation = serde_json::from_str::<DatabaseQueryResult>(
+            r#"{"results":[{"statement_id":0,"series":[{"columns":["cardinality estimation"],"values":[[42]]}]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            total_cardinality(estimation.deserialize_next::<(u64,)>().wait().unwrap()),
+            42
+        );
+
+        let mut exact = serde_json::from_str::<DatabaseQueryResult>(
+            r#"{"results":[{"statement_id":0,"series":[{"name":"humidity","columns":["count"],"values":[[3]]},{"name":"weather","columns":["count"],"values":[[7]]}]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            total_cardinality(exact.deserialize_next::<(u64,)>().wait().unwrap()),
+            10
+        );
+
+        let mut empty =
+            serde_json::from_str::<DatabaseQueryResult>(r#"{"results":[{"statement_id":0}]}"#)
+                .unwrap();
+        assert_eq!(
+            total_cardinality(empty.deserialize_next::<(u64,)>().wait().unwrap()),
+            0
+        );
+    }
+}
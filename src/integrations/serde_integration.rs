@@ -48,65 +48,1053 @@
 //!         })
 //!     });
 //! ```
+//!
+//! When a query requests epoch timestamps using [`with_epoch`](crate::query::read_query::InfluxDbReadQuery::with_epoch),
+//! the integer `time` column can be deserialized into an `i64`, a `chrono::DateTime<Utc>` or a `std::time::Duration`
+//! since the Unix epoch. The precision of the query is used to convert the integer automatically.
+//!
+//! To not commit to either representation, the `time` column can be deserialized into a [`Time`], which accepts
+//! both RFC3339 strings and, if the query requested them, epoch integers.
+//!
+//! Columns may contain `null`, e.g. for the empty intervals of a `GROUP BY time(..) fill(null)` query.
+//! Such columns need to be deserialized into an `Option`, which is `None` for `null` values.
 
-use crate::client::InfluxDbClient;
+use crate::client::{
+    check_rate_limit, fetch_response, unexpected_response, unfollowed_redirect, InfluxDbClient,
+    ResultFormat,
+};
+use crate::integrations::csv::csv_results;
 
-use serde::de::DeserializeOwned;
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use serde::de::{self, DeserializeOwned, Deserializer, Visitor};
 
-use futures::{Future, Stream};
-use reqwest::r#async::{Client, Decoder};
-use reqwest::{StatusCode, Url};
-use std::mem;
+use futures::{stream, Future, Stream};
+use reqwest::header::ACCEPT;
+use reqwest::StatusCode;
 
 use serde::Deserialize;
 use serde_json;
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::fmt;
 
 use crate::error::InfluxDbError;
 
 use crate::query::read_query::InfluxDbReadQuery;
-use crate::query::InfluxDbQuery;
+use crate::query::select_query::InfluxDbSelectQuery;
+use crate::query::{requires_post, InfluxDbQuery, Method, Precision};
 
 use futures::future::Either;
 
 #[derive(Deserialize)]
 #[doc(hidden)]
 struct _DatabaseError {
-    error: String,
+    error: Option<String>,
+    message: Option<String>,
+    code: Option<String>,
+}
+
+/// Parses InfluxDBs `{ "error": "error message here" }`, or `{ "code": "not found", "message": "error message here" }`
+/// into a [`DatabaseError`](crate::error::InfluxDbError::DatabaseError), or a more specific error for missing databases and retention policies
+pub(crate) fn parse_database_error(body: &[u8]) -> Option<InfluxDbError> {
+    let database_error = serde_json::from_slice::<_DatabaseError>(body).ok()?;
+    let error = database_error.error.or(database_error.message)?;
+    Some(InfluxDbError::database_error(error, database_error.code))
+}
+
+#[derive(Deserialize)]
+#[doc(hidden)]
+struct _StatementErrors {
+    results: Vec<_StatementError>,
+}
+
+#[derive(Deserialize)]
+#[doc(hidden)]
+struct _StatementError {
+    error: Option<String>,
+}
+
+/// Parses the `results` of a query response, returning the error of the first failed statement.
+///
+/// InfluxDB responds with status 200 if statements fail, only reporting their errors in the body, e.g.
+/// `{"results":[{"statement_id":0,"error":"database not found: test"}]}`. `None` if the body is no query response.
+pub(crate) fn parse_statement_error(body: &[u8]) -> Option<Result<(), InfluxDbError>> {
+    let statements = serde_json::from_slice::<_StatementErrors>(body).ok()?;
+    Some(
+        match statements
+            .results
+            .into_iter()
+            .find_map(|statement| statement.error)
+        {
+            Some(error) => Err(InfluxDbError::database_error(error, None)),
+            None => Ok(()),
+        },
+    )
 }
 
 #[derive(Deserialize, Debug)]
 #[doc(hidden)]
 pub struct DatabaseQueryResult {
     pub results: Vec<serde_json::Value>,
+    /// Epoch precision of the `time` column, if the query requested one
+    #[serde(skip)]
+    pub precision: Option<Precision>,
+    /// Name of the column holding the timestamps, `time` if `None`,
+    /// see [`with_time_column`](crate::query::read_query::InfluxDbReadQuery::with_time_column)
+    #[serde(skip)]
+    pub time_column: Option<String>,
+    /// Database the query ran against, `None` if it was sent without one
+    #[serde(skip)]
+    pub database: Option<String>,
 }
 
 impl DatabaseQueryResult {
+    /// Deserializes the result of the statement with the lowest `statement_id` which has not been deserialized yet
     pub fn deserialize_next<T: 'static>(
         &mut self,
     ) -> impl Future<Item = InfluxDbReturn<T>, Error = InfluxDbError>
     where
         T: DeserializeOwned,
     {
-        match serde_json::from_value::<InfluxDbReturn<T>>(self.results.remove(0)) {
-            Ok(item) => futures::future::result(Ok(item)),
-            Err(err) => futures::future::err(InfluxDbError::DeserializationError {
-                error: format!("could not deserialize: {}", err),
+        futures::future::result(
+            self.next_result()
+                .and_then(|result| self.deserialize_result(result)),
+        )
+    }
+
+    /// Deserializes the rows of all series of the next statement result into a flat `Vec`.
+    ///
+    /// This allows reading rows into tuples instead of named structs, as the values of a row are mapped
+    /// positionally. If `T` is a tuple, it needs to have as many elements as the result has columns.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use futures::prelude::*;
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// let mut rt = tokio::runtime::current_thread::Runtime::new().unwrap();
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let query = InfluxDbQuery::raw_read_query("SELECT temperature, location FROM weather");
+    /// let _rows = rt.block_on(
+    ///     client
+    ///         .json_query(query)
+    ///         .and_then(|mut db_result| db_result.deserialize_next_rows::<(String, f64, String)>()),
+    /// );
+    /// ```
+    pub fn deserialize_next_rows<T>(&mut self) -> impl Future<Item = Vec<T>, Error = InfluxDbError>
+    where
+        T: DeserializeOwned + 'static,
+    {
+        let rows = self
+            .next_result()
+            .and_then(|result| self.deserialize_rows(result));
+        futures::future::result(rows)
+    }
+
+    /// Deserializes the rows of the next statement result like
+    /// [`deserialize_next_rows`](crate::integrations::serde_integration::DatabaseQueryResult::deserialize_next_rows),
+    /// returning the rows together with the JSON of the result they were deserialized from.
+    ///
+    /// This helps debugging results which do not deserialize into `T`: the JSON is returned even if the rows
+    /// can not be deserialized, so the shape of the result can be inspected without sending the query again.
+    /// Only a missing statement result fails the returned future.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use futures::prelude::*;
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// let mut rt = tokio::runtime::current_thread::Runtime::new().unwrap();
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let query = InfluxDbQuery::raw_read_query("SELECT temperature FROM weather");
+    /// let (rows, raw) = rt
+    ///     .block_on(client.json_query(query).and_then(|mut db_result| {
+    ///         db_result.deserialize_next_rows_with_raw::<(String, f64)>()
+    ///     }))
+    ///     .unwrap();
+    /// if let Err(error) = rows {
+    ///     println!("{} in {}", error, raw);
+    /// }
+    /// ```
+    pub fn deserialize_next_rows_with_raw<T>(
+        &mut self,
+    ) -> impl Future<Item = (Result<Vec<T>, InfluxDbError>, serde_json::Value), Error = InfluxDbError>
+    where
+        T: DeserializeOwned + 'static,
+    {
+        let rows = self.next_result().map(|result| {
+            let raw = result.clone();
+            (self.deserialize_rows(result), raw)
+        });
+        futures::future::result(rows)
+    }
+
+    /// Deserializes the rows of all series of a statement result into a flat `Vec`
+    fn deserialize_rows<T>(&self, result: serde_json::Value) -> Result<Vec<T>, InfluxDbError>
+    where
+        T: DeserializeOwned + 'static,
+    {
+        if let Some(arity) = tuple_arity::<T>() {
+            check_column_count(&result, arity)?;
+        }
+        self.deserialize_result::<T>(result).map(|statement| {
+            statement
+                .series
+                .into_iter()
+                .flat_map(|series| series.values)
+                .collect()
+        })
+    }
+
+    /// Deserializes the next statement result like [`deserialize_next`](crate::integrations::serde_integration::DatabaseQueryResult::deserialize_next),
+    /// but passes every value through `map_value` first.
+    ///
+    /// `map_value` is called with the name of the column and the value returned by InfluxDB, and returns the value to deserialize
+    /// instead. This allows handling non-standard time formats or coercing numbers, which were written as strings.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use futures::prelude::*;
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::InfluxDbQuery;
+    /// use serde::Deserialize;
+    /// use serde_json::Value;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Weather {
+    ///     time: String,
+    ///     temperature: f64,
+    /// }
+    ///
+    /// let mut rt = tokio::runtime::current_thread::Runtime::new().unwrap();
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let query = InfluxDbQuery::raw_read_query("SELECT temperature FROM weather");
+    /// let _result = rt.block_on(client.json_query(query).and_then(|mut db_result| {
+    ///     db_result.deserialize_next_with::<Weather, _>(|column, value| match (column, value) {
+    ///         ("temperature", Value::String(temperature)) => temperature.parse::<f64>().map_or(Value::Null, Value::from),
+    ///         (_, value) => value,
+    ///     })
+    /// }));
+    /// ```
+    pub fn deserialize_next_with<T, F>(
+        &mut self,
+        map_value: F,
+    ) -> impl Future<Item = InfluxDbReturn<T>, Error = InfluxDbError>
+    where
+        T: DeserializeOwned + 'static,
+        F: FnMut(&str, serde_json::Value) -> serde_json::Value,
+    {
+        futures::future::result(self.next_result().and_then(|mut result| {
+            map_columns(&mut result, map_value);
+            self.deserialize_result(result)
+        }))
+    }
+
+    /// Deserializes the results of all remaining statements, ordered by their `statement_id`.
+    ///
+    /// Statements of a multi-statement query fail independently, so the outcome of every statement is returned
+    /// instead of failing on the first error. Statements InfluxDB reported an error for yield a
+    /// [`DatabaseError`](crate::error::InfluxDbError::DatabaseError), or a more specific error like
+    /// [`DatabaseNotFound`](crate::error::InfluxDbError::DatabaseNotFound).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use futures::prelude::*;
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// let mut rt = tokio::runtime::current_thread::Runtime::new().unwrap();
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let query = InfluxDbQuery::raw_read_query("SELECT * FROM weather; SELECT * FROM humidity");
+    /// let statements = rt
+    ///     .block_on(client.json_query(query).and_then(|mut db_result| {
+    ///         db_result.deserialize_statements::<serde_json::Value>()
+    ///     }))
+    ///     .unwrap();
+    /// for statement in statements {
+    ///     match statement {
+    ///         Ok(result) => println!("{} series", result.series.len()),
+    ///         Err(error) => println!("statement failed: {}", error),
+    ///     }
+    /// }
+    /// ```
+    pub fn deserialize_statements<T>(
+        &mut self,
+    ) -> impl Future<Item = Vec<Result<InfluxDbReturn<T>, InfluxDbError>>, Error = InfluxDbError>
+    where
+        T: DeserializeOwned + 'static,
+    {
+        let mut statements = Vec::with_capacity(self.results.len());
+        while !self.results.is_empty() {
+            statements.push(
+                self.next_result()
+                    .and_then(|result| self.deserialize_result(result)),
+            );
+        }
+        futures::future::ok(statements)
+    }
+
+    /// Removes the result with the lowest `statement_id` from the results
+    fn next_result(&mut self) -> Result<serde_json::Value, InfluxDbError> {
+        let next = self
+            .results
+            .iter()
+            .enumerate()
+            .min_by_key(|(position, result)| statement_id(result).unwrap_or(*position as u64))
+            .map(|(position, _)| position);
+
+        match next {
+            Some(position) => Ok(self.results.remove(position)),
+            None => Err(InfluxDbError::DeserializationError {
+                error: "no statement results left to deserialize".to_string(),
+            }),
+        }
+    }
+
+    /// Deserializes the result of the statement with the given `statement_id`, regardless of the order
+    /// InfluxDB returned the results in
+    pub fn deserialize_statement<T>(
+        &mut self,
+        statement_id: u64,
+    ) -> impl Future<Item = InfluxDbReturn<T>, Error = InfluxDbError>
+    where
+        T: DeserializeOwned + 'static,
+    {
+        let position = self
+            .results
+            .iter()
+            .position(|result| self::statement_id(result) == Some(statement_id));
+
+        match position {
+            Some(position) => {
+                let result = self.results.remove(position);
+                futures::future::result(self.deserialize_result(result))
+            }
+            None => futures::future::err(InfluxDbError::DeserializationError {
+                error: format!("no result for statement_id {}", statement_id),
             }),
         }
     }
+
+    fn deserialize_result<T>(
+        &self,
+        result: serde_json::Value,
+    ) -> Result<InfluxDbReturn<T>, InfluxDbError>
+    where
+        T: DeserializeOwned,
+    {
+        // a single statement of a multi-statement query failed
+        if let Some(error) = result.get("error").and_then(serde_json::Value::as_str) {
+            return Err(InfluxDbError::database_error(error.to_string(), None));
+        }
+
+        serde_json::from_value::<InfluxDbReturn<T>>(result.clone())
+            .or_else(|err| match self.precision {
+                // The integer `time` column did not fit the target type,
+                // retry as a `DateTime` and as a `Duration` since the epoch
+                Some(precision) => [epoch_to_rfc3339, epoch_to_duration]
+                    .iter()
+                    .filter_map(|convert| {
+                        let converted =
+                            convert_epoch_time(&result, self.time_column(), precision, *convert);
+                        serde_json::from_value::<InfluxDbReturn<T>>(converted).ok()
+                    })
+                    .next()
+                    .ok_or(err),
+                None => Err(err),
+            })
+            .map(|mut deserialized| {
+                deserialized.database = self.database.clone();
+                deserialized
+            })
+            .map_err(|err| {
+                let mut error = match self.locate_row_error::<T>(&result) {
+                    Some(location) => format!("could not deserialize {}", location),
+                    None => format!("could not deserialize: {}", err),
+                };
+                if error.contains("invalid type: null") {
+                    // e.g. empty intervals of a `GROUP BY time(..) fill(null)` query
+                    error.push_str(", use an `Option` for columns which may contain null");
+                }
+                InfluxDbError::DeserializationError { error }
+            })
+    }
+}
+
+impl DatabaseQueryResult {
+    /// Returns the name of the column holding the timestamps
+    fn time_column(&self) -> &str {
+        self.time_column.as_deref().unwrap_or("time")
+    }
+
+    /// Describes the first row of a statement result which can not be deserialized into `T`, including the
+    /// column and raw value causing the failure.
+    ///
+    /// With an epoch precision, the row is located in every representation of the `time` column, and the
+    /// representation which got furthest is reported, as it is most likely the one `T` expects.
+    fn locate_row_error<T: DeserializeOwned>(&self, result: &serde_json::Value) -> Option<String> {
+        let mut candidates = vec![result.clone()];
+        if let Some(precision) = self.precision {
+            let time_column = self.time_column();
+            candidates.push(convert_epoch_time(
+                result,
+                time_column,
+                precision,
+                epoch_to_rfc3339,
+            ));
+            candidates.push(convert_epoch_time(
+                result,
+                time_column,
+                precision,
+                epoch_to_duration,
+            ));
+        }
+        candidates
+            .iter()
+            .filter_map(row_error::<T>)
+            .max_by_key(|row_error| row_error.position)
+            .map(|row_error| row_error.message)
+    }
+}
+
+/// A row which could not be deserialized, see [`row_error`]
+struct RowError {
+    /// Index of the series, row and column, used to find the error which got furthest
+    position: (usize, usize, Option<usize>),
+    message: String,
+}
+
+/// Finds the first row of a statement result which can not be deserialized into `T`
+fn row_error<T: DeserializeOwned>(result: &serde_json::Value) -> Option<RowError> {
+    let series = result.get("series")?.as_array()?;
+    for (series_index, single_series) in series.iter().enumerate() {
+        let name = single_series
+            .get("name")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or_default();
+        let columns = single_series
+            .get("columns")
+            .and_then(serde_json::Value::as_array);
+        let rows = match single_series
+            .get("values")
+            .and_then(serde_json::Value::as_array)
+        {
+            Some(rows) => rows,
+            None => continue,
+        };
+        for (row_index, row) in rows.iter().enumerate() {
+            let row = match row.as_array() {
+                Some(row) => row,
+                None => continue,
+            };
+            let column = Cell::new(None);
+            let error = match T::deserialize(RowDeserializer {
+                row,
+                column: &column,
+            }) {
+                Ok(_) => continue,
+                Err(error) => error,
+            };
+
+            let message = match column.get() {
+                Some(index) => format!(
+                    "row {} of series \"{}\", column {} (value {}): {}",
+                    row_index,
+                    name,
+                    columns
+                        .and_then(|columns| columns.get(index))
+                        .map_or_else(|| index.to_string(), ToString::to_string),
+                    row[index],
+                    error
+                ),
+                None => format!("row {} of series \"{}\": {}", row_index, name, error),
+            };
+            return Some(RowError {
+                position: (series_index, row_index, column.get()),
+                message,
+            });
+        }
+    }
+    None
+}
+
+/// Deserializer of a single row, tracking the index of the column being deserialized
+struct RowDeserializer<'a> {
+    row: &'a [serde_json::Value],
+    column: &'a Cell<Option<usize>>,
+}
+
+impl<'de, 'a> serde::Deserializer<'de> for RowDeserializer<'a> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(RowAccess {
+            values: self.row.iter().enumerate(),
+            len: self.row.len(),
+            column: self.column,
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct
+        map struct enum identifier ignored_any
+    }
+}
+
+struct RowAccess<'a, I> {
+    values: I,
+    len: usize,
+    column: &'a Cell<Option<usize>>,
+}
+
+impl<'de, 'a, I> de::SeqAccess<'de> for RowAccess<'a, I>
+where
+    I: Iterator<Item = (usize, &'a serde_json::Value)>,
+{
+    type Error = serde_json::Error;
+
+    fn next_element_seed<S: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, Self::Error> {
+        let (index, value) = match self.values.next() {
+            Some(next) => next,
+            None => return Ok(None),
+        };
+        self.len -= 1;
+        // left set if deserializing the value fails
+        self.column.set(Some(index));
+        let value = seed.deserialize(value.clone())?;
+        self.column.set(None);
+        Ok(Some(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+/// Parses the body of a query response returned in `format`
+fn parse_results(body: &[u8], format: ResultFormat) -> Result<DatabaseQueryResult, InfluxDbError> {
+    match format {
+        // Json has another structure, let's try actually parsing it to the type we're deserializing
+        ResultFormat::Json => {
+            json_results(body).map_err(|err| InfluxDbError::DeserializationError {
+                error: format!("serde error: {}", err),
+            })
+        }
+        ResultFormat::Csv => {
+            let body =
+                std::str::from_utf8(body).map_err(|_| InfluxDbError::DeserializationError {
+                    error: "response could not be converted to UTF-8".to_string(),
+                })?;
+            Ok(DatabaseQueryResult {
+                results: csv_results(body)?,
+                precision: None,
+                time_column: None,
+                database: None,
+            })
+        }
+    }
+}
+
+/// Parses a JSON query response, which consists of one JSON document per chunk if the query requested
+/// [chunking](crate::query::read_query::InfluxDbReadQuery::with_chunk_size). The results of all chunks are merged.
+fn json_results(body: &[u8]) -> Result<DatabaseQueryResult, serde_json::Error> {
+    let mut chunks = serde_json::Deserializer::from_slice(body).into_iter::<DatabaseQueryResult>();
+    // an empty body fails like any other invalid body
+    let mut merged = chunks
+        .next()
+        .unwrap_or_else(|| serde_json::from_slice(body))?;
+    for chunk in chunks {
+        for result in chunk?.results {
+            match merged.results.last_mut() {
+                Some(last)
+                    if statement_id(last).is_some()
+                        && statement_id(last) == statement_id(&result) =>
+                {
+                    merge_result(last, result)
+                }
+                _ => merged.results.push(result),
+            }
+        }
+    }
+    Ok(merged)
+}
+
+/// Parses the chunks of a [chunked](crate::query::read_query::InfluxDbReadQuery::with_chunk_size) JSON query
+/// response one by one, without merging them.
+///
+/// The results and series of a chunk are marked as [`partial`](InfluxDbReturn::partial) if more rows of the
+/// same statement or series follow in the next chunk, so they need to be combined with it rather than treated
+/// as separate results. A response which was not chunked consists of a single chunk.
+///
+/// # Examples
+///
+/// ```rust
+/// use futures::Future;
+/// use influxdb::integrations::serde_integration::json_chunks;
+///
+/// let body = br#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["temperature"],"values":[[82]],"partial":true}],"partial":true}]}
+/// {"results":[{"statement_id":0,"series":[{"name":"weather","columns":["temperature"],"values":[[84]]}]}]}"#;
+///
+/// let partial: Vec<bool> = json_chunks(body)
+///     .map(|chunk| chunk.unwrap().deserialize_next::<(i32,)>().wait().unwrap().partial)
+///     .collect();
+/// assert_eq!(partial, vec![true, false]);
+/// ```
+pub fn json_chunks(
+    body: &[u8],
+) -> impl Iterator<Item = Result<DatabaseQueryResult, InfluxDbError>> + '_ {
+    serde_json::Deserializer::from_slice(body)
+        .into_iter::<DatabaseQueryResult>()
+        .map(|chunk| {
+            chunk.map_err(|err| InfluxDbError::DeserializationError {
+                error: format!("serde error: {}", err),
+            })
+        })
+}
+
+/// Merges the result of a statement continued in a later chunk into the result of the earlier chunks.
+///
+/// InfluxDB marks series which are continued in the next chunk as `partial`, so their values are appended
+/// to the series instead of starting a new one.
+fn merge_result(merged: &mut serde_json::Value, result: serde_json::Value) {
+    use serde_json::Value;
+
+    let (merged, result) = match (merged.as_object_mut(), result) {
+        (Some(merged), Value::Object(result)) => (merged, result),
+        _ => return,
+    };
+    // the statement is no longer partial once a chunk without the flag continued it
+    if !result.contains_key("partial") {
+        merged.remove("partial");
+    }
+    for (key, value) in result {
+        let series = match (key.as_str(), value) {
+            ("series", Value::Array(series)) => series,
+            ("messages", Value::Array(mut messages)) => {
+                match merged.get_mut("messages") {
+                    Some(Value::Array(merged_messages)) => merged_messages.append(&mut messages),
+                    _ => {
+                        merged.insert(key, Value::Array(messages));
+                    }
+                }
+                continue;
+            }
+            (_, value) => {
+                merged.insert(key, value);
+                continue;
+            }
+        };
+        let merged_series = match merged
+            .entry("series")
+            .or_insert_with(|| Value::Array(vec![]))
+        {
+            Value::Array(merged_series) => merged_series,
+            _ => continue,
+        };
+        for mut next in series {
+            match merged_series.last_mut() {
+                Some(last)
+                    if last.get("partial") == Some(&Value::Bool(true))
+                        && last.get("name") == next.get("name")
+                        && last.get("tags") == next.get("tags") =>
+                {
+                    if let (Some(Value::Array(values)), Some(Value::Array(next_values))) =
+                        (last.get_mut("values"), next.get_mut("values"))
+                    {
+                        values.append(next_values);
+                    }
+                    match next.get("partial") {
+                        Some(partial) => last["partial"] = partial.clone(),
+                        None => {
+                            if let Some(last) = last.as_object_mut() {
+                                last.remove("partial");
+                            }
+                        }
+                    }
+                }
+                _ => merged_series.push(next),
+            }
+        }
+    }
+}
+
+/// Returns the `db` parameter of the URL of a query
+fn query_database(url: &reqwest::Url) -> Option<String> {
+    url.query_pairs()
+        .find(|(key, _)| key == "db")
+        .map(|(_, database)| database.into_owned())
+}
+
+fn statement_id(result: &serde_json::Value) -> Option<u64> {
+    result
+        .get("statement_id")
+        .and_then(serde_json::Value::as_u64)
+}
+
+/// Replaces every value of every series in a statement result with the return value of `map_value`
+fn map_columns<F>(result: &mut serde_json::Value, mut map_value: F)
+where
+    F: FnMut(&str, serde_json::Value) -> serde_json::Value,
+{
+    let series = match result
+        .get_mut("series")
+        .and_then(serde_json::Value::as_array_mut)
+    {
+        Some(series) => series,
+        None => return,
+    };
+    for single_series in series {
+        let columns: Vec<String> = single_series
+            .get("columns")
+            .and_then(serde_json::Value::as_array)
+            .map(|columns| {
+                columns
+                    .iter()
+                    .map(|column| column.as_str().unwrap_or_default().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let values = match single_series
+            .get_mut("values")
+            .and_then(serde_json::Value::as_array_mut)
+        {
+            Some(values) => values,
+            None => continue,
+        };
+        for row in values
+            .iter_mut()
+            .filter_map(serde_json::Value::as_array_mut)
+        {
+            for (column, value) in columns.iter().zip(row.iter_mut()) {
+                *value = map_value(column, value.take());
+            }
+        }
+    }
+}
+
+/// Checks that every series of a statement result has `expected` columns
+fn check_column_count(result: &serde_json::Value, expected: usize) -> Result<(), InfluxDbError> {
+    let series = result
+        .get("series")
+        .and_then(serde_json::Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for single_series in series {
+        let columns = single_series
+            .get("columns")
+            .and_then(serde_json::Value::as_array)
+            .map_or(0, Vec::len);
+        if columns != expected {
+            return Err(InfluxDbError::DeserializationError {
+                error: format!(
+                    "result has {} columns, but the tuple has {} elements",
+                    columns, expected
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the number of elements of `T`, if `T` deserializes from a tuple
+fn tuple_arity<T: DeserializeOwned>() -> Option<usize> {
+    match T::deserialize(ArityProbe) {
+        Err(ArityProbeError(arity)) => arity,
+        Ok(_) => None,
+    }
+}
+
+/// Deserializer which fails on the first request, reporting the length if a tuple was requested
+struct ArityProbe;
+
+#[derive(Debug)]
+struct ArityProbeError(Option<usize>);
+
+impl std::fmt::Display for ArityProbeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "probing tuple arity")
+    }
+}
+
+impl std::error::Error for ArityProbeError {}
+
+impl serde::de::Error for ArityProbeError {
+    fn custom<M: std::fmt::Display>(_msg: M) -> Self {
+        ArityProbeError(None)
+    }
+}
+
+impl<'de> serde::Deserializer<'de> for ArityProbe {
+    type Error = ArityProbeError;
+
+    fn deserialize_any<V: serde::de::Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(ArityProbeError(None))
+    }
+
+    fn deserialize_tuple<V: serde::de::Visitor<'de>>(
+        self,
+        len: usize,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(ArityProbeError(Some(len)))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple_struct
+        map struct enum identifier ignored_any
+    }
+}
+
+/// Splits an epoch timestamp of the given precision into seconds and subsecond nanoseconds
+fn split_epoch(timestamp: i64, precision: Precision) -> Option<(i64, u32)> {
+    let nanos = timestamp.checked_mul(precision.nanos_per_unit())?;
+    Some((
+        nanos.div_euclid(1_000_000_000),
+        nanos.rem_euclid(1_000_000_000) as u32,
+    ))
+}
+
+fn epoch_to_rfc3339(timestamp: i64, precision: Precision) -> Option<serde_json::Value> {
+    let (secs, nanos) = split_epoch(timestamp, precision)?;
+    let time = Utc.timestamp_opt(secs, nanos).single()?;
+    Some(serde_json::Value::String(
+        time.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+    ))
+}
+
+fn epoch_to_duration(timestamp: i64, precision: Precision) -> Option<serde_json::Value> {
+    let (secs, nanos) = split_epoch(timestamp, precision)?;
+    if secs < 0 {
+        return None;
+    }
+    Some(serde_json::json!({ "secs": secs, "nanos": nanos }))
+}
+
+/// Rewrites the integer time column `time_column` of every series in a statement result using `convert`
+fn convert_epoch_time(
+    result: &serde_json::Value,
+    time_column: &str,
+    precision: Precision,
+    convert: fn(i64, Precision) -> Option<serde_json::Value>,
+) -> serde_json::Value {
+    let mut result = result.clone();
+    if let Some(series) = result
+        .get_mut("series")
+        .and_then(serde_json::Value::as_array_mut)
+    {
+        for single_series in series {
+            let time_index = single_series
+                .get("columns")
+                .and_then(serde_json::Value::as_array)
+                .and_then(|columns| columns.iter().position(|column| column == time_column));
+            let time_index = match time_index {
+                Some(index) => index,
+                None => continue,
+            };
+            if let Some(values) = single_series
+                .get_mut("values")
+                .and_then(serde_json::Value::as_array_mut)
+            {
+                for row in values {
+                    if let Some(time) = row.get_mut(time_index) {
+                        if let Some(converted) = time.as_i64().and_then(|ts| convert(ts, precision))
+                        {
+                            *time = converted;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    result
 }
 
 #[derive(Deserialize, Debug)]
 #[doc(hidden)]
 pub struct InfluxDbReturn<T> {
+    pub statement_id: Option<u64>,
+    /// InfluxDB omits the series of statements which did not return any data
+    #[serde(default = "Vec::new")]
     pub series: Vec<InfluxDbSeries<T>>,
+    /// Messages InfluxDB returned along with the result, e.g. deprecation warnings. These do not fail the statement.
+    #[serde(default)]
+    pub messages: Vec<InfluxDbMessage>,
+    /// Whether more rows of the statement follow in the next chunk of a [chunked](crate::query::read_query::InfluxDbReadQuery::with_chunk_size)
+    /// response. Results of merged chunks are only partial if the response ended before the last chunk of the statement.
+    #[serde(default)]
+    pub partial: bool,
+    /// Database the query ran against, which may differ from the database of the client
+    /// if it was overridden with [`with_database`](crate::query::read_query::InfluxDbReadQuery::with_database)
+    #[serde(skip)]
+    pub database: Option<String>,
+}
+
+impl<T> InfluxDbReturn<T> {
+    /// Iterates over the rows of all series, in the order of the series, without their nesting into series.
+    ///
+    /// Use [`rows_with_series`](crate::integrations::serde_integration::InfluxDbReturn::rows_with_series)
+    /// to tell apart the rows of different series, e.g. of a `GROUP BY` query.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use futures::prelude::*;
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::InfluxDbQuery;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Weather {
+    ///     time: String,
+    ///     temperature: i32,
+    /// }
+    ///
+    /// let mut rt = tokio::runtime::current_thread::Runtime::new().unwrap();
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let query = InfluxDbQuery::raw_read_query("SELECT temperature FROM weather GROUP BY location");
+    /// let result = rt
+    ///     .block_on(client.json_query(query).and_then(|mut db_result| db_result.deserialize_next::<Weather>()))
+    ///     .unwrap();
+    /// for weather in result.rows() {
+    ///     println!("{}: {}", weather.time, weather.temperature);
+    /// }
+    /// ```
+    pub fn rows(&self) -> impl Iterator<Item = &T> {
+        self.series.iter().flat_map(|series| series.values.iter())
+    }
+
+    /// Iterates over the rows of all series like [`rows`](crate::integrations::serde_integration::InfluxDbReturn::rows),
+    /// along with the series each row belongs to, i.e. its name and tags
+    pub fn rows_with_series(&self) -> impl Iterator<Item = (&InfluxDbSeries<T>, &T)> {
+        self.series
+            .iter()
+            .flat_map(|series| series.values.iter().map(move |row| (series, row)))
+    }
+
+    /// Consumes the result, iterating over the owned rows of all series like
+    /// [`rows`](crate::integrations::serde_integration::InfluxDbReturn::rows)
+    pub fn into_rows(self) -> impl Iterator<Item = T> {
+        self.series.into_iter().flat_map(|series| series.values)
+    }
+}
+
+/// A message InfluxDB returned along with the result of a statement
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct InfluxDbMessage {
+    /// Severity of the message, e.g. `warning`
+    pub level: String,
+    pub text: String,
 }
 
 #[derive(Deserialize, Debug)]
 /// Represents a returned series from InfluxDB
 pub struct InfluxDbSeries<T> {
+    /// Empty for results which are not bound to a measurement, e.g. of `SHOW SERIES CARDINALITY`
+    #[serde(default)]
     pub name: String,
+    /// Tags of the series, e.g. of a `GROUP BY` clause or the statistics of `SHOW STATS`
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+    /// Names of the columns, in the order of the values of each row
+    #[serde(default)]
+    pub columns: Vec<String>,
+    /// Omitted by InfluxDB for series without any rows, e.g. databases without shards in `SHOW SHARDS`
+    #[serde(default = "Vec::new")]
     pub values: Vec<T>,
+    /// Whether more rows of the series follow in the next chunk of a chunked response
+    #[serde(default)]
+    pub partial: bool,
+}
+
+/// Timestamp of the `time` column, regardless of whether InfluxDB returned it as RFC3339 string or
+/// as epoch integer of a query using [`with_epoch`](crate::query::read_query::InfluxDbReadQuery::with_epoch).
+///
+/// Epoch integers are converted using the precision of the query, so they can only be deserialized
+/// as part of a [`DatabaseQueryResult`].
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::integrations::serde_integration::Time;
+///
+/// let time: Time = serde_json::from_str("\"2019-09-10T09:05:10.123Z\"").unwrap();
+/// assert_eq!(time.as_nanos(), 1_568_106_310_123_000_000);
+/// assert_eq!(time.as_datetime().timestamp_millis(), 1_568_106_310_123);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time(i64);
+
+impl Time {
+    /// Returns the timestamp as `DateTime`
+    pub fn as_datetime(self) -> DateTime<Utc> {
+        Utc.timestamp_nanos(self.0)
+    }
+
+    /// Returns the timestamp as nanoseconds since the Unix epoch, the precision InfluxDB stores timestamps in
+    pub fn as_nanos(self) -> i64 {
+        self.0
+    }
+}
+
+impl<'de> Deserialize<'de> for Time {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct TimeVisitor;
+
+        impl<'de> Visitor<'de> for TimeVisitor {
+            type Value = Time;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "an RFC3339 timestamp")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Time, E> {
+                DateTime::parse_from_rfc3339(value)
+                    .map_err(E::custom)?
+                    .timestamp_nanos_opt()
+                    .map(Time)
+                    .ok_or_else(|| {
+                        E::custom(format!(
+                            "{} exceeds the range of InfluxDB timestamps",
+                            value
+                        ))
+                    })
+            }
+
+            // the query did not request epoch timestamps, whose precision is unknown otherwise
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Time, E> {
+                Err(E::custom(format!(
+                    "epoch timestamp {} without the precision of the query",
+                    value
+                )))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Time, E> {
+                Err(E::custom(format!(
+                    "epoch timestamp {} without the precision of the query",
+                    value
+                )))
+            }
+        }
+
+        deserializer.deserialize_any(TimeVisitor)
+    }
 }
 
 impl InfluxDbClient {
@@ -117,30 +1105,41 @@ impl InfluxDbClient {
         use futures::future;
 
         let query = q.build().unwrap();
-        let basic_parameters: Vec<(String, String)> = self.into();
+        let precision = q.get_epoch();
+        let time_column = match q.get_time_column() {
+            "time" => None,
+            column => Some(column.to_string()),
+        };
+        let result_format = self.result_format();
+        let max_error_body_size = self.max_error_body_size();
+        let database;
         let client = {
             let read_query = query.get();
 
-            let mut url = match Url::parse_with_params(
-                format!("{url}/query", url = self.database_url()).as_str(),
-                basic_parameters,
-            ) {
+            let url = match self.read_url(&q, &read_query) {
                 Ok(url) => url,
-                Err(err) => {
-                    let error = InfluxDbError::UrlConstructionError {
-                        error: format!("{}", err),
-                    };
+                Err(error) => {
                     return Either::B(future::err::<DatabaseQueryResult, InfluxDbError>(error));
                 }
             };
-            url.query_pairs_mut().append_pair("q", &read_query.clone());
+            database = query_database(&url);
 
-            if read_query.contains("SELECT") || read_query.contains("SHOW") {
-                Client::new().get(url.as_str())
+            // an explicitly set method is trusted to return a JSON result, even for statements detected as writes
+            if q.get_method().is_some() || !requires_post(&read_query) {
+                match self.http_client() {
+                    Ok(http_client) => match q.http_method(&read_query) {
+                        Method::Get => http_client.get(url.as_str()),
+                        Method::Post => http_client.post(url.as_str()),
+                    }
+                    .header(ACCEPT, self.result_format().mime_type()),
+                    Err(error) => {
+                        return Either::B(future::err::<DatabaseQueryResult, InfluxDbError>(error));
+                    }
+                }
             } else {
                 let error = InfluxDbError::InvalidQueryError {
                     error: String::from(
-                        "Only SELECT and SHOW queries supported with JSON deserialization",
+                        "Only SELECT, SHOW and EXPLAIN queries supported with JSON deserialization",
                     ),
                 };
                 return Either::B(future::err::<DatabaseQueryResult, InfluxDbError>(error));
@@ -148,50 +1147,924 @@ impl InfluxDbClient {
         };
 
         Either::A(
-            client
-                .send()
-                .map_err(|err| InfluxDbError::ConnectionError { error: err })
-                .and_then(
-                    |res| -> future::FutureResult<reqwest::r#async::Response, InfluxDbError> {
-                        match res.status() {
-                            StatusCode::UNAUTHORIZED => {
-                                futures::future::err(InfluxDbError::AuthorizationError)
-                            }
-                            StatusCode::FORBIDDEN => {
-                                futures::future::err(InfluxDbError::AuthenticationError)
-                            }
-                            _ => futures::future::ok(res),
-                        }
-                    },
-                )
-                .and_then(|mut res| {
-                    let body = mem::replace(res.body_mut(), Decoder::empty());
-                    body.concat2().map_err(|err| InfluxDbError::ProtocolError {
-                        error: format!("{}", err),
-                    })
+            fetch_response(client, self.metrics_hook(), max_error_body_size)
+                .and_then(|(res, body)| {
+                    check_rate_limit(res.status(), res.headers())?;
+                    Ok((res, body))
                 })
-                .and_then(|body| {
-                    // Try parsing InfluxDBs { "error": "error message here" }
-                    if let Ok(error) = serde_json::from_slice::<_DatabaseError>(&body) {
-                        return futures::future::err(InfluxDbError::DatabaseError {
-                            error: error.error.to_string(),
-                        });
-                    } else {
-                        // Json has another structure, let's try actually parsing it to the type we're deserializing
-                        let from_slice = serde_json::from_slice::<DatabaseQueryResult>(&body);
-
-                        let deserialized = match from_slice {
-                            Ok(deserialized) => deserialized,
-                            Err(err) => {
-                                return futures::future::err(InfluxDbError::DeserializationError {
-                                    error: format!("serde error: {}", err),
-                                })
-                            }
-                        };
-
-                        return futures::future::result(Ok(deserialized));
+                .and_then(|(res, body)| match res.status() {
+                    StatusCode::UNAUTHORIZED => {
+                        futures::future::err(InfluxDbError::AuthorizationError)
                     }
+                    StatusCode::FORBIDDEN => {
+                        futures::future::err(InfluxDbError::AuthenticationError)
+                    }
+                    status if status.is_redirection() => {
+                        futures::future::err(unfollowed_redirect(status))
+                    }
+                    status => futures::future::ok((status, body)),
+                })
+                .and_then(move |(status, body)| {
+                    if let Some(error) = parse_database_error(&body) {
+                        return futures::future::err(error.truncated(max_error_body_size));
+                    }
+                    if !status.is_success() {
+                        let error = unexpected_response(status, &body);
+                        return futures::future::err(error.truncated(max_error_body_size));
+                    }
+                    let deserialized =
+                        parse_results(&body, result_format).map(|mut deserialized| {
+                            // CSV results always contain epoch timestamps, in nanoseconds unless the query requested otherwise
+                            deserialized.precision = match result_format {
+                                ResultFormat::Json => precision,
+                                ResultFormat::Csv => precision.or(Some(Precision::NANOSECONDS)),
+                            };
+                            deserialized.time_column = time_column;
+                            deserialized.database = database;
+                            deserialized
+                        });
+                    futures::future::result(deserialized)
                 }),
         )
     }
+
+    /// Pages through the result of a [`InfluxDbSelectQuery`](crate::query::select_query::InfluxDbSelectQuery),
+    /// issuing one query with `LIMIT page_size OFFSET <offset>` per page.
+    ///
+    /// The returned `Stream` yields each page until InfluxDB returns an empty page.
+    /// `LIMIT` and `OFFSET` apply per series, so a page of a grouped query contains up to `page_size` rows of every series.
+    /// A `page_size` of zero fails with an [`InvalidQueryError`](crate::error::InfluxDbError::InvalidQueryError).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use futures::prelude::*;
+    /// use influxdb::client::InfluxDbClient;
+    /// use influxdb::query::InfluxDbQuery;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Weather {
+    ///     time: String,
+    ///     temperature: i32,
+    /// }
+    ///
+    /// let mut rt = tokio::runtime::current_thread::Runtime::new().unwrap();
+    /// let client = InfluxDbClient::new("http://localhost:8086", "test");
+    /// let pages = client
+    ///     .paginate::<Weather>(InfluxDbQuery::select_query("weather"), 100)
+    ///     .collect();
+    /// let _pages = rt.block_on(pages);
+    /// ```
+    pub fn paginate<T>(
+        &self,
+        query: InfluxDbSelectQuery,
+        page_size: usize,
+    ) -> impl Stream<Item = InfluxDbReturn<T>, Error = InfluxDbError>
+    where
+        T: DeserializeOwned + 'static,
+    {
+        let client = self.clone();
+        paginate(query, page_size, move |read_query| {
+            client.json_query(read_query)
+        })
+    }
+}
+
+/// Drives the pagination of `query`, using `fetch` to run the query of each page
+fn paginate<T, F, R>(
+    query: InfluxDbSelectQuery,
+    page_size: usize,
+    mut fetch: F,
+) -> impl Stream<Item = InfluxDbReturn<T>, Error = InfluxDbError>
+where
+    T: DeserializeOwned + 'static,
+    F: FnMut(InfluxDbReadQuery) -> R,
+    R: Future<Item = DatabaseQueryResult, Error = InfluxDbError>,
+{
+    use futures::future::Either;
+
+    // an empty page ends the pagination, so every page would be the first one
+    if page_size == 0 {
+        return Either::A(stream::once(Err(InfluxDbError::InvalidQueryError {
+            error: "the page size of a pagination must be greater than 0".to_string(),
+        })));
+    }
+    let pages = stream::unfold(0, move |offset| {
+        let page_query = query.clone().limit(page_size).offset(offset).into();
+        Some(
+            fetch(page_query)
+                .and_then(|mut result| result.deserialize_next::<T>())
+                .map(move |page| (page, offset + page_size)),
+        )
+    })
+    .take_while(|page| {
+        futures::future::ok(page.series.iter().any(|series| !series.values.is_empty()))
+    });
+    Either::B(pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        json_chunks, paginate, parse_database_error, parse_results, query_database,
+        DatabaseQueryResult, InfluxDbMessage, InfluxDbReturn, Time,
+    };
+    use crate::client::InfluxDbClient;
+    use crate::client::ResultFormat;
+    use crate::error::InfluxDbError;
+    use crate::query::{InfluxDbQuery, Precision};
+
+    use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+    use futures::{Future, Stream};
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    fn epoch_result(precision: Option<Precision>) -> DatabaseQueryResult {
+        let mut result: DatabaseQueryResult = serde_json::from_str(
+            r#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","temperature"],"values":[[1568106310123,82]]}]}]}"#,
+        )
+        .unwrap();
+        result.precision = precision;
+        result
+    }
+
+    #[test]
+    fn test_deserialize_epoch_into_integer() {
+        #[derive(Deserialize)]
+        struct Weather {
+            time: i64,
+            temperature: i32,
+        }
+
+        let weather = epoch_result(Some(Precision::MILLISECONDS))
+            .deserialize_next::<Weather>()
+            .wait()
+            .unwrap();
+        assert_eq!(weather.series[0].values[0].time, 1_568_106_310_123);
+        assert_eq!(weather.series[0].values[0].temperature, 82);
+    }
+
+    #[test]
+    fn test_deserialize_epoch_into_datetime() {
+        #[derive(Deserialize)]
+        struct Weather {
+            time: DateTime<Utc>,
+            temperature: i32,
+        }
+
+        let weather = epoch_result(Some(Precision::MILLISECONDS))
+            .deserialize_next::<Weather>()
+            .wait()
+            .unwrap();
+        assert_eq!(
+            weather.series[0].values[0].time,
+            Utc.timestamp_opt(1_568_106_310, 123_000_000).unwrap()
+        );
+        assert_eq!(weather.series[0].values[0].temperature, 82);
+    }
+
+    #[test]
+    fn test_deserialize_epoch_custom_time_column() {
+        #[derive(Deserialize)]
+        struct Weather {
+            ts: DateTime<Utc>,
+            temperature: i32,
+        }
+
+        let ts_result = |time_column: Option<&str>| {
+            let mut result: DatabaseQueryResult = serde_json::from_str(
+                r#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["ts","temperature"],"values":[[1568106310123,82]]}]}]}"#,
+            )
+            .unwrap();
+            result.precision = Some(Precision::MILLISECONDS);
+            result.time_column = time_column.map(str::to_string);
+            result
+        };
+        // only the `time` column is converted by default
+        assert!(ts_result(None)
+            .deserialize_next::<Weather>()
+            .wait()
+            .is_err());
+
+        let mut result = ts_result(Some("ts"));
+        let weather = result.deserialize_next::<Weather>().wait().unwrap();
+        assert_eq!(
+            weather.series[0].values[0].ts,
+            Utc.timestamp_opt(1_568_106_310, 123_000_000).unwrap()
+        );
+        assert_eq!(weather.series[0].values[0].temperature, 82);
+    }
+
+    #[test]
+    fn test_deserialize_rfc3339_nanoseconds() {
+        #[derive(Deserialize)]
+        struct Weather {
+            time: DateTime<Utc>,
+            temperature: i32,
+        }
+
+        let mut result: DatabaseQueryResult = serde_json::from_str(
+            r#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","temperature"],"values":[["2020-01-01T00:00:00.123456789Z",82],["2020-01-01T00:00:01.000000001Z",83]]}]}]}"#,
+        )
+        .unwrap();
+        let weather = result.deserialize_next::<Weather>().wait().unwrap();
+        let values = &weather.series[0].values;
+        assert_eq!(
+            values[0].time,
+            Utc.timestamp_opt(1_577_836_800, 123_456_789).unwrap()
+        );
+        assert_eq!(values[0].temperature, 82);
+        assert_eq!(values[1].time, Utc.timestamp_opt(1_577_836_801, 1).unwrap());
+        assert_eq!(values[1].temperature, 83);
+    }
+
+    #[test]
+    fn test_deserialize_nanosecond_epoch_into_datetime() {
+        #[derive(Deserialize)]
+        struct Weather {
+            time: DateTime<Utc>,
+            temperature: i32,
+        }
+
+        let mut result: DatabaseQueryResult = serde_json::from_str(
+            r#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","temperature"],"values":[[1577836800123456789,82]]}]}]}"#,
+        )
+        .unwrap();
+        result.precision = Some(Precision::NANOSECONDS);
+        let weather = result.deserialize_next::<Weather>().wait().unwrap();
+        assert_eq!(
+            weather.series[0].values[0].time,
+            Utc.timestamp_opt(1_577_836_800, 123_456_789).unwrap()
+        );
+        assert_eq!(weather.series[0].values[0].temperature, 82);
+    }
+
+    #[test]
+    fn test_deserialize_epoch_into_duration() {
+        #[derive(Deserialize)]
+        struct Weather {
+            time: Duration,
+            temperature: i32,
+        }
+
+        let weather = epoch_result(Some(Precision::MILLISECONDS))
+            .deserialize_next::<Weather>()
+            .wait()
+            .unwrap();
+        assert_eq!(
+            weather.series[0].values[0].time,
+            Duration::from_millis(1_568_106_310_123)
+        );
+        assert_eq!(weather.series[0].values[0].temperature, 82);
+    }
+
+    #[derive(Deserialize)]
+    struct WeatherWithTime {
+        time: Time,
+        temperature: i32,
+    }
+
+    #[test]
+    fn test_deserialize_epoch_into_time() {
+        let weather = epoch_result(Some(Precision::MILLISECONDS))
+            .deserialize_next::<WeatherWithTime>()
+            .wait()
+            .unwrap();
+        let time = weather.series[0].values[0].time;
+        assert_eq!(time.as_nanos(), 1_568_106_310_123_000_000);
+        assert_eq!(
+            time.as_datetime(),
+            Utc.timestamp_opt(1_568_106_310, 123_000_000).unwrap()
+        );
+        assert_eq!(weather.series[0].values[0].temperature, 82);
+
+        assert!(epoch_result(None)
+            .deserialize_next::<WeatherWithTime>()
+            .wait()
+            .is_err());
+    }
+
+    #[test]
+    fn test_deserialize_rfc3339_into_time() {
+        let mut result: DatabaseQueryResult = serde_json::from_str(
+            r#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","temperature"],"values":[["2019-09-10T09:05:10.123Z",82]]}]}]}"#,
+        )
+        .unwrap();
+        let weather = result.deserialize_next::<WeatherWithTime>().wait().unwrap();
+        let time = weather.series[0].values[0].time;
+        assert_eq!(time.as_nanos(), 1_568_106_310_123_000_000);
+        assert_eq!(
+            time.as_datetime(),
+            Utc.timestamp_opt(1_568_106_310, 123_000_000).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_results_by_format() {
+        let json = br#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","temperature"],"values":[["2019-09-10T09:05:10Z",82]]}]}]}"#;
+        let csv = b"name,tags,time,temperature\nweather,,1568106310000000000,82\n";
+
+        for (body, format) in &[
+            (&json[..], ResultFormat::Json),
+            (&csv[..], ResultFormat::Csv),
+        ] {
+            let mut result = parse_results(body, *format).unwrap();
+            result.precision = Some(Precision::NANOSECONDS);
+            let weather = result.deserialize_next::<WeatherWithTime>().wait().unwrap();
+            assert_eq!(weather.series[0].name, "weather");
+            assert_eq!(
+                weather.series[0].values[0].time.as_nanos(),
+                1_568_106_310_000_000_000,
+                "{:?}",
+                format
+            );
+            assert_eq!(weather.series[0].values[0].temperature, 82);
+        }
+
+        // each format only parses its own kind of body
+        assert!(parse_results(csv, ResultFormat::Json).is_err());
+        assert!(parse_results(json, ResultFormat::Csv).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_messages() {
+        let mut result = serde_json::from_str::<DatabaseQueryResult>(
+            r#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","temperature"],"values":[["2019-09-10T09:05:10Z",82]]}],
+                "messages":[{"level":"warning","text":"deprecated use of 'SHOW FIELD KEYS' without a measurement"}]}]}"#,
+        )
+        .unwrap();
+
+        let weather = result.deserialize_next::<WeatherWithTime>().wait().unwrap();
+        assert_eq!(weather.series[0].values[0].temperature, 82);
+        assert_eq!(
+            weather.messages,
+            vec![InfluxDbMessage {
+                level: "warning".to_string(),
+                text: "deprecated use of 'SHOW FIELD KEYS' without a measurement".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_chunked_results() {
+        let body = br#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","temperature"],"values":[["2019-09-10T09:05:10Z",82]],"partial":true}],"messages":[{"level":"warning","text":"first"}],"partial":true}]}
+{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","temperature"],"values":[["2019-09-10T09:06:10Z",84]]},{"name":"humidity","columns":["time","humidity"],"values":[["2019-09-10T09:05:10Z",69]]}],"messages":[{"level":"warning","text":"second"}]}]}
+{"results":[{"statement_id":1,"series":[{"name":"weather","columns":["time","temperature"],"values":[["2019-09-10T09:07:10Z",86]]}]}]}
+"#;
+
+        let mut result = parse_results(body, ResultFormat::Json).unwrap();
+        let first = result.deserialize_next::<Value>().wait().unwrap();
+        assert_eq!(first.series.len(), 2);
+        assert_eq!(first.series[0].name, "weather");
+        assert_eq!(
+            first.series[0].values,
+            vec![
+                json!(["2019-09-10T09:05:10Z", 82]),
+                json!(["2019-09-10T09:06:10Z", 84])
+            ]
+        );
+        assert_eq!(first.series[1].name, "humidity");
+        assert_eq!(first.messages.len(), 2);
+
+        let second = result.deserialize_next::<Value>().wait().unwrap();
+        assert_eq!(second.statement_id, Some(1));
+        assert_eq!(second.series[0].values.len(), 1);
+
+        assert!(parse_results(b"", ResultFormat::Json).is_err());
+    }
+
+    #[test]
+    fn test_rows_across_series() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Weather {
+            time: String,
+            temperature: i32,
+        }
+
+        let mut result = serde_json::from_str::<DatabaseQueryResult>(
+            r#"{"results":[{"statement_id":0,"series":[
+                {"name":"weather","tags":{"location":"us-midwest"},"columns":["time","temperature"],"values":[["2019-09-10T09:05:10Z",82],["2019-09-10T09:06:10Z",84]]},
+                {"name":"weather","tags":{"location":"us-east"},"columns":["time","temperature"],"values":[["2019-09-10T09:05:10Z",64]]}
+            ]}]}"#,
+        )
+        .unwrap();
+        let weather = result.deserialize_next::<Weather>().wait().unwrap();
+
+        let temperatures: Vec<i32> = weather.rows().map(|row| row.temperature).collect();
+        assert_eq!(temperatures, vec![82, 84, 64]);
+
+        let locations: Vec<(&str, i32)> = weather
+            .rows_with_series()
+            .map(|(series, row)| (series.tags["location"].as_str(), row.temperature))
+            .collect();
+        assert_eq!(
+            locations,
+            vec![("us-midwest", 82), ("us-midwest", 84), ("us-east", 64)]
+        );
+
+        let rows: Vec<Weather> = weather.into_rows().collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            rows[2],
+            Weather {
+                time: "2019-09-10T09:05:10Z".to_string(),
+                temperature: 64
+            }
+        );
+    }
+
+    #[test]
+    fn test_json_chunks_partial() {
+        let body = br#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","temperature"],"values":[["2019-09-10T09:05:10Z",82]],"partial":true}],"partial":true}]}
+{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","temperature"],"values":[["2019-09-10T09:06:10Z",84]]},{"name":"humidity","columns":["time","humidity"],"values":[["2019-09-10T09:05:10Z",69]],"partial":true}],"partial":true}]}
+{"results":[{"statement_id":0,"series":[{"name":"humidity","columns":["time","humidity"],"values":[["2019-09-10T09:06:10Z",71]]}]}]}
+"#;
+
+        let chunks: Vec<InfluxDbReturn<Value>> = json_chunks(body)
+            .map(|chunk| chunk.unwrap().deserialize_next().wait().unwrap())
+            .collect();
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks[0].partial);
+        assert!(chunks[0].series[0].partial);
+        assert!(chunks[1].partial);
+        assert!(!chunks[1].series[0].partial);
+        assert!(chunks[1].series[1].partial);
+        assert!(!chunks[2].partial);
+        assert!(!chunks[2].series[0].partial);
+
+        let merged = parse_results(body, ResultFormat::Json)
+            .unwrap()
+            .deserialize_next::<Value>()
+            .wait()
+            .unwrap();
+        assert!(!merged.partial);
+        assert_eq!(merged.series.len(), 2);
+        assert!(merged.series.iter().all(|series| !series.partial));
+        assert_eq!(merged.series[0].values.len(), 2);
+        assert_eq!(merged.series[1].values.len(), 2);
+
+        // a response ending before the last chunk of the statement stays partial
+        let truncated = body.split(|&b| b == b'\n').next().unwrap();
+        let merged = parse_results(truncated, ResultFormat::Json)
+            .unwrap()
+            .deserialize_next::<Value>()
+            .wait()
+            .unwrap();
+        assert!(merged.partial);
+        assert!(merged.series[0].partial);
+    }
+
+    #[test]
+    fn test_deserialize_offset_timestamps() {
+        #[derive(Deserialize)]
+        struct Weather {
+            time: DateTime<FixedOffset>,
+            temperature: i32,
+        }
+
+        #[derive(Deserialize)]
+        struct UtcWeather {
+            time: DateTime<Utc>,
+            #[allow(dead_code)]
+            temperature: i32,
+        }
+
+        // the result of a query using tz('America/New_York')
+        let body = r#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","temperature"],"values":[["2019-09-10T05:05:10.123-04:00",82]]}]}]}"#;
+        let instant = Utc.with_ymd_and_hms(2019, 9, 10, 9, 5, 10).unwrap()
+            + chrono::Duration::milliseconds(123);
+
+        let mut result = serde_json::from_str::<DatabaseQueryResult>(body).unwrap();
+        let weather = result.deserialize_next::<Weather>().wait().unwrap();
+        let row = &weather.series[0].values[0];
+        assert_eq!(
+            row.time.offset(),
+            &FixedOffset::west_opt(4 * 60 * 60).unwrap()
+        );
+        assert_eq!(row.time, instant);
+        assert_eq!(row.temperature, 82);
+
+        let mut result = serde_json::from_str::<DatabaseQueryResult>(body).unwrap();
+        let weather = result.deserialize_next::<UtcWeather>().wait().unwrap();
+        assert_eq!(weather.series[0].values[0].time, instant);
+
+        let mut result = serde_json::from_str::<DatabaseQueryResult>(body).unwrap();
+        let rows = result
+            .deserialize_next_rows::<(Time, i32)>()
+            .wait()
+            .unwrap();
+        assert_eq!(rows[0].0.as_datetime(), instant);
+    }
+
+    #[test]
+    fn test_deserialize_epoch_without_precision() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Weather {
+            time: DateTime<Utc>,
+            temperature: i32,
+        }
+
+        let weather = epoch_result(None).deserialize_next::<Weather>().wait();
+        assert!(weather.is_err());
+    }
+
+    #[test]
+    fn test_parse_database_error() {
+        match parse_database_error(
+            br#"{"error":"unable to parse 'weather temperature=': missing field value"}"#,
+        ) {
+            Some(InfluxDbError::DatabaseError { error, code }) => {
+                assert_eq!(
+                    error,
+                    "unable to parse 'weather temperature=': missing field value"
+                );
+                assert_eq!(code, None);
+            }
+            _ => panic!("Should be a DatabaseError"),
+        }
+    }
+
+    #[test]
+    fn test_parse_database_error_with_code() {
+        match parse_database_error(
+            br#"{"code":"invalid","message":"partial write: field type conflict"}"#,
+        ) {
+            Some(InfluxDbError::DatabaseError { error, code }) => {
+                assert_eq!(error, "partial write: field type conflict");
+                assert_eq!(code, Some("invalid".to_string()));
+            }
+            _ => panic!("Should be a DatabaseError"),
+        }
+    }
+
+    #[test]
+    fn test_parse_database_not_found() {
+        match parse_database_error(br#"{"error":"database not found: \"test\""}"#) {
+            Some(InfluxDbError::DatabaseNotFound { name }) => assert_eq!(name, "test"),
+            _ => panic!("Should be DatabaseNotFound"),
+        }
+        match parse_database_error(
+            br#"{"code":"not found","message":"retention policy not found: autogen"}"#,
+        ) {
+            Some(InfluxDbError::RetentionPolicyNotFound { name }) => assert_eq!(name, "autogen"),
+            _ => panic!("Should be RetentionPolicyNotFound"),
+        }
+
+        let mut result = serde_json::from_str::<DatabaseQueryResult>(
+            r#"{"results":[{"statement_id":0,"error":"database not found: weather"}]}"#,
+        )
+        .unwrap();
+        match result.deserialize_next::<Value>().wait() {
+            Err(InfluxDbError::DatabaseNotFound { name }) => assert_eq!(name, "weather"),
+            _ => panic!("Should be DatabaseNotFound"),
+        }
+    }
+
+    #[test]
+    fn test_parse_database_error_no_error() {
+        assert!(parse_database_error(br#"{"results":[{"statement_id":0}]}"#).is_none());
+    }
+
+    fn multi_statement_result() -> DatabaseQueryResult {
+        serde_json::from_str(
+            r#"{"results":[{"statement_id":1,"series":[{"name":"humidity","columns":["time","humidity"],"values":[["1970-01-01T11:00:00Z",69]]}]},{"statement_id":0,"series":[{"name":"temperature","columns":["time","temperature"],"values":[["1970-01-01T11:00:00Z",16]]}]}]}"#,
+        )
+        .unwrap()
+    }
+
+    #[derive(Deserialize)]
+    struct Temperature {
+        #[allow(dead_code)]
+        time: String,
+        temperature: i32,
+    }
+
+    #[derive(Deserialize)]
+    struct Humidity {
+        #[allow(dead_code)]
+        time: String,
+        humidity: i32,
+    }
+
+    #[test]
+    fn test_deserialize_next_by_statement_id() {
+        let mut result = multi_statement_result();
+
+        let temperature = result.deserialize_next::<Temperature>().wait().unwrap();
+        assert_eq!(temperature.statement_id, Some(0));
+        assert_eq!(temperature.series[0].values[0].temperature, 16);
+
+        let humidity = result.deserialize_next::<Humidity>().wait().unwrap();
+        assert_eq!(humidity.statement_id, Some(1));
+        assert_eq!(humidity.series[0].values[0].humidity, 69);
+
+        assert!(result.deserialize_next::<Humidity>().wait().is_err());
+    }
+
+    #[test]
+    fn test_deserialize_statement() {
+        let mut result = multi_statement_result();
+
+        let humidity = result.deserialize_statement::<Humidity>(1).wait().unwrap();
+        assert_eq!(humidity.series[0].name, "humidity");
+        assert_eq!(humidity.series[0].values[0].humidity, 69);
+
+        assert!(result.deserialize_statement::<Humidity>(1).wait().is_err());
+
+        let temperature = result
+            .deserialize_statement::<Temperature>(0)
+            .wait()
+            .unwrap();
+        assert_eq!(temperature.series[0].name, "temperature");
+        assert_eq!(temperature.series[0].values[0].temperature, 16);
+    }
+
+    #[test]
+    fn test_deserialize_statements_with_errors() {
+        let mut result = serde_json::from_str::<DatabaseQueryResult>(
+            r#"{"results":[{"statement_id":1,"series":[{"name":"temperature","columns":["time","temperature"],"values":[["2019-09-10T09:05:10Z",16]]}]},{"statement_id":0,"error":"measurement not found"}]}"#,
+        )
+        .unwrap();
+
+        let statements = result
+            .deserialize_statements::<Temperature>()
+            .wait()
+            .unwrap();
+        assert_eq!(statements.len(), 2);
+        match &statements[0] {
+            Err(InfluxDbError::DatabaseError { error, .. }) => {
+                assert_eq!(error, "measurement not found")
+            }
+            _ => panic!("Should be a DatabaseError"),
+        }
+        let temperature = statements[1].as_ref().unwrap();
+        assert_eq!(temperature.statement_id, Some(1));
+        assert_eq!(temperature.series[0].values[0].temperature, 16);
+
+        assert!(result.results.is_empty());
+    }
+
+    #[test]
+    fn test_deserialize_next_with_mapping() {
+        #[derive(Deserialize)]
+        struct Weather {
+            time: String,
+            temperature: f64,
+        }
+
+        let mut result: DatabaseQueryResult = serde_json::from_str(
+            r#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","temperature"],"values":[["11:00",16.5],["12:00","17.5"]]}]}]}"#,
+        )
+        .unwrap();
+
+        let weather = result
+            .deserialize_next_with::<Weather, _>(|column, value| match (column, value) {
+                ("time", serde_json::Value::String(time)) => {
+                    serde_json::Value::String(format!("1970-01-01T{}:00Z", time))
+                }
+                ("temperature", serde_json::Value::String(temperature)) => {
+                    serde_json::Value::from(temperature.parse::<f64>().unwrap())
+                }
+                (_, value) => value,
+            })
+            .wait()
+            .unwrap();
+
+        let values = &weather.series[0].values;
+        assert_eq!(values[0].time, "1970-01-01T11:00:00Z");
+        assert_eq!(values[0].temperature, 16.5);
+        assert_eq!(values[1].time, "1970-01-01T12:00:00Z");
+        assert_eq!(values[1].temperature, 17.5);
+    }
+
+    fn filled_result() -> DatabaseQueryResult {
+        serde_json::from_str(
+            r#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","mean"],"values":[["1970-01-01T11:00:00Z",16.5],["1970-01-01T12:00:00Z",null]]}]}]}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_deserialize_null_into_option() {
+        #[derive(Deserialize)]
+        struct Mean {
+            #[allow(dead_code)]
+            time: String,
+            mean: Option<f64>,
+        }
+
+        let result = filled_result().deserialize_next::<Mean>().wait().unwrap();
+        let values = &result.series[0].values;
+        assert_eq!(values[0].mean, Some(16.5));
+        assert_eq!(values[1].mean, None);
+    }
+
+    #[test]
+    fn test_deserialize_error_names_row_and_column() {
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Weather {
+            time: String,
+            temperature: i32,
+        }
+
+        let mut result: DatabaseQueryResult = serde_json::from_str(
+            r#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","temperature"],"values":[["2019-09-10T09:05:10Z",82],["2019-09-10T09:06:10Z","hot"]]}]}]}"#,
+        )
+        .unwrap();
+        match result.deserialize_next::<Weather>().wait() {
+            Err(InfluxDbError::DeserializationError { error }) => {
+                assert!(error.contains("row 1 "), "{}", error);
+                assert!(error.contains("column \"temperature\""), "{}", error);
+                assert!(error.contains("value \"hot\""), "{}", error);
+            }
+            other => panic!("Should be a DeserializationError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_deserialize_null_into_required_field() {
+        #[derive(Deserialize, Debug)]
+        struct Mean {
+            #[allow(dead_code)]
+            time: String,
+            #[allow(dead_code)]
+            mean: f64,
+        }
+
+        match filled_result().deserialize_next::<Mean>().wait() {
+            Err(InfluxDbError::DeserializationError { error }) => {
+                assert!(error.contains("invalid type: null"), "{}", error);
+                assert!(error.ends_with("use an `Option` for columns which may contain null"));
+            }
+            _ => panic!("Should be a DeserializationError"),
+        }
+    }
+
+    fn three_column_result() -> DatabaseQueryResult {
+        serde_json::from_str(
+            r#"{"results":[{"statement_id":0,"series":[{"name":"weather","columns":["time","temperature","location"],"values":[["1970-01-01T11:00:00Z",16.5,"berlin"],["1970-01-01T12:00:00Z",17.0,"london"]]}]}]}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_deserialize_next_rows_into_tuples() {
+        let rows = three_column_result()
+            .deserialize_next_rows::<(String, f64, String)>()
+            .wait()
+            .unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                (
+                    "1970-01-01T11:00:00Z".to_string(),
+                    16.5,
+                    "berlin".to_string()
+                ),
+                (
+                    "1970-01-01T12:00:00Z".to_string(),
+                    17.0,
+                    "london".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_next_rows_arity_mismatch() {
+        let result = three_column_result()
+            .deserialize_next_rows::<(String, f64)>()
+            .wait();
+
+        match result {
+            Err(InfluxDbError::DeserializationError { error }) => {
+                assert_eq!(error, "result has 3 columns, but the tuple has 2 elements")
+            }
+            _ => panic!("expected a DeserializationError"),
+        }
+    }
+
+    #[test]
+    fn test_deserialize_next_rows_with_raw() {
+        let mut result = three_column_result();
+        let expected_raw = result.results[0].clone();
+
+        let (rows, raw) = result
+            .deserialize_next_rows_with_raw::<(String, f64, String)>()
+            .wait()
+            .unwrap();
+        assert_eq!(raw, expected_raw);
+        let rows = rows.unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(raw["series"][0]["values"][1][1], rows[1].1);
+        assert_eq!(raw["series"][0]["values"][1][2], rows[1].2.as_str());
+
+        // the JSON is kept if the rows can not be deserialized
+        let (rows, raw) = three_column_result()
+            .deserialize_next_rows_with_raw::<(String, String, String)>()
+            .wait()
+            .unwrap();
+        assert!(rows.is_err());
+        assert_eq!(raw, expected_raw);
+
+        // only a missing result fails
+        assert!(result
+            .deserialize_next_rows_with_raw::<(String, f64, String)>()
+            .wait()
+            .is_err());
+    }
+
+    #[test]
+    fn test_paginate_stops_at_empty_page() {
+        let pages = [
+            r#"{"results":[{"statement_id":0,"series":[{"name":"temperature","columns":["time","temperature"],"values":[["1970-01-01T11:00:00Z",16],["1970-01-01T12:00:00Z",17]]}]}]}"#,
+            r#"{"results":[{"statement_id":0,"series":[{"name":"temperature","columns":["time","temperature"],"values":[["1970-01-01T13:00:00Z",18]]}]}]}"#,
+            r#"{"results":[{"statement_id":0}]}"#,
+            r#"{"results":[{"statement_id":0,"series":[{"name":"temperature","columns":["time","temperature"],"values":[["1970-01-01T14:00:00Z",19]]}]}]}"#,
+        ];
+        let mut queries = vec![];
+
+        let result = paginate::<Temperature, _, _>(
+            InfluxDbQuery::select_query("temperature"),
+            2,
+            |read_query| {
+                let page = pages[queries.len()];
+                queries.push(read_query.to_string());
+                futures::future::ok(serde_json::from_str::<DatabaseQueryResult>(page).unwrap())
+            },
+        )
+        .collect()
+        .wait()
+        .unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].series[0].values[1].temperature, 17);
+        assert_eq!(result[1].series[0].values[0].temperature, 18);
+        assert_eq!(
+            queries,
+            vec![
+                "SELECT * FROM \"temperature\" LIMIT 2 OFFSET 0",
+                "SELECT * FROM \"temperature\" LIMIT 2 OFFSET 2",
+                "SELECT * FROM \"temperature\" LIMIT 2 OFFSET 4",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_paginate_rejects_empty_pages() {
+        let mut fetched = false;
+        let result =
+            paginate::<Temperature, _, _>(InfluxDbQuery::select_query("temperature"), 0, |_| {
+                fetched = true;
+                futures::future::ok(
+                    serde_json::from_str::<DatabaseQueryResult>(r#"{"results":[]}"#).unwrap(),
+                )
+            })
+            .collect()
+            .wait();
+
+        match result {
+            Err(InfluxDbError::InvalidQueryError { error }) => assert_eq!(
+                error,
+                "the page size of a pagination must be greater than 0"
+            ),
+            Err(error) => panic!("Should be an InvalidQueryError, got {:?}", error),
+            Ok(_) => panic!("Should be an InvalidQueryError"),
+        }
+        assert!(!fetched);
+    }
+
+    #[test]
+    fn test_result_database() {
+        #[derive(Deserialize)]
+        struct Weather {
+            time: i64,
+            temperature: i32,
+        }
+
+        let client = InfluxDbClient::new("http://localhost:8086", "test");
+        let read_query = InfluxDbQuery::raw_read_query("SELECT temperature FROM weather")
+            .with_database("archive");
+        let url = client
+            .read_url(&read_query, "SELECT temperature FROM weather")
+            .unwrap();
+
+        let mut result = epoch_result(None);
+        result.database = query_database(&url);
+        let weather = result.deserialize_next::<Weather>().wait().unwrap();
+        assert_eq!(weather.database, Some("archive".to_string()));
+        assert_eq!(weather.series[0].values[0].time, 1_568_106_310_123);
+        assert_eq!(weather.series[0].values[0].temperature, 82);
+
+        let without_database = read_query.without_database();
+        let url = client
+            .read_url(&without_database, "SHOW DATABASES")
+            .unwrap();
+        assert_eq!(query_database(&url), None);
+    }
 }
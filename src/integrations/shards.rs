@@ -0,0 +1,179 @@
+//! Inspection and removal of shards, as returned by `SHOW SHARDS` and removed by `DROP SHARD`.
+//!
+//! ```rust,no_run
+//! use futures::prelude::*;
+//! use influxdb::client::InfluxDbClient;
+//!
+//! let mut rt = tokio::runtime::current_thread::Runtime::new().unwrap();
+//! let client = InfluxDbClient::new("http://localhost:8086", "test");
+//!
+//! for shard in rt.block_on(client.show_shards()).unwrap() {
+//!     if shard.database == "scratch" {
+//!         rt.block_on(client.drop_shard(shard.id)).unwrap();
+//!     }
+//! }
+//! ```
+
+use chrono::{DateTime, Utc};
+use futures::Future;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::client::InfluxDbClient;
+use crate::error::InfluxDbError;
+use crate::integrations::serde_integration::InfluxDbReturn;
+use crate::query::read_query::InfluxDbReadQuery;
+
+/// A shard of `SHOW SHARDS`, holding the data of a retention policy for the time range from `start_time` to `end_time`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shard {
+    pub id: u64,
+    pub database: String,
+    pub retention_policy: String,
+    pub shard_group: u64,
+    pub start_time: DateTime<Utc>,
+    /// End of the time range, exclusive
+    pub end_time: DateTime<Utc>,
+    /// Time the shard is dropped by the retention policy
+    pub expiry_time: DateTime<Utc>,
+    /// Ids of the data nodes storing the shard, empty for InfluxDB OSS
+    pub owners: Vec<u64>,
+}
+
+/// A row of `SHOW SHARDS`, before its owners are parsed
+#[derive(Deserialize)]
+struct ShardRow {
+    id: u64,
+    database: String,
+    retention_policy: String,
+    shard_group: u64,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    expiry_time: DateTime<Utc>,
+    #[serde(default)]
+    owners: String,
+}
+
+impl InfluxDbClient {
+    /// Returns the shards of all databases using `SHOW SHARDS`
+    pub fn show_shards(&self) -> impl Future<Item = Vec<Shard>, Error = InfluxDbError> {
+        self.json_query(InfluxDbReadQuery::new("SHOW SHARDS").without_database())
+            .and_then(|mut result| result.deserialize_next::<Vec<Value>>())
+            .and_then(shards)
+    }
+
+    /// Drops the shard with the given id using `DROP SHARD`, deleting its data
+    pub fn drop_shard(&self, id: u64) -> impl Future<Item = String, Error = InfluxDbError> {
+        self.query(&drop_shard_query(id))
+    }
+}
+
+/// Creates the `DROP SHARD` statement, which does not run against a database
+fn drop_shard_query(id: u64) -> InfluxDbReadQuery {
+    InfluxDbReadQuery::new(format!("DROP SHARD {}", id)).without_database()
+}
+
+/// Converts the series of a `SHOW SHARDS` result, one per database, into a flat list of shards.
+///
+/// Databases without shards are returned as series without values.
+fn shards(result: InfluxDbReturn<Vec<Value>>) -> Result<Vec<Shard>, InfluxDbError> {
+    let mut shards = vec![];
+    for series in result.series {
+        for row in series.values {
+            let row: Map<String, Value> = series.columns.iter().cloned().zip(row).collect();
+            let row: ShardRow = serde_json::from_value(Value::Object(row)).map_err(|err| {
+                InfluxDbError::DeserializationError {
+                    error: format!("could not deserialize shard: {}", err),
+                }
+            })?;
+            shards.push(shard(row)?);
+        }
+    }
+    Ok(shards)
+}
+
+fn shard(row: ShardRow) -> Result<Shard, InfluxDbError> {
+    let owners = row
+        .owners
+        .split(',')
+        .map(str::trim)
+        .filter(|owner| !owner.is_empty())
+        .map(|owner| {
+            owner
+                .parse()
+                .map_err(|_| InfluxDbError::DeserializationError {
+                    error: format!("invalid owner \"{}\" of shard {}", owner, row.id),
+                })
+        })
+        .collect::<Result<Vec<u64>, InfluxDbError>>()?;
+
+    Ok(Shard {
+        id: row.id,
+        database: row.database,
+        retention_policy: row.retention_policy,
+        shard_group: row.shard_group,
+        start_time: row.start_time,
+        end_time: row.end_time,
+        expiry_time: row.expiry_time,
+        owners,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{drop_shard_query, shards};
+    use crate::integrations::serde_integration::DatabaseQueryResult;
+    use crate::query::InfluxDbQuery;
+
+    use chrono::{TimeZone, Utc};
+    use futures::Future;
+
+    #[test]
+    fn test_shards() {
+        let mut result = serde_json::from_str::<DatabaseQueryResult>(
+            r#"{"results":[{"statement_id":0,"series":[
+                {"name":"_internal","columns":["id","database","retention_policy","shard_group","start_time","end_time","expiry_time","owners"],"values":[
+                    [1,"_internal","monitor",1,"2019-09-09T00:00:00Z","2019-09-10T00:00:00Z","2019-09-17T00:00:00Z",""],
+                    [2,"_internal","monitor",2,"2019-09-10T00:00:00Z","2019-09-11T00:00:00Z","2019-09-18T00:00:00Z",""]
+                ]},
+                {"name":"empty","columns":["id","database","retention_policy","shard_group","start_time","end_time","expiry_time","owners"]},
+                {"name":"weather","columns":["id","database","retention_policy","shard_group","start_time","end_time","expiry_time","owners"],"values":[
+                    [3,"weather","autogen",3,"2019-09-09T00:00:00Z","2019-09-16T00:00:00Z","2019-09-16T00:00:00Z","4,5"]
+                ]}
+            ]}]}"#,
+        )
+        .unwrap();
+        let shards = shards(result.deserialize_next().wait().unwrap()).unwrap();
+
+        assert_eq!(shards.len(), 3);
+        assert_eq!(shards[0].id, 1);
+        assert_eq!(shards[0].database, "_internal");
+        assert_eq!(shards[0].retention_policy, "monitor");
+        assert_eq!(
+            shards[1].start_time,
+            Utc.with_ymd_and_hms(2019, 9, 10, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            shards[1].end_time,
+            Utc.with_ymd_and_hms(2019, 9, 11, 0, 0, 0).unwrap()
+        );
+        assert!(shards[1].owners.is_empty());
+
+        assert_eq!(shards[2].id, 3);
+        assert_eq!(shards[2].database, "weather");
+        assert_eq!(shards[2].shard_group, 3);
+        assert_eq!(
+            shards[2].expiry_time,
+            Utc.with_ymd_and_hms(2019, 9, 16, 0, 0, 0).unwrap()
+        );
+        assert_eq!(shards[2].owners, vec![4, 5]);
+    }
+
+    #[test]
+    fn test_drop_shard_query() {
+        let query = drop_shard_query(42);
+
+        assert_eq!(query.build().unwrap(), "DROP SHARD 42");
+        assert!(query.skips_database());
+    }
+}
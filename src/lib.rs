@@ -10,11 +10,11 @@
 //!
 //!  * Reading and Writing to InfluxDB
 //!  * Optional Serde Support for Deserialization
+//!  * Query Builder for `SELECT` statements
 //!
 //! # Planned Features
 //!
 //!  * Running multiple queries in one request (e.g. `SELECT * FROM weather_berlin; SELECT * FROM weather_london`)
-//!  * Read Query Builder instead of supplying raw queries
 //!  * Authentication against InfluxDB
 //!  * Methods for setting time and time precision in a query
 //!
@@ -72,6 +72,8 @@
 
 #[macro_use]
 extern crate failure;
+#[macro_use]
+extern crate log;
 
 pub mod client;
 pub mod error;
@@ -79,6 +81,16 @@ pub mod query;
 
 #[cfg(feature = "use-serde")]
 pub mod integrations {
+    #[cfg(feature = "use-serde")]
+    pub mod conditional_write;
+    #[cfg(feature = "use-serde")]
+    pub(crate) mod csv;
+    #[cfg(feature = "use-serde")]
+    pub mod diagnostics;
+    #[cfg(feature = "use-serde")]
+    pub mod schema;
     #[cfg(feature = "use-serde")]
     pub mod serde_integration;
+    #[cfg(feature = "use-serde")]
+    pub mod shards;
 }
@@ -0,0 +1,241 @@
+//! Create Database Query Builder returned by InfluxDbQuery::create_database
+//!
+//! Can only be instantiated by using InfluxDbQuery::create_database
+
+use crate::error::InfluxDbError;
+use crate::query::retention_policy::{validate_retention_policy, RetentionDuration};
+use crate::query::{quote_identifier, InfluxDbQuery, QueryType, ValidQuery};
+
+/// Internal Representation of a `CREATE DATABASE` statement that has not yet been built
+///
+/// Setting any of the options adds a `WITH` clause, which creates the default retention policy
+/// of the database along with it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InfluxDbCreateDatabaseQuery {
+    name: String,
+    duration: Option<RetentionDuration>,
+    replication: Option<usize>,
+    shard_duration: Option<RetentionDuration>,
+    retention_policy: Option<String>,
+    if_not_exists: bool,
+}
+
+impl InfluxDbCreateDatabaseQuery {
+    /// Creates a new [`InfluxDbCreateDatabaseQuery`](crate::query::create_database::InfluxDbCreateDatabaseQuery)
+    pub fn new<S>(name: S) -> Self
+    where
+        S: ToString,
+    {
+        InfluxDbCreateDatabaseQuery {
+            name: name.to_string(),
+            duration: None,
+            replication: None,
+            shard_duration: None,
+            retention_policy: None,
+            if_not_exists: false,
+        }
+    }
+
+    /// Sets how long the default retention policy keeps data
+    pub fn duration<D>(mut self, duration: D) -> Self
+    where
+        D: Into<RetentionDuration>,
+    {
+        self.duration = Some(duration.into());
+        self
+    }
+
+    /// Sets how many copies of the data the default retention policy stores in the cluster
+    pub fn replication(mut self, replication: usize) -> Self {
+        self.replication = Some(replication);
+        self
+    }
+
+    /// Sets the time range covered by a shard group of the default retention policy
+    pub fn shard_duration<D>(mut self, shard_duration: D) -> Self
+    where
+        D: Into<RetentionDuration>,
+    {
+        self.shard_duration = Some(shard_duration.into());
+        self
+    }
+
+    /// Sets the name of the default retention policy, which InfluxDB names `autogen` otherwise
+    pub fn retention_policy<S>(mut self, name: S) -> Self
+    where
+        S: ToString,
+    {
+        self.retention_policy = Some(name.to_string());
+        self
+    }
+
+    /// Adds `IF NOT EXISTS`, so creating a database which already exists does not fail.
+    ///
+    /// Only InfluxDB versions before 1.0 accept the clause. Later versions reject it, but create databases idempotently
+    /// anyway, failing only if the database exists with a different default retention policy. The
+    /// [`InfluxDbClient`](crate::client::InfluxDbClient) therefore leaves the clause out, unless its
+    /// [server version](crate::client::InfluxDbClient::with_server_version) is older than 1.0.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// let query = InfluxDbQuery::create_database("weather").if_not_exists();
+    ///
+    /// assert_eq!(query.build().unwrap(), "CREATE DATABASE IF NOT EXISTS \"weather\"");
+    /// ```
+    pub fn if_not_exists(mut self) -> Self {
+        self.if_not_exists = true;
+        self
+    }
+
+    /// Returns the statement without `IF NOT EXISTS`, for servers which do not accept the clause
+    pub(crate) fn without_if_not_exists(&self) -> Option<Self> {
+        if self.if_not_exists {
+            Some(InfluxDbCreateDatabaseQuery {
+                if_not_exists: false,
+                ..self.clone()
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Rejects option combinations InfluxDB does not accept
+    fn validate(&self) -> Result<(), InfluxDbError> {
+        if let Some(name) = &self.retention_policy {
+            if name.is_empty() {
+                return Err(InfluxDbError::InvalidQueryError {
+                    error: "retention policy name cannot be empty".to_string(),
+                });
+            }
+        }
+        validate_retention_policy(self.duration, self.shard_duration, self.replication)
+    }
+}
+
+impl InfluxDbQuery for InfluxDbCreateDatabaseQuery {
+    fn build(&self) -> Result<ValidQuery, InfluxDbError> {
+        if self.name.is_empty() {
+            return Err(InfluxDbError::InvalidQueryError {
+                error: "database name cannot be empty".to_string(),
+            });
+        }
+        self.validate()?;
+
+        let mut options = vec![];
+        if let Some(duration) = self.duration {
+            options.push(format!("DURATION {}", duration));
+        }
+        if let Some(replication) = self.replication {
+            options.push(format!("REPLICATION {}", replication));
+        }
+        if let Some(shard_duration) = self.shard_duration {
+            options.push(format!("SHARD DURATION {}", shard_duration));
+        }
+        if let Some(name) = &self.retention_policy {
+            options.push(format!("NAME {}", quote_identifier(name)));
+        }
+
+        let mut query = String::from("CREATE DATABASE ");
+        if self.if_not_exists {
+            query.push_str("IF NOT EXISTS ");
+        }
+        query.push_str(&quote_identifier(&self.name));
+        if !options.is_empty() {
+            query.push_str(&format!(" WITH {}", options.join(" ")));
+        }
+        Ok(ValidQuery(query))
+    }
+
+    fn get_type(&self) -> QueryType {
+        QueryType::ReadQuery
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::query::retention_policy::RetentionDuration;
+    use crate::query::InfluxDbQuery;
+    use std::time::Duration;
+
+    #[test]
+    fn test_create_database() {
+        let query = InfluxDbQuery::create_database("weather").build();
+
+        assert_eq!(query.unwrap(), "CREATE DATABASE \"weather\"");
+    }
+
+    #[test]
+    fn test_create_database_with_retention_policy() {
+        let query = InfluxDbQuery::create_database("weather")
+            .duration(Duration::from_secs(52 * 7 * 24 * 60 * 60))
+            .replication(2)
+            .shard_duration(Duration::from_secs(7 * 24 * 60 * 60))
+            .retention_policy("one_year")
+            .build();
+
+        assert_eq!(
+            query.unwrap(),
+            "CREATE DATABASE \"weather\" WITH DURATION 52w REPLICATION 2 SHARD DURATION 1w NAME \"one_year\""
+        );
+
+        let query = InfluxDbQuery::create_database("weather")
+            .duration(RetentionDuration::Infinite)
+            .build();
+        assert_eq!(
+            query.unwrap(),
+            "CREATE DATABASE \"weather\" WITH DURATION INF"
+        );
+    }
+
+    #[test]
+    fn test_create_database_if_not_exists() {
+        let query = InfluxDbQuery::create_database("weather")
+            .duration(Duration::from_secs(7 * 24 * 60 * 60))
+            .if_not_exists();
+        assert_eq!(
+            query.build().unwrap(),
+            "CREATE DATABASE IF NOT EXISTS \"weather\" WITH DURATION 1w"
+        );
+        assert_eq!(
+            query.without_if_not_exists().unwrap().build().unwrap(),
+            "CREATE DATABASE \"weather\" WITH DURATION 1w"
+        );
+
+        let query = InfluxDbQuery::create_database("weather");
+        assert!(!query.build().unwrap().get().contains("IF NOT EXISTS"));
+        assert!(query.without_if_not_exists().is_none());
+    }
+
+    #[test]
+    fn test_create_database_invalid() {
+        assert!(InfluxDbQuery::create_database("").build().is_err());
+        assert!(InfluxDbQuery::create_database("weather")
+            .duration(Duration::from_secs(0))
+            .build()
+            .is_err());
+        assert!(InfluxDbQuery::create_database("weather")
+            .shard_duration(RetentionDuration::Infinite)
+            .build()
+            .is_err());
+        assert!(InfluxDbQuery::create_database("weather")
+            .replication(0)
+            .build()
+            .is_err());
+        assert!(InfluxDbQuery::create_database("weather")
+            .retention_policy("")
+            .build()
+            .is_err());
+
+        let shorter_than_shards = InfluxDbQuery::create_database("weather")
+            .duration(Duration::from_secs(60 * 60))
+            .shard_duration(Duration::from_secs(24 * 60 * 60))
+            .build();
+        assert!(
+            shorter_than_shards.is_err(),
+            "Duration shorter than the shard duration was accepted"
+        );
+    }
+}
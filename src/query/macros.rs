@@ -0,0 +1,96 @@
+//! Macros building queries from string literals, checked at compile time
+
+/// Builds a [`InfluxDbSelectQuery`](crate::query::select_query::InfluxDbSelectQuery) from InfluxQL-like syntax.
+///
+/// The measurement, fields and `GROUP BY` tags need to be string literals, which are quoted as identifiers.
+/// Passing variables or other literals, like numbers, fails to compile, so identifiers built at runtime
+/// can not end up in the query unquoted. The `WHERE` condition is a string literal as well and is used verbatim.
+/// The result is a regular select builder, so it can be extended further, e.g. with a `LIMIT`.
+///
+/// Only the syntax is checked, not whether the measurement or fields exist.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::select_query;
+///
+/// let query = select_query!(SELECT "temperature", "wind speed" FROM "weather" WHERE "time > now() - 1h" GROUP BY "location");
+/// assert_eq!(
+///     query.to_string(),
+///     "SELECT \"temperature\",\"wind speed\" FROM \"weather\" WHERE time > now() - 1h GROUP BY \"location\""
+/// );
+///
+/// let query = select_query!(SELECT * FROM "weather").limit(10);
+/// assert_eq!(query.to_string(), "SELECT * FROM \"weather\" LIMIT 10");
+/// ```
+///
+/// Identifiers which are not string literals are rejected:
+///
+/// ```compile_fail
+/// use influxdb::select_query;
+///
+/// let measurement = "weather";
+/// let _query = select_query!(SELECT * FROM measurement);
+/// ```
+///
+/// ```compile_fail
+/// use influxdb::select_query;
+///
+/// let _query = select_query!(SELECT 1 FROM "weather");
+/// ```
+#[macro_export]
+macro_rules! select_query {
+    (SELECT * FROM $measurement:literal $(WHERE $condition:literal)? $(GROUP BY $($tag:literal),+)?) => {
+        $crate::query::select_query::InfluxDbSelectQuery::new($crate::__str_literal!($measurement))
+            $(.where_clause($crate::__str_literal!($condition)))?
+            $($(.group_by($crate::__str_literal!($tag)))+)?
+    };
+    (SELECT $($field:literal),+ FROM $measurement:literal $(WHERE $condition:literal)? $(GROUP BY $($tag:literal),+)?) => {
+        $crate::query::select_query::InfluxDbSelectQuery::new($crate::__str_literal!($measurement))
+            $(.add_field($crate::__str_literal!($field)))+
+            $(.where_clause($crate::__str_literal!($condition)))?
+            $($(.group_by($crate::__str_literal!($tag)))+)?
+    };
+}
+
+/// Fails to compile unless the literal is a string literal
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __str_literal {
+    ($literal:literal) => {{
+        let literal: &'static str = $literal;
+        literal
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::query::InfluxDbQuery;
+
+    #[test]
+    fn test_select_query_macro_quotes_identifiers() {
+        let query = select_query!(SELECT "wind speed" FROM "weather \"berlin\"");
+
+        assert_eq!(
+            query.to_string(),
+            "SELECT \"wind speed\" FROM \"weather \\\"berlin\\\"\""
+        );
+    }
+
+    #[test]
+    fn test_select_query_macro_matches_builder() {
+        let query = select_query!(SELECT "temperature", "humidity" FROM "weather" WHERE "time > now() - 1h" GROUP BY "location", "station");
+        let built = InfluxDbQuery::select_query("weather")
+            .add_field("temperature")
+            .add_field("humidity")
+            .where_clause("time > now() - 1h")
+            .group_by("location")
+            .group_by("station");
+
+        assert_eq!(query, built);
+        assert_eq!(
+            select_query!(SELECT * FROM "weather"),
+            InfluxDbQuery::select_query("weather")
+        );
+    }
+}
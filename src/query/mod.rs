@@ -19,16 +19,27 @@
 //! assert!(read_query.is_ok());
 //! ```
 
+pub mod create_database;
+mod macros;
 pub mod read_query;
+pub mod retention_policy;
+pub mod select_query;
 pub mod write_query;
 
+use std::convert::TryFrom;
 use std::fmt;
+use std::mem;
+use std::str::FromStr;
+use std::time::Duration;
 
 use crate::error::InfluxDbError;
+use crate::query::create_database::InfluxDbCreateDatabaseQuery;
 use crate::query::read_query::InfluxDbReadQuery;
+use crate::query::retention_policy::{InfluxDbRetentionPolicyQuery, RetentionDuration};
+use crate::query::select_query::InfluxDbSelectQuery;
 use crate::query::write_query::InfluxDbWriteQuery;
 
-#[derive(PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Timestamp {
     NOW,
     NANOSECONDS(usize),
@@ -50,6 +61,127 @@ impl fmt::Display for Timestamp {
     }
 }
 
+impl Timestamp {
+    /// Creates a timestamp in `precision` from a duration since the Unix epoch, e.g. computed using
+    /// `SystemTime::duration_since(UNIX_EPOCH)`. Parts of the duration finer than `precision` are truncated.
+    ///
+    /// Fails with an `InvalidQueryError` if the duration exceeds the range of InfluxDB timestamps,
+    /// which are stored as `i64` nanoseconds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::{Precision, Timestamp};
+    /// use std::time::Duration;
+    ///
+    /// let timestamp = Timestamp::from_duration(Duration::from_millis(1_568_106_310_123), Precision::SECONDS);
+    /// assert_eq!(timestamp.unwrap(), Timestamp::SECONDS(1_568_106_310));
+    /// ```
+    pub fn from_duration(duration: Duration, precision: Precision) -> Result<Self, InfluxDbError> {
+        let nanos = duration.as_nanos();
+        if nanos > i64::MAX as u128 {
+            return Err(InfluxDbError::InvalidQueryError {
+                error: format!(
+                    "{:?} since the epoch exceeds the range of InfluxDB timestamps",
+                    duration
+                ),
+            });
+        }
+
+        use Precision::*;
+        let units = (nanos / precision.nanos_per_unit() as u128) as usize;
+        Ok(match precision {
+            NANOSECONDS => Timestamp::NANOSECONDS(units),
+            MICROSECONDS => Timestamp::MICROSECONDS(units),
+            MILLISECONDS => Timestamp::MILLISECONDS(units),
+            SECONDS => Timestamp::SECONDS(units),
+            MINUTES => Timestamp::MINUTES(units),
+            HOURS => Timestamp::HOURS(units),
+        })
+    }
+}
+
+/// Interprets the duration as nanoseconds since the Unix epoch, see [`Timestamp::from_duration`](crate::query::Timestamp::from_duration)
+impl TryFrom<Duration> for Timestamp {
+    type Error = InfluxDbError;
+
+    fn try_from(duration: Duration) -> Result<Self, Self::Error> {
+        Timestamp::from_duration(duration, Precision::NANOSECONDS)
+    }
+}
+
+/// Precision of a timestamp, as understood by the `precision` and `epoch` parameters of InfluxDB
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Precision {
+    NANOSECONDS,
+    MICROSECONDS,
+    MILLISECONDS,
+    SECONDS,
+    MINUTES,
+    HOURS,
+}
+
+impl Precision {
+    /// Returns how many nanoseconds a single unit of this precision spans
+    pub fn nanos_per_unit(self) -> i64 {
+        use Precision::*;
+        match self {
+            NANOSECONDS => 1,
+            MICROSECONDS => 1_000,
+            MILLISECONDS => 1_000_000,
+            SECONDS => 1_000_000_000,
+            MINUTES => 60 * 1_000_000_000,
+            HOURS => 60 * 60 * 1_000_000_000,
+        }
+    }
+}
+
+impl fmt::Display for Precision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Precision::*;
+        let modifier = match self {
+            NANOSECONDS => "ns",
+            MICROSECONDS => "u",
+            MILLISECONDS => "ms",
+            SECONDS => "s",
+            MINUTES => "m",
+            HOURS => "h",
+        };
+        write!(f, "{}", modifier)
+    }
+}
+
+/// Parses a precision as written by [`Display`](std::fmt::Display), e.g. from configuration. Microseconds are
+/// also accepted as `us` and `µs`, though InfluxDB only understands `u`, which is what is sent.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::query::Precision;
+///
+/// assert_eq!("ms".parse::<Precision>().unwrap(), Precision::MILLISECONDS);
+/// assert_eq!("µs".parse::<Precision>().unwrap().to_string(), "u");
+/// ```
+impl FromStr for Precision {
+    type Err = InfluxDbError;
+
+    fn from_str(precision: &str) -> Result<Self, Self::Err> {
+        use Precision::*;
+        match precision {
+            "ns" => Ok(NANOSECONDS),
+            // the micro sign and the greek letter mu look alike, so both are accepted
+            "u" | "us" | "\u{b5}s" | "\u{3bc}s" => Ok(MICROSECONDS),
+            "ms" => Ok(MILLISECONDS),
+            "s" => Ok(SECONDS),
+            "m" => Ok(MINUTES),
+            "h" => Ok(HOURS),
+            _ => Err(InfluxDbError::InvalidQueryError {
+                error: format!("invalid precision \"{}\"", precision),
+            }),
+        }
+    }
+}
+
 pub trait InfluxDbQuery {
     /// Builds valid InfluxSQL which can be run against the Database.
     /// In case no fields have been specified, it will return an error,
@@ -69,6 +201,35 @@ pub trait InfluxDbQuery {
     fn build(&self) -> Result<ValidQuery, InfluxDbError>;
 
     fn get_type(&self) -> QueryType;
+
+    /// Returns whether the query is sent to the query endpoint, which includes statements
+    /// like `CREATE RETENTION POLICY` besides reading data
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::{InfluxDbQuery, Timestamp};
+    ///
+    /// assert!(InfluxDbQuery::raw_read_query("SELECT * FROM weather").is_read());
+    /// assert!(!InfluxDbQuery::write_query(Timestamp::NOW, "weather").is_read());
+    /// ```
+    fn is_read(&self) -> bool {
+        self.get_type() == QueryType::ReadQuery
+    }
+
+    /// Returns whether the query writes points in line protocol to the write endpoint
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::{InfluxDbQuery, Timestamp};
+    ///
+    /// assert!(InfluxDbQuery::write_query(Timestamp::NOW, "weather").is_write());
+    /// assert!(!InfluxDbQuery::raw_read_query("SELECT * FROM weather").is_write());
+    /// ```
+    fn is_write(&self) -> bool {
+        self.get_type() == QueryType::WriteQuery
+    }
 }
 
 impl InfluxDbQuery {
@@ -103,6 +264,346 @@ impl InfluxDbQuery {
     {
         InfluxDbReadQuery::new(read_query)
     }
+
+    /// Returns a [`InfluxDbSelectQuery`](crate::query::select_query::InfluxDbSelectQuery) builder,
+    /// which can be converted into a [`InfluxDbReadQuery`](crate::query::read_query::InfluxDbReadQuery).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    /// use influxdb::query::read_query::InfluxDbReadQuery;
+    ///
+    /// let query: InfluxDbReadQuery = InfluxDbQuery::select_query("weather")
+    ///     .add_field("temperature")
+    ///     .into();
+    /// ```
+    pub fn select_query<S>(measurement: S) -> InfluxDbSelectQuery
+    where
+        S: ToString,
+    {
+        InfluxDbSelectQuery::new(measurement)
+    }
+
+    /// Returns a [`InfluxDbRetentionPolicyQuery`](crate::query::retention_policy::InfluxDbRetentionPolicyQuery) builder
+    /// for a `CREATE RETENTION POLICY` statement.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    /// use influxdb::query::retention_policy::RetentionDuration;
+    /// use std::time::Duration;
+    ///
+    /// InfluxDbQuery::create_retention_policy("one_week", "weather", Duration::from_secs(7 * 24 * 60 * 60));
+    /// InfluxDbQuery::create_retention_policy("forever", "weather", RetentionDuration::Infinite);
+    /// ```
+    pub fn create_retention_policy<S1, S2, D>(
+        name: S1,
+        database: S2,
+        duration: D,
+    ) -> InfluxDbRetentionPolicyQuery
+    where
+        S1: ToString,
+        S2: ToString,
+        D: Into<RetentionDuration>,
+    {
+        InfluxDbRetentionPolicyQuery::new(name, database, duration)
+    }
+
+    /// Returns a [`InfluxDbCreateDatabaseQuery`](crate::query::create_database::InfluxDbCreateDatabaseQuery) builder
+    /// for a `CREATE DATABASE` statement, optionally creating the default retention policy of the database.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    /// use std::time::Duration;
+    ///
+    /// InfluxDbQuery::create_database("weather");
+    /// InfluxDbQuery::create_database("weather")
+    ///     .duration(Duration::from_secs(7 * 24 * 60 * 60))
+    ///     .retention_policy("one_week");
+    /// ```
+    pub fn create_database<S>(name: S) -> InfluxDbCreateDatabaseQuery
+    where
+        S: ToString,
+    {
+        InfluxDbCreateDatabaseQuery::new(name)
+    }
+}
+
+/// Quotes an identifier (e.g. a measurement, field or tag key) for use in InfluxQL, escaping `"`, `\\` and newlines.
+///
+/// Use this to interpolate identifiers into a [raw read query](crate::query::InfluxDbQuery::raw_read_query), so they are
+/// always read as a single identifier, whatever characters they contain.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::query::{quote_identifier, InfluxDbQuery};
+///
+/// let measurement = "weather \"berlin\"";
+/// let query = InfluxDbQuery::raw_read_query(format!("SELECT * FROM {}", quote_identifier(measurement)));
+///
+/// assert_eq!(query.build().unwrap(), r#"SELECT * FROM "weather \"berlin\"""#);
+/// ```
+pub fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", escape(identifier, '"'))
+}
+
+/// Quotes a string literal (e.g. a tag value) for use in InfluxQL, escaping `'`, `\\` and newlines.
+///
+/// Use this to interpolate values into a [raw read query](crate::query::InfluxDbQuery::raw_read_query), so they can not
+/// end the literal and inject InfluxQL. Alternatively, bind them as parameters using
+/// [`bind_param`](crate::query::read_query::InfluxDbReadQuery::bind_param).
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::query::{quote_identifier, quote_string_literal, InfluxDbQuery};
+///
+/// let location = "'; DROP DATABASE weather; --";
+/// let query = InfluxDbQuery::raw_read_query(format!(
+///     "SELECT * FROM weather WHERE {} = {}",
+///     quote_identifier("location"),
+///     quote_string_literal(location)
+/// ));
+///
+/// assert_eq!(
+///     query.build().unwrap(),
+///     r#"SELECT * FROM weather WHERE "location" = '\'; DROP DATABASE weather; --'"#
+/// );
+/// ```
+pub fn quote_string_literal(text: &str) -> String {
+    format!("'{}'", escape(text, '\''))
+}
+
+/// Escapes `\\`, newlines and the `delimiter` of a quoted identifier or string literal
+fn escape(text: &str, delimiter: char) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c if c == delimiter => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Returns whether a built read query needs to be sent using `POST`, because one of its statements
+/// changes data or schema. Only `SELECT` (without `INTO`), `SHOW` and `EXPLAIN` statements are sent using `GET`.
+pub(crate) fn requires_post(query: &str) -> bool {
+    statements(query)
+        .iter()
+        .any(|keywords| match keywords.first().map(String::as_str) {
+            Some("SELECT") => keywords.iter().any(|keyword| keyword == "INTO"),
+            Some("SHOW") | Some("EXPLAIN") | None => false,
+            Some(_) => true,
+        })
+}
+
+/// Returns whether `query` is a single `SELECT` statement (without `INTO`), which InfluxDB can plan
+/// using `EXPLAIN` without running it
+pub(crate) fn explainable(query: &str) -> bool {
+    let statements: Vec<_> = statements(query)
+        .into_iter()
+        .filter(|keywords| !keywords.is_empty())
+        .collect();
+    match statements.as_slice() {
+        [keywords] => keywords[0] == "SELECT" && !keywords.iter().any(|keyword| keyword == "INTO"),
+        _ => false,
+    }
+}
+
+/// Checks `query` for gross syntax errors InfluxDB would reject it for: unterminated quoted identifiers,
+/// strings or regular expressions, and unbalanced parentheses. This is no full InfluxQL parser, so a query
+/// passing the check can still be rejected by InfluxDB.
+pub(crate) fn check_syntax(query: &str) -> Result<(), InfluxDbError> {
+    let invalid = |error: String| Err(InfluxDbError::InvalidQueryError { error });
+    let mut chars = query.char_indices().peekable();
+    // position and delimiter of the open quoted identifier, string or regular expression
+    let mut quote: Option<(usize, char)> = None;
+    let mut escaped = false;
+    let mut parentheses = vec![];
+    let mut previous = ' ';
+
+    while let Some((position, c)) = chars.next() {
+        if let Some((_, q)) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '-' if chars.peek().map(|&(_, next)| next) == Some('-') => {
+                // line comment
+                for (_, c) in &mut chars {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek().map(|&(_, next)| next) == Some('*') => {
+                chars.next();
+                let mut closed = false;
+                let mut star = false;
+                for (_, c) in &mut chars {
+                    if star && c == '/' {
+                        closed = true;
+                        break;
+                    }
+                    star = c == '*';
+                }
+                if !closed {
+                    return invalid(format!("unterminated comment at position {}", position));
+                }
+            }
+            '\'' | '"' => quote = Some((position, c)),
+            // regular expressions follow the `=~` and `!~` operators
+            '/' if previous == '~' => quote = Some((position, c)),
+            '(' => parentheses.push(position),
+            ')' if parentheses.pop().is_none() => {
+                return invalid(format!("unbalanced ')' at position {}", position));
+            }
+            _ => {}
+        }
+        if !c.is_whitespace() {
+            previous = c;
+        }
+    }
+
+    if let Some((position, q)) = quote {
+        let what = match q {
+            '\'' => "string",
+            '"' => "identifier",
+            _ => "regular expression",
+        };
+        return invalid(format!("unterminated {} at position {}", what, position));
+    }
+    if let Some(position) = parentheses.pop() {
+        return invalid(format!("unbalanced '(' at position {}", position));
+    }
+    Ok(())
+}
+
+/// Returns the first `SELECT` statement of `query` which neither compares `time` in its `WHERE` clause nor
+/// uses a `LIMIT`, and thus might scan the whole measurement. `EXPLAIN ANALYZE` runs the statement it explains,
+/// so its statement is checked as well.
+pub(crate) fn unbounded_select(query: &str) -> Option<&str> {
+    split_statements(query)
+        .into_iter()
+        .find(|(_, tokens)| {
+            let tokens = match tokens.as_slice() {
+                [explain, analyze, statement @ ..]
+                    if explain == "EXPLAIN" && analyze == "ANALYZE" =>
+                {
+                    statement
+                }
+                statement => statement,
+            };
+            if tokens.first().map(String::as_str) != Some("SELECT") {
+                return false;
+            }
+            let condition = match tokens.iter().position(|token| token == "WHERE") {
+                Some(position) => {
+                    let condition = &tokens[position + 1..];
+                    let end = condition
+                        .iter()
+                        .position(|token| CLAUSES_AFTER_WHERE.contains(&token.as_str()))
+                        .unwrap_or(condition.len());
+                    &condition[..end]
+                }
+                None => &[],
+            };
+            let time_bound = condition.windows(2).any(|pair| {
+                (pair[0] == "TIME" && COMPARISONS.contains(&pair[1].as_str()))
+                    || (COMPARISONS.contains(&pair[0].as_str()) && pair[1] == "TIME")
+            });
+            !time_bound && !tokens.iter().any(|token| token == "LIMIT")
+        })
+        .map(|(statement, _)| statement)
+}
+
+/// Keywords of the clauses which can follow the `WHERE` clause of a `SELECT` statement
+const CLAUSES_AFTER_WHERE: &[&str] = &["GROUP", "ORDER", "LIMIT", "OFFSET", "SLIMIT", "SOFFSET"];
+
+/// Comparison operators of InfluxQL, except the regular expression operators
+const COMPARISONS: &[&str] = &["=", "!=", "<>", "<", "<=", ">", ">="];
+
+/// Splits `query` into its statements, each given as its upper cased words and comparison operators
+/// outside of quotes and regular expressions
+fn statements(query: &str) -> Vec<Vec<String>> {
+    split_statements(query)
+        .into_iter()
+        .map(|(_, tokens)| tokens)
+        .collect()
+}
+
+/// Splits `query` into its statements like [`statements`], keeping the trimmed text of each statement
+fn split_statements(query: &str) -> Vec<(&str, Vec<String>)> {
+    // words and operators are tokens, consecutive characters of the same kind belong to the same token
+    fn kind(c: char) -> Option<bool> {
+        if c.is_alphanumeric() || c == '_' {
+            Some(true)
+        } else if "<>=!~".contains(c) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    let mut statements = vec![];
+    let mut tokens = vec![];
+    let mut token = String::new();
+    let mut start = 0;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut previous = ' ';
+
+    for (position, c) in query.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        if !token.is_empty() && token.chars().next().and_then(kind) != kind(c) {
+            tokens.push(mem::take(&mut token));
+        }
+        match c {
+            '\'' | '"' => quote = Some(c),
+            // regular expressions follow the `=~` and `!~` operators
+            '/' if previous == '~' => quote = Some(c),
+            ';' => {
+                statements.push((query[start..position].trim(), mem::take(&mut tokens)));
+                start = position + 1;
+            }
+            c if kind(c).is_some() => token.extend(c.to_uppercase()),
+            _ => {}
+        }
+        if !c.is_whitespace() {
+            previous = c;
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    statements.push((query[start..].trim(), tokens));
+    statements
 }
 
 #[derive(Debug)]
@@ -132,6 +633,30 @@ impl PartialEq<&str> for ValidQuery {
     }
 }
 
+/// HTTP method a read query is sent with, see [`InfluxDbReadQuery::method`](crate::query::read_query::InfluxDbReadQuery::method)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Method {
+    Get,
+    Post,
+}
+
+/// Direction of the `ORDER BY time` clause, see
+/// [`InfluxDbSelectQuery::order_by_time`](crate::query::select_query::InfluxDbSelectQuery::order_by_time)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+impl fmt::Display for Order {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Order::Asc => write!(f, "ASC"),
+            Order::Desc => write!(f, "DESC"),
+        }
+    }
+}
+
 /// Internal Enum used to decide if a `POST` or `GET` request should be sent to InfluxDB. See [InfluxDB Docs](https://docs.influxdata.com/influxdb/v1.7/tools/api/#query-http-endpoint).
 #[derive(PartialEq, Debug)]
 pub enum QueryType {
@@ -141,7 +666,29 @@ pub enum QueryType {
 
 #[cfg(test)]
 mod tests {
-    use crate::query::{Timestamp, ValidQuery};
+    use crate::query::retention_policy::{InfluxDbRetentionPolicyQuery, RetentionDuration};
+    use crate::query::{
+        check_syntax, explainable, quote_identifier, quote_string_literal, requires_post,
+        InfluxDbQuery, Precision, Timestamp, ValidQuery,
+    };
+    use std::convert::TryFrom;
+    use std::time::Duration;
+
+    #[test]
+    fn test_query_classification() {
+        let read_query = InfluxDbQuery::raw_read_query("SELECT * FROM weather");
+        assert!(read_query.is_read());
+        assert!(!read_query.is_write());
+
+        let write_query =
+            InfluxDbQuery::write_query(Timestamp::NOW, "weather").add_field("temperature", 82);
+        assert!(write_query.is_write());
+        assert!(!write_query.is_read());
+
+        let retention_policy =
+            InfluxDbRetentionPolicyQuery::new("one_week", "weather", RetentionDuration::Infinite);
+        assert!(retention_policy.is_read());
+    }
 
     #[test]
     fn test_equality_str() {
@@ -165,4 +712,209 @@ mod tests {
     fn test_format_for_timestamp_else() {
         assert!(format!("{}", Timestamp::NANOSECONDS(100)) == String::from("100"));
     }
+
+    #[test]
+    fn test_timestamp_from_duration() {
+        let duration = Duration::new(1_568_106_310, 123_456_789);
+        let timestamp = Timestamp::try_from(duration).unwrap();
+        assert_eq!(timestamp, Timestamp::NANOSECONDS(1_568_106_310_123_456_789));
+        assert_eq!(
+            InfluxDbQuery::write_query(timestamp, "weather")
+                .add_field("temperature", 82)
+                .build()
+                .unwrap(),
+            "weather temperature=82i 1568106310123456789"
+        );
+
+        assert_eq!(
+            Timestamp::from_duration(duration, Precision::MILLISECONDS).unwrap(),
+            Timestamp::MILLISECONDS(1_568_106_310_123)
+        );
+        assert_eq!(
+            Timestamp::from_duration(duration, Precision::HOURS).unwrap(),
+            Timestamp::HOURS(435_585)
+        );
+    }
+
+    #[test]
+    fn test_timestamp_from_duration_out_of_range() {
+        let max = Duration::from_nanos(i64::MAX as u64);
+        assert!(Timestamp::try_from(max).is_ok());
+        assert!(Timestamp::try_from(max + Duration::from_nanos(1)).is_err());
+        assert!(Timestamp::from_duration(Duration::from_secs(u64::MAX), Precision::HOURS).is_err());
+    }
+
+    #[test]
+    fn test_requires_post_reads() {
+        assert!(!requires_post("SELECT * FROM weather"));
+        assert!(!requires_post("select * from weather"));
+        assert!(!requires_post("SHOW DATABASES"));
+        assert!(!requires_post("EXPLAIN SELECT * FROM weather"));
+        assert!(!requires_post(
+            "SELECT max(mean) FROM (SELECT mean(temperature) FROM weather GROUP BY time(1h))"
+        ));
+        assert!(!requires_post("SELECT * FROM weather;SHOW MEASUREMENTS"));
+        assert!(!requires_post(
+            "SELECT * FROM weather WHERE location = 'DROP INTO'"
+        ));
+        assert!(!requires_post(
+            "SELECT * FROM weather WHERE location =~ /into; drop/"
+        ));
+    }
+
+    #[test]
+    fn test_requires_post_writes() {
+        assert!(requires_post("CREATE DATABASE weather"));
+        assert!(requires_post("DROP MEASUREMENT \"SELECT\""));
+        assert!(requires_post(
+            "ALTER RETENTION POLICY \"one_week\" ON \"weather\" DURATION 2w"
+        ));
+        assert!(requires_post(
+            "SELECT mean(temperature) INTO weather_hourly FROM weather GROUP BY time(1h)"
+        ));
+        assert!(requires_post("SELECT * FROM weather;DROP DATABASE weather"));
+        assert!(requires_post(
+            "CREATE CONTINUOUS QUERY cq ON weather BEGIN SELECT mean(temperature) INTO hourly FROM weather GROUP BY time(1h) END"
+        ));
+    }
+
+    #[test]
+    fn test_check_syntax() {
+        assert!(check_syntax("SELECT * FROM weather").is_ok());
+        assert!(check_syntax(
+            "SELECT mean(\"temp (°C)\") FROM weather WHERE location = 'it\\'s (here' GROUP BY time(1h)"
+        )
+        .is_ok());
+        assert!(check_syntax("SELECT * FROM weather WHERE location =~ /^us-(mid|east/").is_ok());
+        assert!(check_syntax("SELECT * FROM weather -- it's all\nLIMIT 1").is_ok());
+        assert!(check_syntax("SELECT /* it's ( */ * FROM weather").is_ok());
+    }
+
+    #[test]
+    fn test_check_syntax_errors() {
+        let error = |query: &str| match check_syntax(query) {
+            Err(crate::error::InfluxDbError::InvalidQueryError { error }) => error,
+            other => panic!("Unexpected result {:?} for {}", other, query),
+        };
+
+        assert_eq!(
+            error("DROP SERIES FROM weather WHERE location = 'us-midwest"),
+            "unterminated string at position 42"
+        );
+        assert_eq!(
+            error("SELECT * FROM \"weather"),
+            "unterminated identifier at position 14"
+        );
+        assert_eq!(
+            error("SELECT * FROM weather WHERE location =~ /us-midwest"),
+            "unterminated regular expression at position 40"
+        );
+        assert_eq!(
+            error("SELECT mean(temperature FROM weather"),
+            "unbalanced '(' at position 11"
+        );
+        assert_eq!(
+            error("SELECT mean(temperature)) FROM weather"),
+            "unbalanced ')' at position 24"
+        );
+        assert_eq!(
+            error("SELECT * FROM weather /* LIMIT 1"),
+            "unterminated comment at position 22"
+        );
+    }
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(quote_identifier("weather"), r#""weather""#);
+        assert_eq!(quote_identifier("wind speed"), r#""wind speed""#);
+        assert_eq!(quote_identifier(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(quote_identifier(r"C:\data"), r#""C:\\data""#);
+        assert_eq!(quote_identifier("two\nlines"), r#""two\nlines""#);
+        assert_eq!(quote_identifier("it's"), r#""it's""#);
+        assert_eq!(quote_identifier("température"), r#""température""#);
+        // a trailing backslash does not escape the closing quote
+        assert_eq!(quote_identifier(r"weather\"), r#""weather\\""#);
+    }
+
+    #[test]
+    fn test_quote_string_literal() {
+        assert_eq!(quote_string_literal("us-midwest"), "'us-midwest'");
+        assert_eq!(quote_string_literal("it's"), r"'it\'s'");
+        assert_eq!(quote_string_literal(r#"say "hi""#), r#"'say "hi"'"#);
+        assert_eq!(quote_string_literal(r"\'"), r"'\\\''");
+        assert_eq!(quote_string_literal("two\nlines"), r"'two\nlines'");
+        assert_eq!(quote_string_literal(""), "''");
+    }
+
+    #[test]
+    fn test_quoted_values_keep_queries_intact() {
+        for value in &[
+            "'; DROP DATABASE weather; --",
+            r#"" OR 1=1 --"#,
+            r"trailing\",
+            "/* comment",
+            "(unbalanced",
+            "multi\nline",
+        ] {
+            let query = format!(
+                "SELECT {} FROM weather WHERE location = {}",
+                quote_identifier(value),
+                quote_string_literal(value)
+            );
+            assert!(check_syntax(&query).is_ok(), "{} broke {}", value, query);
+            assert!(!requires_post(&query), "{} injected a statement", value);
+        }
+    }
+
+    #[test]
+    fn test_explainable() {
+        assert!(explainable("SELECT * FROM weather"));
+        assert!(explainable("SELECT * FROM weather;"));
+        assert!(!explainable("SELECT * FROM weather;SELECT * FROM humidity"));
+        assert!(!explainable("SHOW DATABASES"));
+        assert!(!explainable(
+            "SELECT mean(temperature) INTO weather_hourly FROM weather GROUP BY time(1h)"
+        ));
+        assert!(!explainable("DROP SERIES FROM weather"));
+        assert!(!explainable(""));
+    }
+
+    #[test]
+    fn test_parse_precision() {
+        assert_eq!("ns".parse::<Precision>().unwrap(), Precision::NANOSECONDS);
+        for micros in &["u", "us", "\u{b5}s", "\u{3bc}s"] {
+            let precision = micros.parse::<Precision>().unwrap();
+            assert_eq!(precision, Precision::MICROSECONDS, "{}", micros);
+            assert_eq!(precision.to_string(), "u");
+        }
+        assert_eq!("ms".parse::<Precision>().unwrap(), Precision::MILLISECONDS);
+        assert_eq!("s".parse::<Precision>().unwrap(), Precision::SECONDS);
+        assert_eq!("m".parse::<Precision>().unwrap(), Precision::MINUTES);
+        assert_eq!("h".parse::<Precision>().unwrap(), Precision::HOURS);
+
+        for invalid in &["", "µ", "MS", "seconds"] {
+            assert!(
+                invalid.parse::<Precision>().is_err(),
+                "{} was parsed",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_precision_microseconds() {
+        let query = InfluxDbQuery::write_query(Timestamp::MICROSECONDS(1), "weather")
+            .add_field("temperature", 82);
+        assert_eq!(query.get_precision(), "u");
+    }
+
+    #[test]
+    fn test_format_for_precision() {
+        assert_eq!(format!("{}", Precision::NANOSECONDS), "ns");
+        assert_eq!(format!("{}", Precision::MICROSECONDS), "u");
+        assert_eq!(format!("{}", Precision::MILLISECONDS), "ms");
+        assert_eq!(format!("{}", Precision::SECONDS), "s");
+        assert_eq!(format!("{}", Precision::MINUTES), "m");
+        assert_eq!(format!("{}", Precision::HOURS), "h");
+    }
 }
@@ -2,11 +2,27 @@
 //!
 //! Can only be instantiated by using InfluxDbQuery::raw_read_query
 
+use std::fmt;
+
 use crate::error::InfluxDbError;
-use crate::query::{InfluxDbQuery, QueryType, ValidQuery};
+use crate::query::write_query::InfluxDbType;
+use crate::query::{
+    check_syntax, explainable, requires_post, unbounded_select, InfluxDbQuery, Method, Precision,
+    QueryType, ValidQuery,
+};
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct InfluxDbReadQuery {
     queries: Vec<String>,
+    epoch: Option<Precision>,
+    time_column: Option<String>,
+    skip_database: bool,
+    database: Option<String>,
+    node_id: Option<u64>,
+    chunk_size: Option<usize>,
+    extra_params: Vec<(String, String)>,
+    bind_params: Vec<(String, InfluxDbType)>,
+    method: Option<Method>,
 }
 
 impl InfluxDbReadQuery {
@@ -17,6 +33,15 @@ impl InfluxDbReadQuery {
     {
         InfluxDbReadQuery {
             queries: vec![query.to_string()],
+            epoch: None,
+            time_column: None,
+            skip_database: false,
+            database: None,
+            node_id: None,
+            chunk_size: None,
+            extra_params: vec![],
+            bind_params: vec![],
+            method: None,
         }
     }
 
@@ -28,11 +53,356 @@ impl InfluxDbReadQuery {
         self.queries.push(query.to_string());
         self
     }
+
+    /// Requests timestamps to be returned as epoch integers in the given [`Precision`](crate::query::Precision)
+    /// instead of RFC3339 strings
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::{InfluxDbQuery, Precision};
+    ///
+    /// InfluxDbQuery::raw_read_query("SELECT * FROM weather").with_epoch(Precision::MILLISECONDS);
+    /// ```
+    pub fn with_epoch(mut self, precision: Precision) -> Self {
+        self.epoch = Some(precision);
+        self
+    }
+
+    /// Returns the epoch precision timestamps will be returned in, if one was set
+    pub fn get_epoch(&self) -> Option<Precision> {
+        self.epoch
+    }
+
+    /// Names the column holding the timestamps, if it is not `time`, so
+    /// [`json_query`](crate::client::InfluxDbClient::json_query) converts the epoch integers of that column
+    /// like those of `time`, see [`with_epoch`](crate::query::read_query::InfluxDbReadQuery::with_epoch)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::{InfluxDbQuery, Precision};
+    ///
+    /// let query = InfluxDbQuery::raw_read_query("SELECT * FROM weather")
+    ///     .with_epoch(Precision::MILLISECONDS)
+    ///     .with_time_column("ts");
+    /// assert_eq!(query.get_time_column(), "ts");
+    /// ```
+    pub fn with_time_column<S>(mut self, column: S) -> Self
+    where
+        S: ToString,
+    {
+        self.time_column = Some(column.to_string());
+        self
+    }
+
+    /// Returns the name of the column holding the timestamps, `time` unless another one was set
+    pub fn get_time_column(&self) -> &str {
+        self.time_column.as_deref().unwrap_or("time")
+    }
+
+    /// Sends the [`InfluxDbReadQuery`] without the database of the client as `db` parameter.
+    ///
+    /// This is useful for management statements like `SHOW DATABASES` or `CREATE DATABASE`,
+    /// which do not run against a database and may be rejected by some servers if one is given.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// InfluxDbQuery::raw_read_query("SHOW DATABASES").without_database();
+    /// ```
+    pub fn without_database(mut self) -> Self {
+        self.skip_database = true;
+        self
+    }
+
+    /// Returns whether the `db` parameter is omitted when sending the [`InfluxDbReadQuery`]
+    pub fn skips_database(&self) -> bool {
+        self.skip_database
+    }
+
+    /// Runs the [`InfluxDbReadQuery`] against `database` instead of the database of the client
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// InfluxDbQuery::raw_read_query("SELECT * FROM weather").with_database("archive");
+    /// ```
+    pub fn with_database<S>(mut self, database: S) -> Self
+    where
+        S: ToString,
+    {
+        self.database = Some(database.to_string());
+        self
+    }
+
+    /// Returns the database overriding the database of the client, if one was set
+    pub fn get_database(&self) -> Option<&str> {
+        self.database.as_deref()
+    }
+
+    /// Directs the [`InfluxDbReadQuery`] to the node with the given id using the `node_id` parameter.
+    ///
+    /// Only InfluxDB Enterprise supports targeting nodes, which is mostly useful to diagnose replication issues.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// InfluxDbQuery::raw_read_query("SELECT * FROM weather").with_node_id(2);
+    /// ```
+    pub fn with_node_id(mut self, node_id: u64) -> Self {
+        self.node_id = Some(node_id);
+        self
+    }
+
+    /// Returns the id of the node the [`InfluxDbReadQuery`] is directed to, if one was set
+    pub fn get_node_id(&self) -> Option<u64> {
+        self.node_id
+    }
+
+    /// Makes InfluxDB return the results in chunks of at most `chunk_size` rows, using the `chunked` and
+    /// `chunk_size` parameters. Without a chunk size, InfluxDB returns the results in a single response.
+    ///
+    /// Smaller chunks reduce the latency until the first rows arrive, larger chunks improve the throughput.
+    /// The chunks of a response are merged again when deserializing the results using
+    /// [`json_query`](crate::client::InfluxDbClient::json_query).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// let query = InfluxDbQuery::raw_read_query("SELECT * FROM weather").with_chunk_size(20_000);
+    /// assert_eq!(query.get_chunk_size(), Some(20_000));
+    /// ```
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Returns the number of rows per chunk InfluxDB returns the results in, if one was set
+    pub fn get_chunk_size(&self) -> Option<usize> {
+        self.chunk_size
+    }
+
+    /// Adds a parameter to the URL of the request, for parameters of InfluxDB not supported by this crate yet.
+    ///
+    /// Parameters set by the client, like `db`, `q` or `epoch`, can not be overridden and result in an
+    /// `InvalidQueryError` when sending the query.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// InfluxDbQuery::raw_read_query("SELECT * FROM weather").with_extra_param("rp", "one_week");
+    /// ```
+    pub fn with_extra_param<S1, S2>(mut self, key: S1, value: S2) -> Self
+    where
+        S1: ToString,
+        S2: ToString,
+    {
+        self.extra_params.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Returns the extra parameters added to the URL of the request
+    pub fn get_extra_params(&self) -> &[(String, String)] {
+        &self.extra_params
+    }
+
+    /// Binds `value` to the placeholder `$name` of the statement. The values are sent as JSON object
+    /// in the `params` parameter and substituted by InfluxDB, so they never need to be escaped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// InfluxDbQuery::raw_read_query("SELECT * FROM weather WHERE location = $location AND temperature > $min")
+    ///     .bind_param("location", "us-midwest")
+    ///     .bind_param("min", 80);
+    /// ```
+    pub fn bind_param<S, I>(mut self, name: S, value: I) -> Self
+    where
+        S: ToString,
+        I: Into<InfluxDbType>,
+    {
+        let name = name.to_string();
+        self.bind_params
+            .push((name.trim_start_matches('$').to_string(), value.into()));
+        self
+    }
+
+    /// Returns the values bound to the placeholders of the statement
+    pub fn get_bind_params(&self) -> &[(String, InfluxDbType)] {
+        &self.bind_params
+    }
+
+    /// Renders the bound values as the JSON object of the `params` parameter, `None` if no value is bound
+    pub(crate) fn bind_params_json(&self) -> Result<Option<String>, InfluxDbError> {
+        if self.bind_params.is_empty() {
+            return Ok(None);
+        }
+
+        let mut members = Vec::with_capacity(self.bind_params.len());
+        for (name, value) in &self.bind_params {
+            let value = match value {
+                InfluxDbType::Float(float) if !float.is_finite() => {
+                    return Err(InfluxDbError::InvalidQueryError {
+                        error: format!("bind parameter \"{}\" is not a finite number", name),
+                    });
+                }
+                InfluxDbType::Float(float) => float.to_string(),
+                InfluxDbType::SignedInteger(integer) => integer.to_string(),
+                InfluxDbType::UnsignedInteger(integer) => integer.to_string(),
+                InfluxDbType::Boolean(boolean) => boolean.to_string(),
+                InfluxDbType::Text(text) => json_string(text),
+            };
+            members.push(format!("{}:{}", json_string(name), value));
+        }
+        Ok(Some(format!("{{{}}}", members.join(","))))
+    }
+
+    /// Sends the [`InfluxDbReadQuery`] using `method`, instead of the method derived from its statements.
+    ///
+    /// By default, queries are sent using `GET` only if all statements are `SELECT` (without `INTO`), `SHOW`
+    /// or `EXPLAIN` statements. This overrides that detection for statements it gets wrong.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::{InfluxDbQuery, Method};
+    ///
+    /// InfluxDbQuery::raw_read_query("SELECT * FROM weather").method(Method::Post);
+    /// ```
+    pub fn method(mut self, method: Method) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    /// Returns the method set using [`method`](crate::query::read_query::InfluxDbReadQuery::method), if any
+    pub fn get_method(&self) -> Option<Method> {
+        self.method
+    }
+
+    /// Returns the method to send the built `query` with, detecting it from the statements unless one was set
+    pub(crate) fn http_method(&self, query: &str) -> Method {
+        self.method.unwrap_or_else(|| {
+            if requires_post(query) {
+                Method::Post
+            } else {
+                Method::Get
+            }
+        })
+    }
+
+    /// Checks the queries for gross syntax errors without sending them, namely unterminated quoted identifiers,
+    /// strings and regular expressions, and unbalanced parentheses. Returns an `InvalidQueryError` naming the
+    /// position of the error in the [rendered](crate::query::read_query::InfluxDbReadQuery#impl-Display) queries.
+    ///
+    /// This is no full InfluxQL parser, so queries passing the check can still be rejected by InfluxDB, see
+    /// [`InfluxDbClient::validate_query`](crate::client::InfluxDbClient::validate_query) for letting InfluxDB check them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// assert!(InfluxDbQuery::raw_read_query("SELECT * FROM weather WHERE location = 'us-midwest'").validate().is_ok());
+    /// assert!(InfluxDbQuery::raw_read_query("SELECT * FROM weather WHERE location = 'us-midwest").validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), InfluxDbError> {
+        check_syntax(&self.to_string())
+    }
+
+    /// Returns whether every query is a single `SELECT` statement, so the queries can be planned using `EXPLAIN`
+    pub(crate) fn is_explainable(&self) -> bool {
+        self.queries.iter().all(|query| explainable(query))
+    }
+
+    /// Wraps every query in an `EXPLAIN` statement, which returns the query plan instead of the data
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// let query = InfluxDbQuery::raw_read_query("SELECT * FROM weather").explain();
+    /// assert_eq!(query.to_string(), "EXPLAIN SELECT * FROM weather");
+    /// ```
+    pub fn explain(self) -> Self {
+        self.prefix_queries("EXPLAIN")
+    }
+
+    /// Wraps every query in an `EXPLAIN ANALYZE` statement, which runs the query and returns the query plan
+    /// annotated with the time spent in each step
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// let query = InfluxDbQuery::raw_read_query("SELECT * FROM weather").explain_analyze();
+    /// assert_eq!(query.to_string(), "EXPLAIN ANALYZE SELECT * FROM weather");
+    /// ```
+    pub fn explain_analyze(self) -> Self {
+        self.prefix_queries("EXPLAIN ANALYZE")
+    }
+
+    fn prefix_queries(mut self, prefix: &str) -> Self {
+        self.queries = self
+            .queries
+            .into_iter()
+            .map(|query| format!("{} {}", prefix, query))
+            .collect();
+        self
+    }
+
+    /// Returns the first `SELECT` statement which neither compares `time` in its `WHERE` clause
+    /// nor uses a `LIMIT`, and thus might scan the whole measurement
+    pub(crate) fn unbounded_select(&self) -> Option<&str> {
+        self.queries
+            .iter()
+            .find_map(|query| unbounded_select(query))
+    }
+}
+
+/// Quotes and escapes `text` as JSON string
+fn json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Renders the InfluxQL of the [`InfluxDbReadQuery`](crate::query::read_query::InfluxDbReadQuery), separating multiple queries with `;`
+impl fmt::Display for InfluxDbReadQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.queries.join(";"))
+    }
 }
 
 impl InfluxDbQuery for InfluxDbReadQuery {
     fn build(&self) -> Result<ValidQuery, InfluxDbError> {
-        Ok(ValidQuery(self.queries.join(";")))
+        Ok(ValidQuery(self.to_string()))
     }
 
     fn get_type(&self) -> QueryType {
@@ -42,7 +412,60 @@ impl InfluxDbQuery for InfluxDbReadQuery {
 
 #[cfg(test)]
 mod tests {
-    use crate::query::{InfluxDbQuery, QueryType};
+    use crate::query::{InfluxDbQuery, Precision, QueryType};
+
+    #[test]
+    fn test_bind_params_json() {
+        let query = InfluxDbQuery::raw_read_query(
+            "SELECT * FROM weather WHERE location = $location AND temperature > $min",
+        )
+        .bind_param("location", "us \"mid\"west\n")
+        .bind_param("$min", 80)
+        .bind_param("rain", false)
+        .bind_param("wind", 1.5);
+
+        assert_eq!(
+            query.bind_params_json().unwrap().unwrap(),
+            r#"{"location":"us \"mid\"west\n","min":80,"rain":false,"wind":1.5}"#
+        );
+        assert_eq!(
+            InfluxDbQuery::raw_read_query("SELECT * FROM weather")
+                .bind_params_json()
+                .unwrap(),
+            None
+        );
+        assert!(
+            InfluxDbQuery::raw_read_query("SELECT * FROM weather WHERE temperature > $min")
+                .bind_param("min", f64::NAN)
+                .bind_params_json()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_explain() {
+        let query = InfluxDbQuery::raw_read_query("SELECT * FROM weather")
+            .add("SELECT * FROM humidity")
+            .explain()
+            .build();
+
+        assert_eq!(
+            query.unwrap(),
+            "EXPLAIN SELECT * FROM weather;EXPLAIN SELECT * FROM humidity"
+        );
+    }
+
+    #[test]
+    fn test_explain_analyze() {
+        let query = InfluxDbQuery::raw_read_query("SELECT mean(temperature) FROM weather")
+            .explain_analyze()
+            .build();
+
+        assert_eq!(
+            query.unwrap(),
+            "EXPLAIN ANALYZE SELECT mean(temperature) FROM weather"
+        );
+    }
 
     #[test]
     fn test_read_builder_single_query() {
@@ -60,10 +483,85 @@ mod tests {
         assert_eq!(query.unwrap(), "SELECT * FROM aachen;SELECT * FROM cologne");
     }
 
+    #[test]
+    fn test_read_builder_display() {
+        let query =
+            InfluxDbQuery::raw_read_query("SELECT * FROM aachen").add("SELECT * FROM cologne");
+
+        assert_eq!(
+            query.to_string(),
+            "SELECT * FROM aachen;SELECT * FROM cologne"
+        );
+        assert_eq!(query.build().unwrap(), query.to_string());
+    }
+
+    #[test]
+    fn test_unbounded_select() {
+        let unbounded = InfluxDbQuery::raw_read_query("SELECT * FROM aachen");
+        assert_eq!(unbounded.unbounded_select(), Some("SELECT * FROM aachen"));
+
+        let unbounded =
+            InfluxDbQuery::raw_read_query("SELECT * FROM aachen WHERE time > now() - 1h")
+                .add("select * from cologne where location = 'zoo'");
+        assert_eq!(
+            unbounded.unbounded_select(),
+            Some("select * from cologne where location = 'zoo'")
+        );
+
+        let bounded = InfluxDbQuery::raw_read_query("SELECT * FROM aachen WHERE time > now() - 1h")
+            .add("SELECT * FROM cologne LIMIT 10")
+            .add("SHOW DATABASES");
+        assert_eq!(bounded.unbounded_select(), None);
+
+        for statement in &[
+            "SELECT * FROM aachen WHERE host = 'a' GROUP BY time(1h)",
+            "SELECT * FROM aachen WHERE uptime > 5",
+            "SELECT * FROM aachen WHERE x = 1 ORDER BY time DESC",
+            "SELECT * FROM aachen WHERE host = 'time > 0'",
+            "SELECT * FROM aachen WHERE host =~ /time > 0/",
+            "SELECT * FROM aachen SLIMIT 1",
+            "SELECT * FROM aachen WHERE host = 'LIMIT 1'",
+            "EXPLAIN ANALYZE SELECT * FROM aachen",
+        ] {
+            let unbounded = InfluxDbQuery::raw_read_query(*statement);
+            assert_eq!(unbounded.unbounded_select(), Some(*statement));
+        }
+
+        let unbounded = InfluxDbQuery::raw_read_query(
+            "SELECT * FROM aachen WHERE host = 'a;b' LIMIT 1;SELECT * FROM cologne",
+        );
+        assert_eq!(unbounded.unbounded_select(), Some("SELECT * FROM cologne"));
+
+        let bounded = InfluxDbQuery::raw_read_query("SELECT * FROM aachen WHERE now() - 1h < time")
+            .add("select * from aachen where host = 'a' and time >= now() - 1h group by time(1m)")
+            .add("EXPLAIN ANALYZE SELECT * FROM aachen LIMIT 1")
+            .add("EXPLAIN SELECT * FROM aachen");
+        assert_eq!(bounded.unbounded_select(), None);
+    }
+
+    #[test]
+    fn test_read_builder_clone() {
+        let query = InfluxDbQuery::raw_read_query("SELECT * FROM aachen");
+        let cloned = query.clone();
+
+        assert_eq!(query, cloned);
+        assert_ne!(query, cloned.add("SELECT * FROM cologne"));
+    }
+
     #[test]
     fn test_correct_query_type() {
         let query = InfluxDbQuery::raw_read_query("SELECT * FROM aachen");
 
         assert_eq!(query.get_type(), QueryType::ReadQuery);
     }
+
+    #[test]
+    fn test_read_builder_epoch() {
+        let query = InfluxDbQuery::raw_read_query("SELECT * FROM aachen");
+        assert_eq!(query.get_epoch(), None);
+
+        let query = query.with_epoch(Precision::MILLISECONDS);
+        assert_eq!(query.get_epoch(), Some(Precision::MILLISECONDS));
+        assert_eq!(query.build().unwrap(), "SELECT * FROM aachen");
+    }
 }
@@ -0,0 +1,276 @@
+//! Retention Policy Query Builder returned by InfluxDbQuery::create_retention_policy
+//!
+//! Can only be instantiated by using InfluxDbQuery::create_retention_policy
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::error::InfluxDbError;
+use crate::query::{quote_identifier, InfluxDbQuery, QueryType, ValidQuery};
+
+/// Duration of a retention policy, which is either finite or kept forever
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::query::retention_policy::RetentionDuration;
+/// use std::time::Duration;
+///
+/// assert_eq!(RetentionDuration::from(Duration::from_secs(7 * 24 * 60 * 60)).to_string(), "1w");
+/// assert_eq!(RetentionDuration::from(Duration::from_secs(90 * 60)).to_string(), "90m");
+/// assert_eq!(RetentionDuration::Infinite.to_string(), "INF");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RetentionDuration {
+    Finite(Duration),
+    Infinite,
+}
+
+impl From<Duration> for RetentionDuration {
+    fn from(duration: Duration) -> Self {
+        RetentionDuration::Finite(duration)
+    }
+}
+
+/// Renders the InfluxQL duration literal, using the largest unit which represents the duration exactly
+impl fmt::Display for RetentionDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [(u128, &str); 8] = [
+            (7 * 24 * 60 * 60 * 1_000_000_000, "w"),
+            (24 * 60 * 60 * 1_000_000_000, "d"),
+            (60 * 60 * 1_000_000_000, "h"),
+            (60 * 1_000_000_000, "m"),
+            (1_000_000_000, "s"),
+            (1_000_000, "ms"),
+            (1_000, "u"),
+            (1, "ns"),
+        ];
+
+        let duration = match self {
+            RetentionDuration::Infinite => return write!(f, "INF"),
+            RetentionDuration::Finite(duration) => duration.as_nanos(),
+        };
+        if duration == 0 {
+            return write!(f, "0s");
+        }
+        let (nanos, unit) = UNITS
+            .iter()
+            .find(|(nanos, _)| duration % nanos == 0)
+            .unwrap_or(&UNITS[UNITS.len() - 1]);
+        write!(f, "{}{}", duration / nanos, unit)
+    }
+}
+
+/// Internal Representation of a `CREATE RETENTION POLICY` statement that has not yet been built
+#[derive(Clone, Debug, PartialEq)]
+pub struct InfluxDbRetentionPolicyQuery {
+    name: String,
+    database: String,
+    duration: RetentionDuration,
+    replication: usize,
+    shard_duration: Option<RetentionDuration>,
+    default: bool,
+}
+
+impl InfluxDbRetentionPolicyQuery {
+    /// Creates a new [`InfluxDbRetentionPolicyQuery`](crate::query::retention_policy::InfluxDbRetentionPolicyQuery)
+    pub fn new<S1, S2, D>(name: S1, database: S2, duration: D) -> Self
+    where
+        S1: ToString,
+        S2: ToString,
+        D: Into<RetentionDuration>,
+    {
+        InfluxDbRetentionPolicyQuery {
+            name: name.to_string(),
+            database: database.to_string(),
+            duration: duration.into(),
+            replication: 1,
+            shard_duration: None,
+            default: false,
+        }
+    }
+
+    /// Sets how many copies of the data are stored in the cluster. Defaults to `1`.
+    pub fn replication(mut self, replication: usize) -> Self {
+        self.replication = replication;
+        self
+    }
+
+    /// Sets the time range covered by a shard group. A duration of zero lets InfluxDB pick the default.
+    pub fn shard_duration<D>(mut self, shard_duration: D) -> Self
+    where
+        D: Into<RetentionDuration>,
+    {
+        self.shard_duration = Some(shard_duration.into());
+        self
+    }
+
+    /// Makes the retention policy the default retention policy of the database
+    pub fn default(mut self) -> Self {
+        self.default = true;
+        self
+    }
+}
+
+/// Rejects retention policy options InfluxDB does not accept. Options which are not set are not checked, as
+/// the `WITH` clause of [`InfluxDbCreateDatabaseQuery`](crate::query::create_database::InfluxDbCreateDatabaseQuery)
+/// leaves them out.
+pub(crate) fn validate_retention_policy(
+    duration: Option<RetentionDuration>,
+    shard_duration: Option<RetentionDuration>,
+    replication: Option<usize>,
+) -> Result<(), InfluxDbError> {
+    let invalid = |error: &str| {
+        Err(InfluxDbError::InvalidQueryError {
+            error: error.to_string(),
+        })
+    };
+
+    if duration == Some(RetentionDuration::Finite(Duration::from_secs(0))) {
+        return invalid("retention policy duration cannot be zero, use RetentionDuration::Infinite to keep data forever");
+    }
+    if shard_duration == Some(RetentionDuration::Infinite) {
+        return invalid("shard duration cannot be infinite");
+    }
+    if replication == Some(0) {
+        return invalid("replication factor must be at least 1");
+    }
+    if let (
+        Some(RetentionDuration::Finite(duration)),
+        Some(RetentionDuration::Finite(shard_duration)),
+    ) = (duration, shard_duration)
+    {
+        if duration < shard_duration {
+            return invalid("retention policy duration must be greater than the shard duration");
+        }
+    }
+    Ok(())
+}
+
+impl InfluxDbQuery for InfluxDbRetentionPolicyQuery {
+    fn build(&self) -> Result<ValidQuery, InfluxDbError> {
+        validate_retention_policy(
+            Some(self.duration),
+            self.shard_duration,
+            Some(self.replication),
+        )?;
+
+        let mut query = format!(
+            "CREATE RETENTION POLICY {name} ON {database} DURATION {duration} REPLICATION {replication}",
+            name = quote_identifier(&self.name),
+            database = quote_identifier(&self.database),
+            duration = self.duration,
+            replication = self.replication
+        );
+        if let Some(shard_duration) = self.shard_duration {
+            query.push_str(&format!(" SHARD DURATION {}", shard_duration));
+        }
+        if self.default {
+            query.push_str(" DEFAULT");
+        }
+        Ok(ValidQuery(query))
+    }
+
+    fn get_type(&self) -> QueryType {
+        QueryType::ReadQuery
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::query::retention_policy::RetentionDuration;
+    use crate::query::InfluxDbQuery;
+    use std::time::Duration;
+
+    #[test]
+    fn test_duration_literals() {
+        let literals = vec![
+            (RetentionDuration::Infinite, "INF"),
+            (Duration::from_secs(0).into(), "0s"),
+            (Duration::from_secs(52 * 7 * 24 * 60 * 60).into(), "52w"),
+            (Duration::from_secs(3 * 24 * 60 * 60).into(), "3d"),
+            (Duration::from_secs(25 * 60 * 60).into(), "25h"),
+            (Duration::from_secs(90).into(), "90s"),
+            (Duration::from_millis(1_500).into(), "1500ms"),
+            (Duration::from_micros(3).into(), "3u"),
+            (Duration::from_nanos(1_000_000_001).into(), "1000000001ns"),
+        ];
+
+        for (duration, literal) in literals {
+            assert_eq!(duration.to_string(), literal);
+        }
+    }
+
+    #[test]
+    fn test_retention_policy_builder() {
+        let query = InfluxDbQuery::create_retention_policy(
+            "one_year",
+            "weather",
+            Duration::from_secs(52 * 7 * 24 * 60 * 60),
+        )
+        .build();
+
+        assert_eq!(
+            query.unwrap(),
+            "CREATE RETENTION POLICY \"one_year\" ON \"weather\" DURATION 52w REPLICATION 1"
+        );
+    }
+
+    #[test]
+    fn test_retention_policy_builder_full() {
+        let query = InfluxDbQuery::create_retention_policy(
+            "forever",
+            "weather",
+            RetentionDuration::Infinite,
+        )
+        .replication(3)
+        .shard_duration(Duration::from_secs(24 * 60 * 60))
+        .default()
+        .build();
+
+        assert_eq!(
+            query.unwrap(),
+            "CREATE RETENTION POLICY \"forever\" ON \"weather\" DURATION INF REPLICATION 3 SHARD DURATION 1d DEFAULT"
+        );
+    }
+
+    #[test]
+    fn test_retention_policy_builder_invalid() {
+        let zero_duration =
+            InfluxDbQuery::create_retention_policy("rp", "weather", Duration::from_secs(0)).build();
+        assert!(zero_duration.is_err(), "Zero duration was accepted");
+
+        let infinite_shard_duration =
+            InfluxDbQuery::create_retention_policy("rp", "weather", RetentionDuration::Infinite)
+                .shard_duration(RetentionDuration::Infinite)
+                .build();
+        assert!(
+            infinite_shard_duration.is_err(),
+            "Infinite shard duration was accepted"
+        );
+
+        let zero_shard_duration =
+            InfluxDbQuery::create_retention_policy("rp", "weather", RetentionDuration::Infinite)
+                .shard_duration(Duration::from_secs(0))
+                .build();
+        assert!(
+            zero_shard_duration.is_ok(),
+            "Zero shard duration was rejected"
+        );
+
+        let no_replication =
+            InfluxDbQuery::create_retention_policy("rp", "weather", RetentionDuration::Infinite)
+                .replication(0)
+                .build();
+        assert!(no_replication.is_err(), "Zero replication was accepted");
+
+        let shorter_than_shard =
+            InfluxDbQuery::create_retention_policy("rp", "weather", Duration::from_secs(60 * 60))
+                .shard_duration(Duration::from_secs(24 * 60 * 60))
+                .build();
+        assert!(
+            shorter_than_shard.is_err(),
+            "Duration shorter than the shard duration was accepted"
+        );
+    }
+}
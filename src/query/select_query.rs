@@ -0,0 +1,660 @@
+//! Select Query Builder returned by InfluxDbQuery::select_query
+//!
+//! Can only be instantiated by using InfluxDbQuery::select_query
+
+use itertools::Itertools;
+use std::fmt;
+
+use std::time::Duration;
+
+use crate::query::read_query::InfluxDbReadQuery;
+use crate::query::retention_policy::RetentionDuration;
+use crate::query::{quote_identifier, quote_string_literal, Order};
+
+/// A column selected by a [`InfluxDbSelectQuery`](crate::query::select_query::InfluxDbSelectQuery),
+/// optionally wrapped in an aggregate function and renamed using an alias
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::query::select_query::Field;
+///
+/// let field = Field::mean("temperature").as_alias("avg_temp");
+///
+/// assert_eq!(field.to_string(), "MEAN(\"temperature\") AS \"avg_temp\"");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    expression: String,
+    alias: Option<String>,
+}
+
+macro_rules! aggregate_fn {
+        ( $( $name:ident => $function:expr, $doc:expr );+ ) => (
+                $(
+                    #[doc = $doc]
+                    pub fn $name<S>(field: S) -> Self
+                    where
+                        S: ToString,
+                    {
+                        Field {
+                            expression: format!("{}({})", $function, quote_column(&field.to_string())),
+                            alias: None,
+                        }
+                    }
+                )+
+        )
+}
+
+impl Field {
+    /// Creates a new [`Field`](crate::query::select_query::Field) selecting the plain column
+    pub fn new<S>(field: S) -> Self
+    where
+        S: ToString,
+    {
+        Field {
+            expression: quote_column(&field.to_string()),
+            alias: None,
+        }
+    }
+
+    aggregate_fn! {
+        mean => "MEAN", "Selects the arithmetic mean of a field";
+        sum => "SUM", "Selects the sum of a field";
+        count => "COUNT", "Selects the number of non-null values of a field";
+        min => "MIN", "Selects the lowest value of a field";
+        max => "MAX", "Selects the greatest value of a field";
+        first => "FIRST", "Selects the value of a field with the oldest timestamp";
+        last => "LAST", "Selects the value of a field with the most recent timestamp";
+        median => "MEDIAN", "Selects the middle value of a field";
+        stddev => "STDDEV", "Selects the standard deviation of a field"
+    }
+
+    /// Renames the column in the result using `AS`
+    pub fn as_alias<S>(mut self, alias: S) -> Self
+    where
+        S: ToString,
+    {
+        self.alias = Some(alias.to_string());
+        self
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.alias {
+            Some(alias) => write!(f, "{} AS {}", self.expression, quote_identifier(alias)),
+            None => write!(f, "{}", self.expression),
+        }
+    }
+}
+
+impl From<&str> for Field {
+    fn from(field: &str) -> Self {
+        Field::new(field)
+    }
+}
+
+impl From<String> for Field {
+    fn from(field: String) -> Self {
+        Field::new(field)
+    }
+}
+
+/// Quotes a column, leaving the `*` wildcard untouched
+fn quote_column(column: &str) -> String {
+    if column == "*" {
+        column.to_string()
+    } else {
+        quote_identifier(column)
+    }
+}
+
+/// Internal Representation of a `SELECT` statement that has not yet been built
+#[derive(Clone, Debug, PartialEq)]
+pub struct InfluxDbSelectQuery {
+    measurement: String,
+    fields: Vec<Field>,
+    into_measurement: Option<String>,
+    where_clause: Option<String>,
+    retention: Option<RetentionDuration>,
+    group_by: Vec<String>,
+    order: Option<Order>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    slimit: Option<usize>,
+    soffset: Option<usize>,
+    timezone: Option<String>,
+    database: Option<String>,
+}
+
+impl InfluxDbSelectQuery {
+    /// Creates a new [`InfluxDbSelectQuery`](crate::query::select_query::InfluxDbSelectQuery)
+    pub fn new<S>(measurement: S) -> Self
+    where
+        S: ToString,
+    {
+        InfluxDbSelectQuery {
+            measurement: measurement.to_string(),
+            fields: vec![],
+            into_measurement: None,
+            where_clause: None,
+            retention: None,
+            group_by: vec![],
+            order: None,
+            limit: None,
+            offset: None,
+            slimit: None,
+            soffset: None,
+            timezone: None,
+            database: None,
+        }
+    }
+
+    /// Adds a field or tag to the columns selected by the [`InfluxDbSelectQuery`](crate::query::select_query::InfluxDbSelectQuery)
+    ///
+    /// If no columns are added, all columns are selected using `SELECT *`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    /// use influxdb::query::select_query::Field;
+    ///
+    /// let query = InfluxDbQuery::select_query("weather")
+    ///     .add_field("location")
+    ///     .add_field(Field::max("temperature"));
+    ///
+    /// assert_eq!(
+    ///     query.to_string(),
+    ///     "SELECT \"location\",MAX(\"temperature\") FROM \"weather\""
+    /// );
+    /// ```
+    pub fn add_field<F>(mut self, field: F) -> Self
+    where
+        F: Into<Field>,
+    {
+        self.fields.push(field.into());
+        self
+    }
+
+    /// Writes the result into the measurement `target` using `INTO`, instead of returning it.
+    ///
+    /// Together with [`group_by_time`](crate::query::select_query::InfluxDbSelectQuery::group_by_time), this
+    /// builds statements downsampling a measurement. As such a statement writes data, it is sent using `POST`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    /// use influxdb::query::select_query::Field;
+    /// use std::time::Duration;
+    ///
+    /// let query = InfluxDbQuery::select_query("weather")
+    ///     .add_field(Field::mean("temperature").as_alias("temperature"))
+    ///     .into_measurement("weather_hourly")
+    ///     .group_by_time(Duration::from_secs(60 * 60))
+    ///     .group_by("location");
+    ///
+    /// assert_eq!(
+    ///     query.to_string(),
+    ///     "SELECT MEAN(\"temperature\") AS \"temperature\" INTO \"weather_hourly\" FROM \"weather\" GROUP BY time(1h),\"location\""
+    /// );
+    /// ```
+    pub fn into_measurement<S>(mut self, target: S) -> Self
+    where
+        S: ToString,
+    {
+        self.into_measurement = Some(target.to_string());
+        self
+    }
+
+    /// Sets the condition of the `WHERE` clause. The condition is used verbatim.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// let query = InfluxDbQuery::select_query("weather").where_clause("time > now() - 1h");
+    ///
+    /// assert_eq!(query.to_string(), "SELECT * FROM \"weather\" WHERE time > now() - 1h");
+    /// ```
+    pub fn where_clause<S>(mut self, condition: S) -> Self
+    where
+        S: ToString,
+    {
+        self.where_clause = Some(condition.to_string());
+        self
+    }
+
+    /// Clamps the lower time bound of the query to `now() - retention`, so it does not ask for data which
+    /// the retention policy already expired.
+    ///
+    /// The bound is combined with the condition of the [`WHERE` clause](crate::query::select_query::InfluxDbSelectQuery::where_clause)
+    /// using `AND`, so a lower bound of the condition within the retention window still applies. The duration needs to be
+    /// the one of the retention policy the measurement is read from; retention policies keeping data forever, which
+    /// InfluxDB reports with a duration of `0s`, add no bound.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    /// use std::time::Duration;
+    ///
+    /// let query = InfluxDbQuery::select_query("weather")
+    ///     .where_clause("time > now() - 30d OR location = 'us-midwest'")
+    ///     .within_retention(Duration::from_secs(7 * 24 * 60 * 60));
+    ///
+    /// assert_eq!(
+    ///     query.to_string(),
+    ///     "SELECT * FROM \"weather\" WHERE (time > now() - 30d OR location = 'us-midwest') AND time >= now() - 1w"
+    /// );
+    /// ```
+    pub fn within_retention<D>(mut self, retention: D) -> Self
+    where
+        D: Into<RetentionDuration>,
+    {
+        self.retention = match retention.into() {
+            RetentionDuration::Finite(duration) if duration.as_nanos() > 0 => {
+                Some(RetentionDuration::Finite(duration))
+            }
+            _ => None,
+        };
+        self
+    }
+
+    /// Adds a tag to the `GROUP BY` clause
+    pub fn group_by<S>(mut self, tag: S) -> Self
+    where
+        S: ToString,
+    {
+        self.group_by.push(quote_identifier(&tag.to_string()));
+        self
+    }
+
+    /// Adds intervals of the given length to the `GROUP BY` clause using `time()`, e.g. to aggregate hourly
+    pub fn group_by_time(mut self, interval: Duration) -> Self {
+        self.group_by
+            .push(format!("time({})", RetentionDuration::Finite(interval)));
+        self
+    }
+
+    /// Orders the rows of each series by time using `ORDER BY time`, e.g. [`Order::Desc`](crate::query::Order::Desc)
+    /// for the newest points first. InfluxDB returns them in ascending order otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::{InfluxDbQuery, Order};
+    ///
+    /// let query = InfluxDbQuery::select_query("weather").order_by_time(Order::Desc).limit(1);
+    ///
+    /// assert_eq!(query.to_string(), "SELECT * FROM \"weather\" ORDER BY time DESC LIMIT 1");
+    /// ```
+    pub fn order_by_time(mut self, order: Order) -> Self {
+        self.order = Some(order);
+        self
+    }
+
+    /// Limits the number of rows returned per series using `LIMIT`
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Skips the given number of rows per series using `OFFSET`
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Limits the number of series returned using `SLIMIT`, e.g. to page through the groups of a `GROUP BY`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// let query = InfluxDbQuery::select_query("weather").group_by("location").slimit(10).soffset(20);
+    ///
+    /// assert_eq!(query.to_string(), "SELECT * FROM \"weather\" GROUP BY \"location\" SLIMIT 10 SOFFSET 20");
+    /// ```
+    pub fn slimit(mut self, slimit: usize) -> Self {
+        self.slimit = Some(slimit);
+        self
+    }
+
+    /// Skips the given number of series using `SOFFSET`
+    pub fn soffset(mut self, soffset: usize) -> Self {
+        self.soffset = Some(soffset);
+        self
+    }
+
+    /// Returns the timestamps in the time zone `zone`, e.g. `America/New_York`, using the `tz()` clause.
+    ///
+    /// InfluxDB then returns RFC3339 timestamps with the UTC offset of the zone, which deserialize into
+    /// `DateTime<FixedOffset>` keeping the offset, or into `DateTime<Utc>` and
+    /// [`Time`](crate::integrations::serde_integration::Time) as the same instant. The zone also aligns the intervals
+    /// of [`group_by_time`](crate::query::select_query::InfluxDbSelectQuery::group_by_time) to local days.
+    /// It has no effect on epoch timestamps, see [`with_epoch`](crate::query::read_query::InfluxDbReadQuery::with_epoch).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::InfluxDbQuery;
+    ///
+    /// let query = InfluxDbQuery::select_query("weather").limit(10).tz("America/New_York");
+    ///
+    /// assert_eq!(query.to_string(), "SELECT * FROM \"weather\" LIMIT 10 tz('America/New_York')");
+    /// ```
+    pub fn tz<S>(mut self, zone: S) -> Self
+    where
+        S: ToString,
+    {
+        self.timezone = Some(zone.to_string());
+        self
+    }
+
+    /// Runs the query against `database` instead of the database of the client,
+    /// see [`InfluxDbReadQuery::with_database`](crate::query::read_query::InfluxDbReadQuery::with_database)
+    pub fn with_database<S>(mut self, database: S) -> Self
+    where
+        S: ToString,
+    {
+        self.database = Some(database.to_string());
+        self
+    }
+}
+
+impl fmt::Display for InfluxDbSelectQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields = if self.fields.is_empty() {
+            String::from("*")
+        } else {
+            self.fields.iter().join(",")
+        };
+        write!(f, "SELECT {}", fields)?;
+        if let Some(target) = &self.into_measurement {
+            write!(f, " INTO {}", quote_identifier(target))?;
+        }
+        write!(f, " FROM {}", quote_identifier(&self.measurement))?;
+        match (&self.where_clause, self.retention) {
+            (Some(condition), Some(retention)) => write!(
+                f,
+                " WHERE ({}) AND time >= now() - {}",
+                condition, retention
+            )?,
+            (Some(condition), None) => write!(f, " WHERE {}", condition)?,
+            (None, Some(retention)) => write!(f, " WHERE time >= now() - {}", retention)?,
+            (None, None) => {}
+        }
+        if !self.group_by.is_empty() {
+            write!(f, " GROUP BY {}", self.group_by.join(","))?;
+        }
+        if let Some(order) = self.order {
+            write!(f, " ORDER BY time {}", order)?;
+        }
+        if let Some(limit) = self.limit {
+            write!(f, " LIMIT {}", limit)?;
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " OFFSET {}", offset)?;
+        }
+        if let Some(slimit) = self.slimit {
+            write!(f, " SLIMIT {}", slimit)?;
+        }
+        if let Some(soffset) = self.soffset {
+            write!(f, " SOFFSET {}", soffset)?;
+        }
+        if let Some(zone) = &self.timezone {
+            write!(f, " tz({})", quote_string_literal(zone))?;
+        }
+        Ok(())
+    }
+}
+
+impl From<InfluxDbSelectQuery> for InfluxDbReadQuery {
+    fn from(select_query: InfluxDbSelectQuery) -> Self {
+        let database = select_query.database.clone();
+        let read_query = InfluxDbReadQuery::new(select_query);
+        match database {
+            Some(database) => read_query.with_database(database),
+            None => read_query,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::query::read_query::InfluxDbReadQuery;
+    use crate::query::retention_policy::RetentionDuration;
+    use crate::query::select_query::Field;
+    use crate::query::{requires_post, InfluxDbQuery, Order};
+    use std::time::Duration;
+
+    #[test]
+    fn test_select_builder_all_fields() {
+        let query = InfluxDbQuery::select_query("weather");
+
+        assert_eq!(query.to_string(), "SELECT * FROM \"weather\"");
+    }
+
+    #[test]
+    fn test_select_builder_multiple_fields() {
+        let query = InfluxDbQuery::select_query("weather")
+            .add_field("temperature")
+            .add_field("wind_strength");
+
+        assert_eq!(
+            query.to_string(),
+            "SELECT \"temperature\",\"wind_strength\" FROM \"weather\""
+        );
+    }
+
+    #[test]
+    fn test_select_builder_within_retention() {
+        let week = Duration::from_secs(7 * 24 * 60 * 60);
+
+        let query = InfluxDbQuery::select_query("weather").within_retention(week);
+        assert_eq!(
+            query.to_string(),
+            "SELECT * FROM \"weather\" WHERE time >= now() - 1w"
+        );
+
+        // the lower bound of the condition reaches beyond the retention window and is clamped by the added bound
+        let query = InfluxDbQuery::select_query("weather")
+            .where_clause("time > now() - 30d")
+            .within_retention(Duration::from_secs(36 * 60 * 60))
+            .limit(10);
+        assert_eq!(
+            query.to_string(),
+            "SELECT * FROM \"weather\" WHERE (time > now() - 30d) AND time >= now() - 36h LIMIT 10"
+        );
+
+        // retention policies keeping data forever do not bound the query
+        for retention in &[RetentionDuration::Infinite, Duration::from_secs(0).into()] {
+            let query = InfluxDbQuery::select_query("weather")
+                .where_clause("time > now() - 30d")
+                .within_retention(*retention);
+            assert_eq!(
+                query.to_string(),
+                "SELECT * FROM \"weather\" WHERE time > now() - 30d"
+            );
+        }
+    }
+
+    #[test]
+    fn test_select_builder_tz() {
+        let query = InfluxDbQuery::select_query("weather")
+            .where_clause("time > now() - 1d")
+            .group_by_time(Duration::from_secs(60 * 60))
+            .offset(5)
+            .tz("Europe/Berlin");
+        assert_eq!(
+            query.to_string(),
+            "SELECT * FROM \"weather\" WHERE time > now() - 1d GROUP BY time(1h) OFFSET 5 tz('Europe/Berlin')"
+        );
+
+        let query = InfluxDbQuery::select_query("weather").tz("it's");
+        assert_eq!(query.to_string(), "SELECT * FROM \"weather\" tz('it\\'s')");
+    }
+
+    #[test]
+    fn test_select_builder_quotes_identifiers() {
+        let query = InfluxDbQuery::select_query("weather \"berlin\"").add_field("wind speed");
+
+        assert_eq!(
+            query.to_string(),
+            "SELECT \"wind speed\" FROM \"weather \\\"berlin\\\"\""
+        );
+    }
+
+    #[test]
+    fn test_select_builder_full_query() {
+        let query = InfluxDbQuery::select_query("weather")
+            .add_field("temperature")
+            .where_clause("time > now() - 1h")
+            .group_by("location")
+            .group_by("season");
+
+        assert_eq!(
+            query.to_string(),
+            "SELECT \"temperature\" FROM \"weather\" WHERE time > now() - 1h GROUP BY \"location\",\"season\""
+        );
+    }
+
+    #[test]
+    fn test_select_builder_into() {
+        let query = InfluxDbQuery::select_query("weather")
+            .add_field(Field::mean("temperature").as_alias("temperature"))
+            .add_field(Field::max("wind_strength"))
+            .into_measurement("weather_hourly")
+            .where_clause("time > now() - 1d")
+            .group_by_time(Duration::from_secs(60 * 60))
+            .group_by("location");
+        let statement = query.to_string();
+
+        assert_eq!(
+            statement,
+            "SELECT MEAN(\"temperature\") AS \"temperature\",MAX(\"wind_strength\") INTO \"weather_hourly\" FROM \"weather\" WHERE time > now() - 1d GROUP BY time(1h),\"location\""
+        );
+        assert!(requires_post(&statement));
+    }
+
+    #[test]
+    fn test_select_builder_group_by_time() {
+        let query = InfluxDbQuery::select_query("weather")
+            .add_field(Field::mean("temperature"))
+            .group_by_time(Duration::from_secs(90));
+
+        assert_eq!(
+            query.to_string(),
+            "SELECT MEAN(\"temperature\") FROM \"weather\" GROUP BY time(90s)"
+        );
+        assert!(!requires_post(&query.to_string()));
+    }
+
+    #[test]
+    fn test_select_builder_limit_offset() {
+        let query = InfluxDbQuery::select_query("weather")
+            .where_clause("time > now() - 1h")
+            .limit(10)
+            .offset(20);
+
+        assert_eq!(
+            query.to_string(),
+            "SELECT * FROM \"weather\" WHERE time > now() - 1h LIMIT 10 OFFSET 20"
+        );
+    }
+
+    #[test]
+    fn test_select_builder_order_by_time() {
+        let query = InfluxDbQuery::select_query("weather")
+            .where_clause("time > now() - 1h")
+            .group_by("location")
+            .order_by_time(Order::Desc)
+            .limit(10);
+        assert_eq!(
+            query.to_string(),
+            "SELECT * FROM \"weather\" WHERE time > now() - 1h GROUP BY \"location\" ORDER BY time DESC LIMIT 10"
+        );
+
+        let query = InfluxDbQuery::select_query("weather").order_by_time(Order::Asc);
+        assert_eq!(
+            query.to_string(),
+            "SELECT * FROM \"weather\" ORDER BY time ASC"
+        );
+    }
+
+    #[test]
+    fn test_select_builder_slimit_soffset() {
+        let query = InfluxDbQuery::select_query("weather")
+            .group_by("location")
+            .soffset(20)
+            .slimit(10);
+        assert_eq!(
+            query.to_string(),
+            "SELECT * FROM \"weather\" GROUP BY \"location\" SLIMIT 10 SOFFSET 20"
+        );
+
+        let query = InfluxDbQuery::select_query("weather")
+            .group_by("location")
+            .limit(5)
+            .offset(1)
+            .slimit(2)
+            .tz("Europe/Berlin");
+        assert_eq!(
+            query.to_string(),
+            "SELECT * FROM \"weather\" GROUP BY \"location\" LIMIT 5 OFFSET 1 SLIMIT 2 tz('Europe/Berlin')"
+        );
+    }
+
+    #[test]
+    fn test_select_builder_into_read_query() {
+        let query: InfluxDbReadQuery = InfluxDbQuery::select_query("weather").into();
+
+        assert_eq!(query.build().unwrap(), "SELECT * FROM \"weather\"");
+    }
+
+    #[test]
+    fn test_field_aggregations() {
+        let aggregations = vec![
+            (Field::mean("temperature"), "MEAN"),
+            (Field::sum("temperature"), "SUM"),
+            (Field::count("temperature"), "COUNT"),
+            (Field::min("temperature"), "MIN"),
+            (Field::max("temperature"), "MAX"),
+            (Field::first("temperature"), "FIRST"),
+            (Field::last("temperature"), "LAST"),
+            (Field::median("temperature"), "MEDIAN"),
+            (Field::stddev("temperature"), "STDDEV"),
+        ];
+
+        for (field, function) in aggregations {
+            assert_eq!(field.to_string(), format!("{}(\"temperature\")", function));
+            assert_eq!(
+                field.as_alias("result").to_string(),
+                format!("{}(\"temperature\") AS \"result\"", function)
+            );
+        }
+    }
+
+    #[test]
+    fn test_field_count_wildcard() {
+        assert_eq!(Field::count("*").to_string(), "COUNT(*)");
+    }
+
+    #[test]
+    fn test_select_builder_aggregation() {
+        let query = InfluxDbQuery::select_query("weather")
+            .add_field(Field::mean("temperature").as_alias("avg_temp"))
+            .where_clause("time > now() - 1d")
+            .group_by("location");
+
+        assert_eq!(
+            query.to_string(),
+            "SELECT MEAN(\"temperature\") AS \"avg_temp\" FROM \"weather\" WHERE time > now() - 1d GROUP BY \"location\""
+        );
+    }
+}
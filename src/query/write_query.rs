@@ -3,17 +3,22 @@
 //! Can only be instantiated by using InfluxDbQuery::write_query
 
 use crate::error::InfluxDbError;
-use crate::query::{InfluxDbQuery, QueryType, Timestamp, ValidQuery};
+use crate::query::{InfluxDbQuery, Precision, QueryType, Timestamp, ValidQuery};
 use itertools::Itertools;
-
-// todo: batch write queries
+use std::fmt;
+use std::mem;
+use std::str::FromStr;
 
 /// Internal Representation of a Write query that has not yet been built
+#[derive(Clone, Debug, PartialEq)]
 pub struct InfluxDbWriteQuery {
-    fields: Vec<(String, String)>,
-    tags: Vec<(String, String)>,
+    fields: Vec<(String, InfluxDbType)>,
+    raw_fields: Vec<String>,
+    tags: Vec<(String, InfluxDbType)>,
     measurement: String,
     timestamp: Timestamp,
+    strict_fields: bool,
+    extra_params: Vec<(String, String)>,
 }
 
 impl InfluxDbWriteQuery {
@@ -24,14 +29,79 @@ impl InfluxDbWriteQuery {
     {
         InfluxDbWriteQuery {
             fields: vec![],
+            raw_fields: vec![],
             tags: vec![],
             measurement: measurement.to_string(),
             timestamp,
+            strict_fields: false,
+            extra_params: vec![],
         }
     }
 
+    /// Creates a new [`InfluxDbWriteQuery`](crate::query::write_query::InfluxDbWriteQuery) with the tags and fields of
+    /// the given maps, e.g. a `HashMap` or `BTreeMap`.
+    ///
+    /// Tags and fields are sorted by their key, so the line protocol does not depend on the iteration order of the maps.
+    /// This is equivalent to calling [`add_tag`](crate::query::write_query::InfluxDbWriteQuery::add_tag) and
+    /// [`add_field`](crate::query::write_query::InfluxDbWriteQuery::add_field) for every entry in that order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::write_query::{InfluxDbType, InfluxDbWriteQuery};
+    /// use influxdb::query::Timestamp;
+    /// use std::collections::HashMap;
+    ///
+    /// let mut tags = HashMap::new();
+    /// tags.insert("station", "1");
+    /// tags.insert("location", "us-midwest");
+    /// let mut fields: HashMap<&str, InfluxDbType> = HashMap::new();
+    /// fields.insert("temperature", 82.into());
+    /// fields.insert("humidity", 0.5.into());
+    ///
+    /// let query = InfluxDbWriteQuery::from_maps(Timestamp::HOURS(11), "weather", tags, fields);
+    /// assert_eq!(
+    ///     query.to_string(),
+    ///     "weather,location=\"us-midwest\",station=\"1\" humidity=0.5,temperature=82i 11"
+    /// );
+    /// ```
+    pub fn from_maps<S, T, TK, TV, F, FK, FV>(
+        timestamp: Timestamp,
+        measurement: S,
+        tags: T,
+        fields: F,
+    ) -> Self
+    where
+        S: ToString,
+        T: IntoIterator<Item = (TK, TV)>,
+        TK: ToString,
+        TV: Into<InfluxDbType>,
+        F: IntoIterator<Item = (FK, FV)>,
+        FK: ToString,
+        FV: Into<InfluxDbType>,
+    {
+        let mut tags: Vec<(String, InfluxDbType)> = tags
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.into()))
+            .collect();
+        let mut fields: Vec<(String, InfluxDbType)> = fields
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.into()))
+            .collect();
+        tags.sort_by(|(a, _), (b, _)| a.cmp(b));
+        fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let query = InfluxDbWriteQuery::new(timestamp, measurement).add_fields(fields);
+        tags.into_iter()
+            .fold(query, |query, (key, value)| query.add_tag(key, value))
+    }
+
     /// Adds a field to the [`InfluxDbWriteQuery`](crate::query::write_query::InfluxDbWriteQuery)
     ///
+    /// If a field with the same key was added before, the field added last wins and the earlier one is dropped
+    /// from the line protocol. Use [`with_strict_fields`](crate::query::write_query::InfluxDbWriteQuery::with_strict_fields)
+    /// to reject duplicate keys instead.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -45,15 +115,191 @@ impl InfluxDbWriteQuery {
         I: Into<InfluxDbType>,
     {
         let val: InfluxDbType = value.into();
-        self.fields.push((tag.to_string(), val.to_string()));
+        self.fields.push((tag.to_string(), val));
         self
     }
 
+    /// Adds a field to the [`InfluxDbWriteQuery`](crate::query::write_query::InfluxDbWriteQuery) if `value` is `Some`,
+    /// leaving the field out otherwise, as line protocol has no `null`.
+    ///
+    /// A query whose fields are all `None` fails to build, as a point needs at least one field.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::{InfluxDbQuery, Timestamp};
+    ///
+    /// let humidity: Option<f64> = None;
+    /// let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+    ///     .add_field_opt("temperature", Some(82))
+    ///     .add_field_opt("humidity", humidity);
+    /// assert_eq!(query.to_string(), "weather temperature=82i 11");
+    /// ```
+    pub fn add_field_opt<S, I>(self, tag: S, value: Option<I>) -> Self
+    where
+        S: ToString,
+        I: Into<InfluxDbType>,
+    {
+        match value {
+            Some(value) => self.add_field(tag, value),
+            None => self,
+        }
+    }
+
+    /// Adds multiple fields to the [`InfluxDbWriteQuery`](crate::query::write_query::InfluxDbWriteQuery), in the
+    /// order they are yielded. This behaves like calling [`add_field`](crate::query::write_query::InfluxDbWriteQuery::add_field)
+    /// for every field, so a later field wins over an earlier one with the same key.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::{InfluxDbQuery, Timestamp};
+    ///
+    /// InfluxDbQuery::write_query(Timestamp::NOW, "measurement")
+    ///     .add_fields(vec![("field1", 5), ("field2", 6)])
+    ///     .build();
+    /// ```
+    pub fn add_fields<F, S, I>(self, fields: F) -> Self
+    where
+        F: IntoIterator<Item = (S, I)>,
+        S: ToString,
+        I: Into<InfluxDbType>,
+    {
+        fields
+            .into_iter()
+            .fold(self, |query, (tag, value)| query.add_field(tag, value))
+    }
+
+    /// Appends a precomputed fragment of fields in line protocol, e.g. `temperature=82i,humidity=0.5`, to the
+    /// [`InfluxDbWriteQuery`](crate::query::write_query::InfluxDbWriteQuery).
+    ///
+    /// The fragment is written verbatim after the fields added using [`add_field`](crate::query::write_query::InfluxDbWriteQuery::add_field),
+    /// for hot paths which serialize their fields themselves. It is trusted to be valid and correctly escaped line protocol:
+    /// it is neither escaped nor validated, so duplicate keys or a `time` key in it are not detected and end up being
+    /// rejected by InfluxDB, or worse, written to other fields than intended.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::{InfluxDbQuery, Timestamp};
+    ///
+    /// let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+    ///     .add_field("temperature", 82)
+    ///     .add_raw_field("humidity=0.5,wind_direction=\"north\"");
+    /// assert_eq!(query.to_string(), "weather temperature=82i,humidity=0.5,wind_direction=\"north\" 11");
+    /// ```
+    pub fn add_raw_field(mut self, fragment: &str) -> Self {
+        if !fragment.is_empty() {
+            self.raw_fields.push(fragment.to_string());
+        }
+        self
+    }
+
+    /// Makes [`build`](crate::query::InfluxDbQuery::build) fail with an `InvalidQueryError` if a field key was added
+    /// more than once, instead of keeping the field added last.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::{InfluxDbQuery, Timestamp};
+    ///
+    /// let query = InfluxDbQuery::write_query(Timestamp::NOW, "measurement")
+    ///     .with_strict_fields()
+    ///     .add_field("field1", 5)
+    ///     .add_field("field1", 6)
+    ///     .build();
+    /// assert!(query.is_err());
+    /// ```
+    pub fn with_strict_fields(mut self) -> Self {
+        self.strict_fields = true;
+        self
+    }
+
+    /// Adds a parameter to the URL of the request, for parameters of InfluxDB not supported by this crate yet.
+    ///
+    /// Parameters set by the client, like `db` or `precision`, can not be overridden and result in an
+    /// `InvalidQueryError` when sending the query.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::{InfluxDbQuery, Timestamp};
+    ///
+    /// InfluxDbQuery::write_query(Timestamp::NOW, "measurement")
+    ///     .add_field("field1", 5)
+    ///     .with_extra_param("consistency", "all");
+    /// ```
+    pub fn with_extra_param<S1, S2>(mut self, key: S1, value: S2) -> Self
+    where
+        S1: ToString,
+        S2: ToString,
+    {
+        self.extra_params.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Returns the extra parameters added to the URL of the request
+    pub fn get_extra_params(&self) -> &[(String, String)] {
+        &self.extra_params
+    }
+
+    /// Returns the size in bytes of the line protocol this query sends, without sending it
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::{InfluxDbQuery, Timestamp};
+    ///
+    /// let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather").add_field("temperature", 82);
+    /// assert_eq!(query.byte_len().unwrap(), "weather temperature=82i 11".len());
+    /// ```
+    pub fn byte_len(&self) -> Result<usize, InfluxDbError> {
+        Ok(self.build()?.get().len())
+    }
+
+    /// Returns the fields of the query, keeping only the field added last for every key
+    fn unique_fields(&self) -> impl Iterator<Item = &(String, InfluxDbType)> {
+        self.fields
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, field)| {
+                if self.fields[index + 1..]
+                    .iter()
+                    .any(|(key, _)| key == &field.0)
+                {
+                    None
+                } else {
+                    Some(field)
+                }
+            })
+    }
+
+    /// Returns the measurement the point is written to
+    #[cfg(feature = "use-serde")]
+    pub(crate) fn get_measurement(&self) -> &str {
+        &self.measurement
+    }
+
+    /// Returns the tags of the point, including tags with an empty value
+    #[cfg(feature = "use-serde")]
+    pub(crate) fn get_tags(&self) -> &[(String, InfluxDbType)] {
+        &self.tags
+    }
+
+    /// Returns whether the query contains an unsigned integer field, which requires InfluxDB 1.6 or later
+    pub(crate) fn has_unsigned_fields(&self) -> bool {
+        self.unique_fields()
+            .any(|(_, value)| matches!(value, InfluxDbType::UnsignedInteger(_)))
+    }
+
     /// Adds a tag to the [`InfluxDbWriteQuery`](crate::query::write_query::InfluxDbWriteQuery)
     ///
     /// Please note that a [`InfluxDbWriteQuery`](crate::query::write_query::InfluxDbWriteQuery) requires at least one field. Composing a query with
     /// only tags will result in a failure building the query.
     ///
+    /// InfluxDB does not store tags with an empty value. Such tags are left out of the line protocol, so the point
+    /// is still written, and a warning is logged when building the query.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -68,24 +314,107 @@ impl InfluxDbWriteQuery {
         I: Into<InfluxDbType>,
     {
         let val: InfluxDbType = value.into();
-        self.tags.push((tag.to_string(), val.to_string()));
+        self.tags.push((tag.to_string(), val));
         self
     }
 
-    pub fn get_precision(&self) -> String {
-        let modifier = match self.timestamp {
-            Timestamp::NOW => return String::from(""),
-            Timestamp::NANOSECONDS(_) => "ns",
-            Timestamp::MICROSECONDS(_) => "u",
-            Timestamp::MILLISECONDS(_) => "ms",
-            Timestamp::SECONDS(_) => "s",
-            Timestamp::MINUTES(_) => "m",
-            Timestamp::HOURS(_) => "h",
+    /// Adds a field holding a `serde_json::Value` to the [`InfluxDbWriteQuery`](crate::query::write_query::InfluxDbWriteQuery)
+    ///
+    /// Numbers, booleans and strings are mapped to the matching [`InfluxDbType`](crate::query::write_query::InfluxDbType).
+    /// Arrays, objects and `null` can not be represented in line protocol and result in an `InvalidQueryError`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::{InfluxDbQuery, Timestamp};
+    /// use serde_json::json;
+    ///
+    /// let query = InfluxDbQuery::write_query(Timestamp::NOW, "measurement")
+    ///     .add_field_value("field1", json!(5))
+    ///     .and_then(|query| query.add_field_value("field2", json!("text")));
+    /// assert!(query.is_ok());
+    ///
+    /// let query = InfluxDbQuery::write_query(Timestamp::NOW, "measurement")
+    ///     .add_field_value("field1", json!([1, 2]));
+    /// assert!(query.is_err());
+    /// ```
+    #[cfg(feature = "use-serde")]
+    pub fn add_field_value<S>(self, tag: S, value: serde_json::Value) -> Result<Self, InfluxDbError>
+    where
+        S: ToString,
+    {
+        use serde_json::Value;
+
+        let tag = tag.to_string();
+        let val = match value {
+            Value::Bool(b) => InfluxDbType::Boolean(b),
+            Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    InfluxDbType::SignedInteger(i)
+                } else if let Some(u) = n.as_u64() {
+                    InfluxDbType::UnsignedInteger(u)
+                } else {
+                    // serde_json numbers are always representable as f64 if they are not integers
+                    InfluxDbType::Float(n.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            Value::String(text) => InfluxDbType::Text(text),
+            Value::Null | Value::Array(_) | Value::Object(_) => {
+                return Err(InfluxDbError::InvalidQueryError {
+                    error: format!(
+                        "field \"{}\" must be a number, boolean or string, got {}",
+                        tag, value
+                    ),
+                })
+            }
         };
-        modifier.to_string()
+        Ok(self.add_field(tag, val))
+    }
+
+    /// Returns the precision of the timestamp of the query, or `None` for [`Timestamp::NOW`](crate::query::Timestamp::NOW)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::{InfluxDbQuery, Precision, Timestamp};
+    ///
+    /// let query = InfluxDbQuery::write_query(Timestamp::MILLISECONDS(1_568_106_310_123), "weather");
+    /// assert_eq!(query.precision(), Some(Precision::MILLISECONDS));
+    /// assert_eq!(InfluxDbQuery::write_query(Timestamp::NOW, "weather").precision(), None);
+    /// ```
+    pub fn precision(&self) -> Option<Precision> {
+        match self.timestamp {
+            Timestamp::NOW => None,
+            Timestamp::NANOSECONDS(_) => Some(Precision::NANOSECONDS),
+            Timestamp::MICROSECONDS(_) => Some(Precision::MICROSECONDS),
+            Timestamp::MILLISECONDS(_) => Some(Precision::MILLISECONDS),
+            Timestamp::SECONDS(_) => Some(Precision::SECONDS),
+            Timestamp::MINUTES(_) => Some(Precision::MINUTES),
+            Timestamp::HOURS(_) => Some(Precision::HOURS),
+        }
+    }
+
+    /// Returns the `precision` parameter sent with the query, e.g. `ms` or `h`, derived from its timestamp.
+    ///
+    /// The parameter is empty for [`Timestamp::NOW`](crate::query::Timestamp::NOW), in which case the client
+    /// sends its [default precision](crate::client::InfluxDbClient::with_default_precision), if any.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use influxdb::query::{InfluxDbQuery, Timestamp};
+    ///
+    /// let query = InfluxDbQuery::write_query(Timestamp::MICROSECONDS(1_568_106_310_123_456), "weather");
+    /// assert_eq!(query.get_precision(), "u");
+    /// ```
+    pub fn get_precision(&self) -> String {
+        self.precision()
+            .map(|precision| precision.to_string())
+            .unwrap_or_default()
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
 pub enum InfluxDbType {
     Boolean(bool),
     Float(f64),
@@ -94,6 +423,8 @@ pub enum InfluxDbType {
     Text(String),
 }
 
+/// Renders the value as a line protocol field value, which InfluxDB infers the type of by its appearance:
+/// integers carry an `i` and unsigned integers a `u` suffix, while bare numbers are floats.
 impl ToString for InfluxDbType {
     fn to_string(&self) -> String {
         use InfluxDbType::*;
@@ -101,9 +432,75 @@ impl ToString for InfluxDbType {
         match self {
             Boolean(x) => x.to_string(),
             Float(x) => x.to_string(),
-            SignedInteger(x) => x.to_string(),
-            UnsignedInteger(x) => x.to_string(),
-            Text(text) => format!("\"{text}\"", text = text),
+            SignedInteger(x) => format!("{}i", x),
+            UnsignedInteger(x) => format!("{}u", x),
+            Text(text) => format!(
+                "\"{text}\"",
+                text = text.replace('\\', "\\\\").replace('"', "\\\"")
+            ),
+        }
+    }
+}
+
+/// Parses a line protocol field value, following the same rules InfluxDB uses to infer its type.
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::query::write_query::InfluxDbType;
+///
+/// assert_eq!("1".parse::<InfluxDbType>().unwrap(), InfluxDbType::Float(1.0));
+/// assert_eq!("1i".parse::<InfluxDbType>().unwrap(), InfluxDbType::SignedInteger(1));
+/// assert_eq!("1u".parse::<InfluxDbType>().unwrap(), InfluxDbType::UnsignedInteger(1));
+/// assert_eq!("T".parse::<InfluxDbType>().unwrap(), InfluxDbType::Boolean(true));
+/// assert_eq!("\"1\"".parse::<InfluxDbType>().unwrap(), InfluxDbType::Text("1".to_string()));
+/// ```
+impl FromStr for InfluxDbType {
+    type Err = InfluxDbError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || InfluxDbError::DeserializationError {
+            error: format!("invalid line protocol field value: {}", value),
+        };
+
+        if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+            let mut text = String::with_capacity(value.len() - 2);
+            let mut chars = value[1..value.len() - 1].chars().peekable();
+            while let Some(c) = chars.next() {
+                match (c, chars.peek()) {
+                    ('\\', Some(&escaped)) if escaped == '"' || escaped == '\\' => {
+                        text.push(escaped);
+                        chars.next();
+                    }
+                    ('"', _) => return Err(invalid()),
+                    (c, _) => text.push(c),
+                }
+            }
+            return Ok(InfluxDbType::Text(text));
+        }
+
+        match value {
+            "t" | "T" | "true" | "True" | "TRUE" => return Ok(InfluxDbType::Boolean(true)),
+            "f" | "F" | "false" | "False" | "FALSE" => return Ok(InfluxDbType::Boolean(false)),
+            _ => {}
+        }
+
+        if let Some(integer) = value.strip_suffix('i') {
+            return integer
+                .parse()
+                .map(InfluxDbType::SignedInteger)
+                .map_err(|_| invalid());
+        }
+        if let Some(unsigned) = value.strip_suffix('u') {
+            return unsigned
+                .parse()
+                .map(InfluxDbType::UnsignedInteger)
+                .map_err(|_| invalid());
+        }
+        // `inf` and `NaN` are parsed by Rust, but can not be written to InfluxDB
+        match value.parse::<f64>() {
+            Ok(float) if float.is_finite() => Ok(InfluxDbType::Float(float)),
+            _ => Err(invalid()),
         }
     }
 }
@@ -130,29 +527,49 @@ impl From<&str> for InfluxDbType {
     }
 }
 
-impl InfluxDbQuery for InfluxDbWriteQuery {
-    fn build(&self) -> Result<ValidQuery, InfluxDbError> {
-        if self.fields.is_empty() {
-            return Err(InfluxDbError::InvalidQueryError {
-                error: "fields cannot be empty".to_string(),
-            });
-        }
+/// Renders a tag value, which InfluxDB always stores as a string, so numbers do not carry type suffixes
+pub(crate) fn tag_value(value: &InfluxDbType) -> String {
+    match value {
+        InfluxDbType::SignedInteger(x) => x.to_string(),
+        InfluxDbType::UnsignedInteger(x) => x.to_string(),
+        InfluxDbType::Text(text) => format!("\"{text}\"", text = text),
+        value => value.to_string(),
+    }
+}
+
+/// Returns whether a tag value is empty, so InfluxDB would drop the tag
+pub(crate) fn is_empty_tag_value(value: &InfluxDbType) -> bool {
+    match value {
+        InfluxDbType::Text(text) => text.is_empty(),
+        _ => false,
+    }
+}
 
+/// Renders the line protocol of the [`InfluxDbWriteQuery`](crate::query::write_query::InfluxDbWriteQuery).
+///
+/// Unlike [`build`](crate::query::InfluxDbQuery::build), this does not validate the query, so a query
+/// without fields renders as line protocol InfluxDB will reject.
+impl fmt::Display for InfluxDbWriteQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut tags = self
             .tags
             .iter()
-            .map(|(tag, value)| format!("{tag}={value}", tag = tag, value = value))
+            .filter(|(_, value)| !is_empty_tag_value(value))
+            .map(|(tag, value)| format!("{tag}={value}", tag = tag, value = tag_value(value)))
             .join(",");
         if !tags.is_empty() {
             tags.insert_str(0, ",");
         }
         let fields = self
-            .fields
-            .iter()
-            .map(|(field, value)| format!("{field}={value}", field = field, value = value))
+            .unique_fields()
+            .map(|(field, value)| {
+                format!("{field}={value}", field = field, value = value.to_string())
+            })
+            .chain(self.raw_fields.iter().cloned())
             .join(",");
 
-        Ok(ValidQuery(format!(
+        write!(
+            f,
             "{measurement}{tags} {fields}{time}",
             measurement = self.measurement,
             tags = tags,
@@ -161,7 +578,71 @@ impl InfluxDbQuery for InfluxDbWriteQuery {
                 Timestamp::NOW => String::from(""),
                 _ => format!(" {}", self.timestamp),
             }
-        )))
+        )
+    }
+}
+
+impl InfluxDbQuery for InfluxDbWriteQuery {
+    fn build(&self) -> Result<ValidQuery, InfluxDbError> {
+        if self.fields.is_empty() && self.raw_fields.is_empty() {
+            return Err(InfluxDbError::InvalidQueryError {
+                error: "fields cannot be empty".to_string(),
+            });
+        }
+
+        if self.strict_fields {
+            if let Some((key, _)) = self
+                .fields
+                .iter()
+                .enumerate()
+                .find(|(index, (key, _))| self.fields[..*index].iter().any(|(k, _)| k == key))
+                .map(|(_, field)| field)
+            {
+                return Err(InfluxDbError::InvalidQueryError {
+                    error: format!("field \"{}\" was added more than once", key),
+                });
+            }
+        }
+
+        if let Some((key, _)) = self
+            .unique_fields()
+            .find(|(_, value)| matches!(value, InfluxDbType::Float(x) if !x.is_finite()))
+        {
+            return Err(InfluxDbError::InvalidQueryError {
+                error: format!(
+                    "field \"{}\" is not a finite number, which line protocol can not represent",
+                    key
+                ),
+            });
+        }
+
+        if let Some(key) = self
+            .fields
+            .iter()
+            .map(|(key, _)| key)
+            .chain(self.tags.iter().map(|(key, _)| key))
+            .find(|key| *key == "time")
+        {
+            return Err(InfluxDbError::InvalidQueryError {
+                error: format!(
+                    "\"{}\" is reserved for the timestamp and cannot be used as a field or tag key",
+                    key
+                ),
+            });
+        }
+
+        for (tag, _) in self
+            .tags
+            .iter()
+            .filter(|(_, value)| is_empty_tag_value(value))
+        {
+            warn!(
+                "skipping tag \"{}\" of measurement \"{}\", as its value is empty",
+                tag, self.measurement
+            );
+        }
+
+        Ok(ValidQuery(self.to_string()))
     }
 
     fn get_type(&self) -> QueryType {
@@ -169,9 +650,77 @@ impl InfluxDbQuery for InfluxDbWriteQuery {
     }
 }
 
+/// Returns the size in bytes of the line protocol a batch of write queries sends in a single request,
+/// see [`InfluxDbClient::batch_write`](crate::client::InfluxDbClient::batch_write)
+///
+/// # Examples
+///
+/// ```rust
+/// use influxdb::query::write_query::batch_byte_len;
+/// use influxdb::query::{InfluxDbQuery, Timestamp};
+///
+/// let batch = [
+///     InfluxDbQuery::write_query(Timestamp::HOURS(1), "weather").add_field("temperature", 82),
+///     InfluxDbQuery::write_query(Timestamp::HOURS(2), "weather").add_field("temperature", 84),
+/// ];
+/// assert_eq!(batch_byte_len(&batch).unwrap(), "weather temperature=82i 1\nweather temperature=84i 2".len());
+/// ```
+pub fn batch_byte_len(queries: &[InfluxDbWriteQuery]) -> Result<usize, InfluxDbError> {
+    let lines = queries
+        .iter()
+        .map(InfluxDbWriteQuery::byte_len)
+        .collect::<Result<Vec<usize>, InfluxDbError>>()?;
+    // lines are separated by newlines
+    Ok(lines.iter().sum::<usize>() + lines.len().saturating_sub(1))
+}
+
+/// Builds a batch of write queries into line protocol bodies of at most `max_bytes` bytes each.
+///
+/// A single point larger than `max_bytes` is put into a body of its own.
+pub(crate) fn chunk_batch(
+    queries: &[InfluxDbWriteQuery],
+    max_bytes: usize,
+) -> Result<Vec<String>, InfluxDbError> {
+    let precision = match queries.first() {
+        Some(query) => query.get_precision(),
+        None => {
+            return Err(InfluxDbError::InvalidQueryError {
+                error: "batch cannot be empty".to_string(),
+            })
+        }
+    };
+    if queries
+        .iter()
+        .any(|query| query.get_precision() != precision)
+    {
+        return Err(InfluxDbError::InvalidQueryError {
+            error: "all queries in a batch need to use the same timestamp precision".to_string(),
+        });
+    }
+
+    let mut chunks: Vec<String> = vec![];
+    let mut chunk = String::new();
+    for query in queries {
+        let line = query.build()?.get();
+        if !chunk.is_empty() && chunk.len() + 1 + line.len() > max_bytes {
+            chunks.push(mem::take(&mut chunk));
+        }
+        if !chunk.is_empty() {
+            chunk.push('\n');
+        }
+        chunk.push_str(&line);
+    }
+    chunks.push(chunk);
+
+    Ok(chunks)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::query::{InfluxDbQuery, Timestamp};
+    use super::{batch_byte_len, chunk_batch, InfluxDbType, InfluxDbWriteQuery};
+    use crate::error::InfluxDbError;
+    use crate::query::{InfluxDbQuery, Precision, Timestamp};
+    use std::collections::{BTreeMap, HashMap};
 
     #[test]
     fn test_write_builder_empty_query() {
@@ -187,7 +736,7 @@ mod tests {
             .build();
 
         assert!(query.is_ok(), "Query was empty");
-        assert_eq!(query.unwrap(), "weather temperature=82 11");
+        assert_eq!(query.unwrap(), "weather temperature=82i 11");
     }
 
     #[test]
@@ -200,8 +749,150 @@ mod tests {
         assert!(query.is_ok(), "Query was empty");
         assert_eq!(
             query.unwrap(),
-            "weather temperature=82,wind_strength=3.7 11"
+            "weather temperature=82i,wind_strength=3.7 11"
+        );
+    }
+
+    #[test]
+    fn test_write_builder_optional_fields() {
+        let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field_opt("temperature", Some(82))
+            .add_field_opt("humidity", None::<f64>)
+            .add_field_opt("location", Some("us-midwest"))
+            .add_field_opt("raining", None::<bool>)
+            .build();
+
+        assert_eq!(
+            query.unwrap(),
+            "weather temperature=82i,location=\"us-midwest\" 11"
         );
+
+        let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field_opt("humidity", None::<f64>)
+            .build();
+        assert!(query.is_err(), "Query without fields was built");
+    }
+
+    #[test]
+    fn test_write_builder_from_maps() {
+        let mut tags = HashMap::new();
+        tags.insert("station", InfluxDbType::from(1));
+        tags.insert("location", InfluxDbType::from("us-midwest"));
+        tags.insert("country", InfluxDbType::from("us"));
+        let mut fields: HashMap<String, InfluxDbType> = HashMap::new();
+        fields.insert("wind_strength".to_string(), 3.7.into());
+        fields.insert("temperature".to_string(), 82.into());
+        fields.insert("raining".to_string(), false.into());
+
+        let query = InfluxDbWriteQuery::from_maps(Timestamp::HOURS(11), "weather", tags, fields);
+        assert_eq!(
+            query.build().unwrap(),
+            "weather,country=\"us\",location=\"us-midwest\",station=1 raining=false,temperature=82i,wind_strength=3.7 11"
+        );
+
+        let mut fields = BTreeMap::new();
+        fields.insert("temperature", 82);
+        let query = InfluxDbWriteQuery::from_maps(
+            Timestamp::HOURS(11),
+            "weather",
+            BTreeMap::<String, String>::new(),
+            fields,
+        );
+        assert_eq!(query.build().unwrap(), "weather temperature=82i 11");
+    }
+
+    #[test]
+    fn test_write_builder_raw_fields() {
+        let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_raw_field("temperature=82i,wind\\ strength=3.7")
+            .build();
+        assert_eq!(
+            query.unwrap(),
+            "weather temperature=82i,wind\\ strength=3.7 11"
+        );
+
+        let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field("temperature", 82)
+            .add_raw_field("humidity=0.5")
+            .add_raw_field("")
+            .build();
+        assert_eq!(query.unwrap(), "weather temperature=82i,humidity=0.5 11");
+    }
+
+    #[test]
+    fn test_write_builder_add_fields() {
+        let mut fields = BTreeMap::new();
+        fields.insert("humidity", 69.5);
+        fields.insert("temperature", 82.0);
+        fields.insert("wind_strength", 3.7);
+
+        let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field("pressure", 1013)
+            .add_fields(fields)
+            .build();
+
+        assert_eq!(
+            query.unwrap(),
+            "weather pressure=1013i,humidity=69.5,temperature=82,wind_strength=3.7 11"
+        );
+    }
+
+    #[test]
+    fn test_write_builder_duplicate_field_last_wins() {
+        let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field("temperature", 82)
+            .add_field("wind_strength", 3.7)
+            .add_field("temperature", 84)
+            .build();
+
+        assert_eq!(
+            query.unwrap(),
+            "weather wind_strength=3.7,temperature=84i 11"
+        );
+    }
+
+    #[test]
+    fn test_write_builder_duplicate_field_strict() {
+        let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .with_strict_fields()
+            .add_field("temperature", 82)
+            .add_field("temperature", 84)
+            .build();
+
+        match query {
+            Err(InfluxDbError::InvalidQueryError { error }) => {
+                assert_eq!(error, "field \"temperature\" was added more than once")
+            }
+            _ => panic!("Should be an InvalidQueryError"),
+        }
+
+        let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .with_strict_fields()
+            .add_field("temperature", 82)
+            .add_field("wind_strength", 3.7)
+            .build();
+        assert!(query.is_ok());
+    }
+
+    #[test]
+    fn test_write_builder_empty_tag_value() {
+        let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field("temperature", 82)
+            .add_tag("location", "")
+            .add_tag("season", "summer")
+            .build();
+
+        assert_eq!(
+            query.unwrap(),
+            "weather,season=\"summer\" temperature=82i 11"
+        );
+
+        let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field("temperature", 82)
+            .add_tag("location", String::new())
+            .build();
+
+        assert_eq!(query.unwrap(), "weather temperature=82i 11");
     }
 
     #[test]
@@ -224,10 +915,136 @@ mod tests {
         assert!(query.is_ok(), "Query was empty");
         assert_eq!(
             query.unwrap(),
-            "weather,location=\"us-midwest\",season=\"summer\" temperature=82 11"
+            "weather,location=\"us-midwest\",season=\"summer\" temperature=82i 11"
         );
     }
 
+    #[test]
+    #[cfg(feature = "use-serde")]
+    fn test_write_builder_json_scalars() {
+        use serde_json::json;
+
+        let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field_value("raining", json!(true))
+            .and_then(|query| query.add_field_value("temperature", json!(-5)))
+            .and_then(|query| query.add_field_value("humidity", json!(u64::MAX)))
+            .and_then(|query| query.add_field_value("wind_strength", json!(3.7)))
+            .and_then(|query| query.add_field_value("location", json!("us-midwest")))
+            .unwrap()
+            .build();
+
+        assert_eq!(
+            query.unwrap(),
+            "weather raining=true,temperature=-5i,humidity=18446744073709551615u,wind_strength=3.7,location=\"us-midwest\" 11"
+        );
+    }
+
+    #[test]
+    fn test_field_value_round_trip() {
+        let values = vec![
+            InfluxDbType::Float(1.0),
+            InfluxDbType::Float(-3.7),
+            InfluxDbType::Float(1e-7),
+            InfluxDbType::Float(f64::MAX),
+            InfluxDbType::SignedInteger(1),
+            InfluxDbType::SignedInteger(i64::MIN),
+            InfluxDbType::UnsignedInteger(u64::MAX),
+            InfluxDbType::Boolean(true),
+            InfluxDbType::Boolean(false),
+            InfluxDbType::Text("1".to_string()),
+            InfluxDbType::Text("say \"hi\" to C:\\".to_string()),
+            InfluxDbType::Text(String::new()),
+        ];
+
+        for value in values {
+            let serialized = value.to_string();
+            assert_eq!(
+                serialized.parse::<InfluxDbType>().unwrap(),
+                value,
+                "{} did not round-trip",
+                serialized
+            );
+        }
+    }
+
+    #[test]
+    fn test_field_value_inference() {
+        assert_eq!(InfluxDbType::Float(1.0).to_string(), "1");
+        assert_eq!(InfluxDbType::SignedInteger(1).to_string(), "1i");
+        assert_eq!(InfluxDbType::UnsignedInteger(1).to_string(), "1u");
+        assert_eq!(
+            InfluxDbType::Text("say \"hi\"".to_string()).to_string(),
+            "\"say \\\"hi\\\"\""
+        );
+
+        for (value, expected) in [
+            ("1", InfluxDbType::Float(1.0)),
+            ("-1.5e3", InfluxDbType::Float(-1500.0)),
+            ("-1i", InfluxDbType::SignedInteger(-1)),
+            ("1u", InfluxDbType::UnsignedInteger(1)),
+            ("t", InfluxDbType::Boolean(true)),
+            ("True", InfluxDbType::Boolean(true)),
+            ("FALSE", InfluxDbType::Boolean(false)),
+            ("\"true\"", InfluxDbType::Text("true".to_string())),
+        ] {
+            assert_eq!(value.parse::<InfluxDbType>().unwrap(), expected);
+        }
+
+        for invalid in ["", "1.5i", "-1u", "1x", "tRUE", "NaN", "inf", "\"a\"b\""] {
+            assert!(
+                invalid.parse::<InfluxDbType>().is_err(),
+                "{} was parsed",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_builder_non_finite_float() {
+        for value in [f64::NAN, f64::INFINITY] {
+            let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+                .add_field("temperature", value)
+                .build();
+            assert!(query.is_err(), "Non-finite float was accepted");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "use-serde")]
+    fn test_write_builder_json_rejects_composites() {
+        use serde_json::json;
+
+        for value in [json!(null), json!([1, 2]), json!({ "celsius": 5 })] {
+            let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+                .add_field_value("temperature", value);
+            assert!(query.is_err(), "Composite or null value was accepted");
+        }
+    }
+
+    #[test]
+    fn test_write_builder_display() {
+        let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field("temperature", 82)
+            .add_tag("location", "us-midwest");
+
+        assert_eq!(
+            query.to_string(),
+            "weather,location=\"us-midwest\" temperature=82i 11"
+        );
+        assert_eq!(query.build().unwrap(), query.to_string());
+    }
+
+    #[test]
+    fn test_write_builder_clone() {
+        let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field("temperature", 82)
+            .add_tag("location", "us-midwest");
+        let cloned = query.clone();
+
+        assert_eq!(query, cloned);
+        assert_ne!(query, cloned.add_field("wind_strength", 3.7));
+    }
+
     #[test]
     fn test_correct_query_type() {
         use crate::query::QueryType;
@@ -239,4 +1056,146 @@ mod tests {
 
         assert_eq!(query.get_type(), QueryType::WriteQuery);
     }
+
+    #[test]
+    fn test_write_builder_reserved_field_key() {
+        let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field("time", 82)
+            .build();
+
+        assert!(query.is_err(), "Query used reserved field key");
+    }
+
+    #[test]
+    fn test_write_builder_reserved_tag_key() {
+        let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_field("temperature", 82)
+            .add_tag("time", "now")
+            .build();
+
+        assert!(query.is_err(), "Query used reserved tag key");
+    }
+
+    #[test]
+    fn test_precision() {
+        let precisions = vec![
+            (Timestamp::NOW, None, ""),
+            (
+                Timestamp::NANOSECONDS(1),
+                Some(Precision::NANOSECONDS),
+                "ns",
+            ),
+            (
+                Timestamp::MICROSECONDS(1),
+                Some(Precision::MICROSECONDS),
+                "u",
+            ),
+            (
+                Timestamp::MILLISECONDS(1),
+                Some(Precision::MILLISECONDS),
+                "ms",
+            ),
+            (Timestamp::SECONDS(1), Some(Precision::SECONDS), "s"),
+            (Timestamp::MINUTES(1), Some(Precision::MINUTES), "m"),
+            (Timestamp::HOURS(1), Some(Precision::HOURS), "h"),
+        ];
+
+        for (timestamp, precision, parameter) in precisions {
+            let query =
+                InfluxDbQuery::write_query(timestamp, "weather").add_field("temperature", 82);
+            assert_eq!(query.precision(), precision);
+            assert_eq!(query.get_precision(), parameter);
+        }
+    }
+
+    #[test]
+    fn test_byte_len() {
+        let query = InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .add_tag("location", "\"Berlin\"")
+            .add_field("temperature", 82);
+
+        assert_eq!(
+            query.byte_len().unwrap(),
+            query.build().unwrap().get().len()
+        );
+        assert!(InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+            .byte_len()
+            .is_err());
+    }
+
+    #[test]
+    fn test_batch_byte_len() {
+        let batch = vec![
+            InfluxDbQuery::write_query(Timestamp::HOURS(1), "weather").add_field("temperature", 82),
+            InfluxDbQuery::write_query(Timestamp::HOURS(2), "weather").add_field("temperature", 84),
+            InfluxDbQuery::write_query(Timestamp::HOURS(3), "weather").add_field("temperature", 86),
+        ];
+        let body = chunk_batch(&batch, usize::MAX).unwrap();
+
+        assert_eq!(body.len(), 1);
+        assert_eq!(batch_byte_len(&batch).unwrap(), body[0].len());
+        assert_eq!(batch_byte_len(&[]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_chunk_batch_fits() {
+        let queries = vec![
+            InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+                .add_field("temperature", 82),
+            InfluxDbQuery::write_query(Timestamp::HOURS(12), "weather")
+                .add_field("temperature", 84),
+        ];
+
+        let chunks = chunk_batch(&queries, 1_000).unwrap();
+        assert_eq!(
+            chunks,
+            vec!["weather temperature=82i 11\nweather temperature=84i 12"]
+        );
+    }
+
+    #[test]
+    fn test_chunk_batch_split() {
+        let queries = (0..10)
+            .map(|hour| {
+                InfluxDbQuery::write_query(Timestamp::HOURS(hour), "weather")
+                    .add_field("temperature", 82)
+            })
+            .collect::<Vec<_>>();
+
+        // every line is 25 bytes long, so three lines and two newlines fit into 80 bytes
+        let chunks = chunk_batch(&queries, 80).unwrap();
+        assert_eq!(chunks.len(), 4);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= 80));
+        assert_eq!(chunks.join("\n").lines().count(), 10);
+        assert_eq!(chunks[3], "weather temperature=82i 9");
+    }
+
+    #[test]
+    fn test_chunk_batch_oversized_point() {
+        let queries = vec![
+            InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+                .add_field("temperature", 82),
+            InfluxDbQuery::write_query(Timestamp::HOURS(12), "weather")
+                .add_field("temperature", 84),
+        ];
+
+        let chunks = chunk_batch(&queries, 10).unwrap();
+        assert_eq!(
+            chunks,
+            vec!["weather temperature=82i 11", "weather temperature=84i 12"]
+        );
+    }
+
+    #[test]
+    fn test_chunk_batch_invalid() {
+        assert!(chunk_batch(&[], 1_000).is_err());
+
+        let mixed_precision = vec![
+            InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather")
+                .add_field("temperature", 82),
+            InfluxDbQuery::write_query(Timestamp::SECONDS(12), "weather")
+                .add_field("temperature", 84),
+        ];
+        assert!(chunk_batch(&mixed_precision, 1_000).is_err());
+    }
 }
@@ -1,9 +1,16 @@
 extern crate influxdb;
 
 use futures::prelude::*;
+use influxdb::client::metrics::InfluxDbMetrics;
 use influxdb::client::InfluxDbClient;
-use influxdb::error::InfluxDbError;
+use influxdb::error::{ConnectionErrorKind, InfluxDbError};
 use influxdb::query::{InfluxDbQuery, Timestamp};
+use reqwest::StatusCode;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 use tokio::runtime::current_thread::Runtime;
 
 fn get_runtime() -> Runtime {
@@ -43,6 +50,65 @@ where
     get_runtime().block_on(create_client(test_name).query(&InfluxDbQuery::raw_read_query(query)))
 }
 
+/// Starts a server answering a single request with the raw HTTP `response`
+fn serve_once(response: &'static str) -> SocketAddr {
+    serve_once_recording(response).0
+}
+
+/// Starts a server answering a single request with the raw HTTP `response`, sending the request to the receiver
+fn serve_once_recording(response: &'static str) -> (SocketAddr, mpsc::Receiver<String>) {
+    serve_recording(vec![response])
+}
+
+/// Starts a server answering one request per connection with the raw HTTP `responses` in order, sending each
+/// request to the receiver
+fn serve_recording(responses: Vec<&'static str>) -> (SocketAddr, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for response in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let request = read_request(&mut stream);
+            stream.write_all(response.as_bytes()).unwrap();
+            let _ = sender.send(request);
+        }
+    });
+    (addr, receiver)
+}
+
+/// Reads the head of a request and as much of its body as its `Content-Length` announces
+fn read_request(stream: &mut TcpStream) -> String {
+    let mut request = Vec::new();
+    let mut buf = [0; 1024];
+    let mut body_start = None;
+    loop {
+        if body_start.is_none() {
+            body_start = request
+                .windows(4)
+                .position(|window| window == b"\r\n\r\n")
+                .map(|position| position + 4);
+        }
+        if let Some(body_start) = body_start {
+            let head = String::from_utf8_lossy(&request[..body_start]).to_lowercase();
+            let length = head
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .and_then(|length| length.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            if request.len() >= body_start + length {
+                break;
+            }
+        }
+        let read = stream.read(&mut buf).unwrap();
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..read]);
+    }
+    String::from_utf8_lossy(&request).into_owned()
+}
+
 #[test]
 /// INTEGRATION TEST
 ///
@@ -63,6 +129,55 @@ fn test_ping_influx_db() {
     println!("build: {} version: {}", build, version);
 }
 
+#[test]
+/// INTEGRATION TEST
+///
+/// This test case tests a ping timing out against a server which accepts connections but never answers
+fn test_ping_with_timeout() {
+    // keep the listener alive, so connections are accepted into the backlog but never answered
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = InfluxDbClient::new(
+        format!("http://{}", listener.local_addr().unwrap()),
+        "test_ping_with_timeout",
+    );
+    let result =
+        get_runtime().block_on(client.ping_with_timeout(std::time::Duration::from_millis(200)));
+    match result {
+        Err(InfluxDbError::ConnectionError { kind, .. }) => {
+            assert_eq!(kind, ConnectionErrorKind::Timeout)
+        }
+        _ => assert!(false, format!("Should be a timeout: {:?}", result)),
+    }
+}
+
+#[test]
+/// INTEGRATION TEST
+///
+/// This test case tests asking for the server version when the ping response lacks the InfluxDB headers,
+/// e.g. because a proxy answered instead of InfluxDB
+fn test_server_version_without_headers() {
+    let addr = serve_once("HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n");
+    let client = InfluxDbClient::new(
+        format!("http://{}", addr),
+        "test_server_version_without_headers",
+    );
+    match get_runtime().block_on(client.server_version()) {
+        Err(InfluxDbError::ProtocolError { .. }) => {}
+        result => panic!("Should be a ProtocolError: {:?}", result),
+    }
+
+    let addr = serve_once("HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\n\r\n");
+    let client = InfluxDbClient::new(
+        format!("http://{}", addr),
+        "test_server_version_without_headers",
+    );
+    match get_runtime().block_on(client.detect_server_version()) {
+        Err(InfluxDbError::ProtocolError { .. }) => {}
+        Err(error) => panic!("Should be a ProtocolError: {:?}", error),
+        Ok(_) => panic!("Should be a ProtocolError"),
+    }
+}
+
 #[test]
 /// INTEGRATION TEST
 ///
@@ -78,7 +193,9 @@ fn test_connection_error() {
         format!("Should be an error: {}", read_result.unwrap_err())
     );
     match read_result {
-        Err(InfluxDbError::ConnectionError { .. }) => assert!(true),
+        Err(InfluxDbError::ConnectionError { kind, .. }) => {
+            assert_eq!(kind, ConnectionErrorKind::ConnectionRefused)
+        }
         _ => assert!(
             false,
             format!(
@@ -524,3 +641,324 @@ fn test_wrong_query_errors() {
         "Should only build SELECT and SHOW queries."
     );
 }
+
+#[test]
+#[cfg(feature = "use-serde")]
+/// INTEGRATION TEST
+///
+/// This test case tests whether epoch timestamps are converted into a `DateTime` when deserializing
+fn test_json_query_epoch_datetime() {
+    use chrono::{DateTime, TimeZone, Utc};
+    use influxdb::query::Precision;
+    use serde::Deserialize;
+
+    let test_name = "test_json_query_epoch_datetime";
+    create_db(test_name).expect("could not setup db");
+    let _run_on_drop = RunOnDrop {
+        closure: Box::new(|| {
+            delete_db("test_json_query_epoch_datetime").expect("could not clean up db");
+        }),
+    };
+
+    let client = create_client(test_name);
+    let write_query =
+        InfluxDbQuery::write_query(Timestamp::MILLISECONDS(1_568_106_310_123), "weather")
+            .add_field("temperature", 82);
+    let write_result = get_runtime().block_on(client.query(&write_query));
+    assert!(
+        write_result.is_ok(),
+        format!("Should be no error: {}", write_result.unwrap_err())
+    );
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Weather {
+        time: DateTime<Utc>,
+        temperature: i32,
+    }
+
+    let query =
+        InfluxDbQuery::raw_read_query("SELECT * FROM weather").with_epoch(Precision::MILLISECONDS);
+    let future = client
+        .json_query(query)
+        .and_then(|mut db_result| db_result.deserialize_next::<Weather>());
+    let result = get_runtime().block_on(future);
+
+    assert!(
+        result.is_ok(),
+        format!("We couldn't read from the DB: {}", result.unwrap_err())
+    );
+
+    assert_eq!(
+        result.unwrap().series[0].values[0],
+        Weather {
+            time: Utc.timestamp_opt(1_568_106_310, 123_000_000).unwrap(),
+            temperature: 82
+        }
+    );
+
+    delete_db(test_name).expect("could not clean up db");
+}
+
+#[test]
+/// INTEGRATION TEST
+///
+/// This test case tests whether a batch exceeding the maximum batch size is written in multiple requests
+fn test_batch_write_split() {
+    let test_name = "test_batch_write_split";
+    create_db(test_name).expect("could not setup db");
+    let _run_on_drop = RunOnDrop {
+        closure: Box::new(|| {
+            delete_db("test_batch_write_split").expect("could not clean up db");
+        }),
+    };
+
+    let client = create_client(test_name).with_max_batch_size(80);
+    let queries = (0..10)
+        .map(|hour| {
+            InfluxDbQuery::write_query(Timestamp::HOURS(hour), "weather")
+                .add_field("temperature", 82)
+        })
+        .collect::<Vec<_>>();
+    let write_result = get_runtime().block_on(client.batch_write(&queries));
+    assert!(
+        write_result.is_ok(),
+        format!("Should be no error: {}", write_result.unwrap_err())
+    );
+
+    let chunk_results = write_result.unwrap();
+    assert_eq!(chunk_results.len(), 4);
+    assert!(chunk_results.iter().all(|result| result.is_ok()));
+
+    let read_query = InfluxDbQuery::raw_read_query("SELECT COUNT(temperature) FROM weather");
+    let read_result = get_runtime().block_on(client.query(&read_query));
+    assert!(
+        read_result.unwrap().contains("10"),
+        "All points should have been written"
+    );
+
+    delete_db(test_name).expect("could not clean up db");
+}
+
+#[derive(Default)]
+struct RecordingMetrics {
+    started: Mutex<usize>,
+    ended: Mutex<Vec<(Option<StatusCode>, usize)>>,
+}
+
+impl InfluxDbMetrics for RecordingMetrics {
+    fn on_request_start(&self) {
+        *self.started.lock().unwrap() += 1;
+    }
+
+    fn on_request_end(&self, status: Option<StatusCode>, _duration: Duration, bytes: usize) {
+        self.ended.lock().unwrap().push((status, bytes));
+    }
+}
+
+#[test]
+/// INTEGRATION TEST
+///
+/// This test case tests whether the metrics hook is called with the status and body size of a response
+fn test_metrics_hook() {
+    let addr = serve_once(
+        "HTTP/1.1 200 OK\r\nContent-Length: 14\r\nConnection: close\r\n\r\n{\"results\":[]}",
+    );
+    let metrics = Arc::new(RecordingMetrics::default());
+    let client = InfluxDbClient::new(format!("http://{}", addr), "test_metrics_hook")
+        .with_metrics(metrics.clone());
+
+    let read_query = InfluxDbQuery::raw_read_query("SELECT * FROM weather");
+    let read_result = get_runtime().block_on(client.query(&read_query));
+    assert_eq!(read_result.unwrap(), r#"{"results":[]}"#);
+    assert_eq!(*metrics.started.lock().unwrap(), 1);
+    assert_eq!(
+        *metrics.ended.lock().unwrap(),
+        vec![(Some(StatusCode::OK), 14)]
+    );
+}
+
+#[test]
+/// INTEGRATION TEST
+///
+/// This test case tests that only the start of a large error page of a proxy is kept
+fn test_large_error_page() {
+    let page = format!("<html>Bad Gateway{}</html>", " ".repeat(1_000_000));
+    let response = format!(
+        "HTTP/1.1 502 Bad Gateway\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        page.len(),
+        page
+    );
+    let addr = serve_once(Box::leak(response.into_boxed_str()));
+    let client = InfluxDbClient::new(format!("http://{}", addr), "test_large_error_page")
+        .with_max_error_body_size(100);
+
+    let read_query = InfluxDbQuery::raw_read_query("SELECT * FROM weather");
+    match get_runtime().block_on(client.query(&read_query)) {
+        Err(InfluxDbError::ProtocolError { error }) => {
+            assert!(error.starts_with("unexpected response (502 Bad Gateway): <html>Bad Gateway"));
+            assert!(error.len() <= 100 + "…".len());
+        }
+        result => panic!("Should be a ProtocolError: {:?}", result),
+    }
+}
+
+#[test]
+/// INTEGRATION TEST
+///
+/// This test case tests sending a raw request to a custom endpoint, including the credentials of the client
+fn test_send_raw() {
+    let (addr, request) =
+        serve_once_recording("HTTP/1.1 200 OK\r\nContent-Length: 7\r\n\r\n{\"a\":1}");
+    let client = InfluxDbClient::new(format!("http://{}", addr), "test_send_raw")
+        .with_auth("admin", "password");
+
+    let result = get_runtime().block_on(client.send_raw(
+        influxdb::query::Method::Get,
+        "debug/vars",
+        &[("pretty", "true")],
+        None,
+    ));
+    assert_eq!(result.unwrap(), "{\"a\":1}");
+
+    let request = request.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(
+        request.starts_with("GET /debug/vars?u=admin&p=password&pretty=true HTTP/1.1\r\n"),
+        "Unexpected request: {}",
+        request
+    );
+}
+
+#[test]
+/// INTEGRATION TEST
+///
+/// This test case tests sending a request using a client setting TCP_NODELAY
+fn test_tcp_nodelay() {
+    let addr = serve_once(
+        "HTTP/1.1 200 OK\r\nContent-Length: 14\r\nConnection: close\r\n\r\n{\"results\":[]}",
+    );
+    let client =
+        InfluxDbClient::new(format!("http://{}", addr), "test_tcp_nodelay").with_tcp_nodelay(true);
+
+    let read_query = InfluxDbQuery::raw_read_query("SELECT * FROM weather");
+    let result = get_runtime().block_on(client.query(&read_query));
+    assert_eq!(result.unwrap(), r#"{"results":[]}"#);
+}
+
+#[test]
+/// INTEGRATION TEST
+///
+/// This test case tests writing to several databases, sending one request per database
+fn test_write_to_databases() {
+    let (addr, requests) = serve_recording(vec!["HTTP/1.1 204 No Content\r\n\r\n"; 2]);
+    let client = InfluxDbClient::new(format!("http://{}", addr), "test_write_to_databases");
+
+    let results = get_runtime()
+        .block_on(
+            client.write_to_databases(&[
+                (
+                    "tenant_a",
+                    InfluxDbQuery::write_query(Timestamp::HOURS(1), "weather")
+                        .add_field("temperature", 82),
+                ),
+                (
+                    "tenant_b",
+                    InfluxDbQuery::write_query(Timestamp::HOURS(1), "weather")
+                        .add_field("temperature", 64),
+                ),
+                (
+                    "tenant_a",
+                    InfluxDbQuery::write_query(Timestamp::HOURS(2), "weather")
+                        .add_field("temperature", 84),
+                ),
+            ]),
+        )
+        .unwrap();
+    assert_eq!(
+        results.keys().collect::<Vec<_>>(),
+        vec!["tenant_a", "tenant_b"]
+    );
+    for results in results.values() {
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].as_ref().unwrap(), "");
+    }
+
+    // the requests of both databases are sent concurrently, so they arrive in any order
+    let mut requests: Vec<String> = (0..2)
+        .map(|_| requests.recv_timeout(Duration::from_secs(5)).unwrap())
+        .collect();
+    requests.sort();
+    assert!(
+        requests[0].starts_with("POST /write?db=tenant_a&precision=h HTTP/1.1\r\n"),
+        "Unexpected request: {}",
+        requests[0]
+    );
+    assert!(requests[0].ends_with("\r\n\r\nweather temperature=82i 1\nweather temperature=84i 2"));
+    assert!(
+        requests[1].starts_with("POST /write?db=tenant_b&precision=h HTTP/1.1\r\n"),
+        "Unexpected request: {}",
+        requests[1]
+    );
+    assert!(requests[1].ends_with("\r\n\r\nweather temperature=64i 1"));
+}
+
+#[test]
+/// INTEGRATION TEST
+///
+/// This test case tests creating a missing database and retrying the write
+fn test_auto_create_database() {
+    let (addr, requests) = serve_recording(vec![
+        "HTTP/1.1 404 Not Found\r\nContent-Type: application/json\r\nContent-Length: 47\r\n\r\n{\"error\":\"database not found: \\\"auto_create\\\"\"}",
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 32\r\n\r\n{\"results\":[{\"statement_id\":0}]}",
+        "HTTP/1.1 204 No Content\r\n\r\n",
+    ]);
+    let client = InfluxDbClient::new(format!("http://{}", addr), "auto_create")
+        .with_auto_create_database(true);
+
+    let write_query =
+        InfluxDbQuery::write_query(Timestamp::HOURS(11), "weather").add_field("temperature", 82);
+    let result = get_runtime().block_on(client.query(&write_query));
+    assert_eq!(result.unwrap(), "");
+
+    let requests: Vec<String> = (0..3)
+        .map(|_| requests.recv_timeout(Duration::from_secs(5)).unwrap())
+        .collect();
+    for write in &[&requests[0], &requests[2]] {
+        assert!(
+            write.starts_with("POST /write?db=auto_create&precision=h HTTP/1.1\r\n"),
+            "Unexpected request: {}",
+            write
+        );
+        assert!(write.ends_with("\r\n\r\nweather temperature=82i 11"));
+    }
+    assert!(
+        requests[1].starts_with(
+            "POST /query?db=auto_create&q=CREATE+DATABASE+%22auto_create%22 HTTP/1.1\r\n"
+        ),
+        "Unexpected request: {}",
+        requests[1]
+    );
+}
+
+#[test]
+/// INTEGRATION TEST
+///
+/// This test case tests writing a single value using write_point
+fn test_write_point() {
+    let (addr, request) = serve_once_recording("HTTP/1.1 204 No Content\r\n\r\n");
+    let client = InfluxDbClient::new(format!("http://{}", addr), "test_write_point");
+
+    let result = get_runtime().block_on(client.write_point(
+        "weather",
+        "temperature",
+        82,
+        Timestamp::HOURS(11),
+    ));
+    assert_eq!(result.unwrap(), "");
+
+    let request = request.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(
+        request.starts_with("POST /write?db=test_write_point&precision=h HTTP/1.1\r\n"),
+        "Unexpected request: {}",
+        request
+    );
+}